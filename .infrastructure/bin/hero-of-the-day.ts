@@ -9,6 +9,15 @@ const app = new cdk.App();
 let APP_NAME = 'hero-of-the-day';
 let HOSTED_DOMAIN = process.env.HOSTED_DOMAIN;
 let MS_CLIENT_ID = process.env.MS_CLIENT_ID;
+// Only needed by heroes on Microsoft Teams.
+let MS_TENANT_ID = process.env.MS_TENANT_ID ?? '';
+let ADMIN_EMAILS = process.env.ADMIN_EMAILS ?? '';
+// E-mail notifications are off without SMTP_HOST.
+let SMTP_HOST = process.env.SMTP_HOST ?? '';
+let SMTP_PORT = process.env.SMTP_PORT ?? '';
+let SMTP_TLS = process.env.SMTP_TLS ?? '';
+let SMTP_FROM = process.env.SMTP_FROM ?? '';
+let SMTP_USERNAME = process.env.SMTP_USERNAME ?? '';
 if (!HOSTED_DOMAIN) {
   Annotations.of(app).addError('Could not determine HOSTED_DOMAIN');
   throw Error('Could not determine HOSTED_DOMAIN')
@@ -22,9 +31,26 @@ new HeroOfTheDayStack(app, 'HeroOfTheDayStack', {
   APP_NAME,
   HOSTED_DOMAIN,
   MS_CLIENT_ID,
+  MS_TENANT_ID,
   HERO_TABLE: `${APP_NAME}-hero`,
+  HERO_TEMPLATE_TABLE: `${APP_NAME}-hero-template`,
   USER_TABLE: `${APP_NAME}-user`,
   SCHEDULE_TABLE: `${APP_NAME}-schedule`,
   PUNCH_CLOCK_TABLE: `${APP_NAME}-punch-clock`,
-  SLACK_TOKEN_PARAMETER: `/${APP_NAME}/slack-token`
+  PUNCH_CLOCK_LEDGER_TABLE: `${APP_NAME}-punch-clock-ledger`,
+  SLACK_USER_TABLE: `${APP_NAME}-slack-user`,
+  WEBHOOK_TABLE: `${APP_NAME}-webhook`,
+  WEBHOOK_DELIVERY_TABLE: `${APP_NAME}-webhook-delivery`,
+  JOB_TABLE: `${APP_NAME}-job`,
+  COMPENSATION_TABLE: `${APP_NAME}-compensation`,
+  SLACK_TOKEN_PARAMETER: `/${APP_NAME}/slack-token`,
+  SLACK_SIGNING_SECRET_PARAMETER: `/${APP_NAME}/slack-signing-secret`,
+  TEAMS_CLIENT_SECRET_PARAMETER: `/${APP_NAME}/teams-client-secret`,
+  ADMIN_EMAILS,
+  SMTP_HOST,
+  SMTP_PORT,
+  SMTP_TLS,
+  SMTP_FROM,
+  SMTP_USERNAME,
+  SMTP_PASSWORD_PARAMETER: `/${APP_NAME}/smtp-password`
 });
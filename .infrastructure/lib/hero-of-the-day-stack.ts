@@ -5,6 +5,9 @@ import * as dynamodb from 'aws-cdk-lib/aws-dynamodb';
 import {AttributeType, BillingMode, ITable} from 'aws-cdk-lib/aws-dynamodb';
 import * as apigw from 'aws-cdk-lib/aws-apigateway';
 import {IFunction} from 'aws-cdk-lib/aws-lambda';
+import {SqsEventSource} from 'aws-cdk-lib/aws-lambda-event-sources';
+import {IQueue, Queue} from 'aws-cdk-lib/aws-sqs';
+import {PolicyStatement} from 'aws-cdk-lib/aws-iam';
 import {IRule, Rule, Schedule} from 'aws-cdk-lib/aws-events';
 import {LambdaFunction} from 'aws-cdk-lib/aws-events-targets';
 import {IParameter, StringParameter} from 'aws-cdk-lib/aws-ssm';
@@ -12,12 +15,29 @@ import {IParameter, StringParameter} from 'aws-cdk-lib/aws-ssm';
 interface Environment {
   readonly APP_NAME: string;
   readonly HERO_TABLE: string,
+  readonly HERO_TEMPLATE_TABLE: string,
   readonly PUNCH_CLOCK_TABLE: string,
+  readonly PUNCH_CLOCK_LEDGER_TABLE: string,
   readonly USER_TABLE: string,
   readonly SCHEDULE_TABLE: string,
+  readonly SLACK_USER_TABLE: string,
+  readonly WEBHOOK_TABLE: string,
+  readonly WEBHOOK_DELIVERY_TABLE: string,
+  readonly JOB_TABLE: string,
+  readonly COMPENSATION_TABLE: string,
   readonly HOSTED_DOMAIN: string,
   readonly MS_CLIENT_ID: string,
-  readonly SLACK_TOKEN_PARAMETER: string
+  readonly MS_TENANT_ID: string,
+  readonly SLACK_TOKEN_PARAMETER: string,
+  readonly SLACK_SIGNING_SECRET_PARAMETER: string,
+  readonly TEAMS_CLIENT_SECRET_PARAMETER: string,
+  readonly SMTP_HOST: string,
+  readonly SMTP_PORT: string,
+  readonly SMTP_TLS: string,
+  readonly SMTP_FROM: string,
+  readonly SMTP_USERNAME: string,
+  readonly SMTP_PASSWORD_PARAMETER: string,
+  readonly ADMIN_EMAILS: string
 }
 
 export class HeroOfTheDayStack extends Stack {
@@ -27,31 +47,73 @@ export class HeroOfTheDayStack extends Stack {
     this.env = props;
 
     let heroTable: ITable = this.heroTable();
+    let heroTemplateTable: ITable = this.heroTemplateTable();
     let userTable: ITable = this.userTable();
     let scheduleTable: ITable = this.scheduleTable();
     let punchClockTable: ITable = this.punchClockTable();
+    let punchClockLedgerTable: ITable = this.punchClockLedgerTable();
+    let slackUserTable: ITable = this.slackUserTable();
+    let webhookTable: ITable = this.webhookTable();
+    let webhookDeliveryTable: ITable = this.webhookDeliveryTable();
+    let jobTable: ITable = this.jobTable();
+    let compensationTable: ITable = this.compensationTable();
+    let recalculationQueue: IQueue = this.recalculationQueue();
 
     let slackParameter = StringParameter.fromStringParameterName(this, 'SlackParameter', this.env.SLACK_TOKEN_PARAMETER);
+    let slackSigningSecretParameter = StringParameter.fromStringParameterName(this, 'SlackSigningSecretParameter', this.env.SLACK_SIGNING_SECRET_PARAMETER);
+    let teamsClientSecretParameter = StringParameter.fromStringParameterName(this, 'TeamsClientSecretParameter', this.env.TEAMS_CLIENT_SECRET_PARAMETER);
+    let smtpPasswordParameter = StringParameter.fromStringParameterName(this, 'SmtpPasswordParameter', this.env.SMTP_PASSWORD_PARAMETER);
 
     let authorizer: IFunction = this.authorizer(heroTable, userTable);
     let heroListFn: IFunction = this.heroList(heroTable);
     let heroGetFn: IFunction = this.heroGet(heroTable);
-    let heroPutFn: IFunction = this.heroPut(heroTable);
+    let heroPutFn: IFunction = this.heroPut(heroTable, webhookTable, webhookDeliveryTable);
+    let heroCloneFn: IFunction = this.heroClone(heroTable, heroTemplateTable, scheduleTable);
+    let heroTemplatePutFn: IFunction = this.heroTemplatePut(heroTable, heroTemplateTable);
+    let heroTemplateListFn: IFunction = this.heroTemplateList(heroTemplateTable);
     let userCreateFn: IFunction = this.userCreate(userTable);
     let scheduleGetFn: IFunction = this.scheduleGet(scheduleTable);
-    let scheduleUpdateFn: IFunction = this.scheduleUpdate(scheduleTable, heroTable, slackParameter);
-    let slackUsergroupUsersUpdateFn: IFunction = this.slackUsergroupUsersUpdate(scheduleTable, heroTable, punchClockTable, slackParameter);
-    let heroMemberDeleteFn: IFunction = this.heroMemeberDelete(heroTable);
-    let heroDeleteFn: IFunction = this.heroDelete(heroTable, scheduleTable);
-    let punchClockRecalculateFn: IFunction = this.punchClockRecalculate(scheduleTable, punchClockTable, slackParameter);
+    let scheduleUpdateFn: IFunction = this.scheduleUpdate(scheduleTable, heroTable, userTable, slackUserTable, webhookTable, webhookDeliveryTable, slackParameter, teamsClientSecretParameter, smtpPasswordParameter);
+    let slackUsergroupUsersUpdateFn: IFunction = this.slackUsergroupUsersUpdate(scheduleTable, heroTable, userTable, punchClockTable, punchClockLedgerTable, slackUserTable, webhookTable, webhookDeliveryTable, slackParameter, teamsClientSecretParameter);
+    let heroMemberDeleteFn: IFunction = this.heroMemeberDelete(heroTable, webhookTable, webhookDeliveryTable);
+    let heroDeleteFn: IFunction = this.heroDelete(heroTable);
+    let heroRestoreFn: IFunction = this.heroRestore(heroTable);
+    let heroPurgeFn: IFunction = this.heroPurge(heroTable, scheduleTable, punchClockTable, punchClockLedgerTable, webhookTable);
+    let punchClockRecalculateFn: IFunction = this.punchClockRecalculate(heroTable, scheduleTable, punchClockTable, punchClockLedgerTable, slackParameter);
     let punchClockStatsFn: IFunction = this.punchClockStats(punchClockTable, scheduleTable, slackParameter);
-    let recalculatePunchClockFn: IFunction = this.recalculatePunchClock(heroTable, scheduleTable, punchClockTable);
+    let recalculatePunchClockFn: IFunction = this.recalculatePunchClock(jobTable, recalculationQueue);
+    this.recalculatePunchClockWorker(heroTable, scheduleTable, punchClockTable, punchClockLedgerTable, jobTable, recalculationQueue);
+    let recalculatePunchClockStatusFn: IFunction = this.recalculatePunchClockStatus(jobTable);
+    let punchClockLedgerFn: IFunction = this.punchClockLedger(punchClockLedgerTable);
     let userUpdateSeenReleaseNotesFn: IFunction = this.userUpdateSeenReleaseNotes(userTable);
+    let userPreferencesGetFn: IFunction = this.userPreferencesGet(userTable);
+    let userPreferencesPutFn: IFunction = this.userPreferencesPut(userTable);
     let userGetFn: IFunction = this.userGet(userTable);
+    let memberWorkloadFn: IFunction = this.memberWorkload(heroTable, scheduleTable, punchClockTable);
+    let compensationGetFn: IFunction = this.compensationGet(heroTable, scheduleTable, compensationTable);
+    let compensationSignOffFn: IFunction = this.compensationSignOff(heroTable, scheduleTable, compensationTable);
+    let slackCommandFn: IFunction = this.slackCommand(scheduleTable, heroTable, slackUserTable, slackParameter, slackSigningSecretParameter);
+    let slackInteractivityFn: IFunction = this.slackInteractivity(scheduleTable, heroTable, userTable, slackUserTable, slackParameter, slackSigningSecretParameter);
+
+    let shiftRemindersFn: IFunction = this.shiftReminders(scheduleTable, heroTable, userTable, slackUserTable, slackParameter, smtpPasswordParameter);
+    let slackWeeklyDigestFn: IFunction = this.slackWeeklyDigest(scheduleTable, heroTable, slackUserTable, slackParameter);
+    let coverageGetFn: IFunction = this.coverageGet(scheduleTable, heroTable);
+    let coverageAlertFn: IFunction = this.coverageAlert(scheduleTable, heroTable, slackParameter);
+    let slackUserCacheRefreshFn: IFunction = this.slackUserCacheRefresh(heroTable, slackUserTable, slackParameter);
+    let slackUsergroupDriftFn: IFunction = this.slackUsergroupDrift(scheduleTable, heroTable, slackUserTable, slackParameter, teamsClientSecretParameter);
+    let webhookListFn: IFunction = this.webhookList(webhookTable);
+    let webhookCreateFn: IFunction = this.webhookCreate(heroTable, webhookTable);
+    let webhookDeleteFn: IFunction = this.webhookDelete(heroTable, webhookTable);
+    let webhookDeliveriesFn: IFunction = this.webhookDeliveries(heroTable, webhookTable, webhookDeliveryTable);
+    let webhookRetryFn: IFunction = this.webhookRetry(webhookTable, webhookDeliveryTable);
 
     this.slackUsergroupUsersUpdateScheduleRule(slackUsergroupUsersUpdateFn);
+    this.shiftRemindersScheduleRule(shiftRemindersFn);
+    this.slackWeeklyDigestScheduleRule(slackWeeklyDigestFn);
+    this.coverageAlertScheduleRule(coverageAlertFn);
+    this.webhookRetryScheduleRule(webhookRetryFn);
 
-    this.apiGateway(authorizer, heroListFn, heroGetFn, userCreateFn, scheduleGetFn, scheduleUpdateFn, heroPutFn, heroMemberDeleteFn, heroDeleteFn, punchClockRecalculateFn, punchClockStatsFn, recalculatePunchClockFn, userUpdateSeenReleaseNotesFn, userGetFn);
+    this.apiGateway(authorizer, heroListFn, heroGetFn, userCreateFn, scheduleGetFn, scheduleUpdateFn, heroPutFn, heroMemberDeleteFn, heroDeleteFn, punchClockRecalculateFn, punchClockStatsFn, recalculatePunchClockFn, recalculatePunchClockStatusFn, punchClockLedgerFn, userUpdateSeenReleaseNotesFn, userPreferencesGetFn, userPreferencesPutFn, userGetFn, memberWorkloadFn, slackCommandFn, slackInteractivityFn, coverageGetFn, slackUserCacheRefreshFn, slackUsergroupDriftFn, webhookListFn, webhookCreateFn, webhookDeleteFn, webhookDeliveriesFn, compensationGetFn, compensationSignOffFn, heroCloneFn, heroTemplatePutFn, heroTemplateListFn, heroRestoreFn, heroPurgeFn);
   }
 
   slackUsergroupUsersUpdateScheduleRule(slackUsergroupUsersUpdateFn: IFunction): IRule {
@@ -61,6 +123,35 @@ export class HeroOfTheDayStack extends Stack {
     });
   }
 
+  // Hourly, so reminders held back by quiet hours go out once the assignee's quiet hours end.
+  shiftRemindersScheduleRule(shiftRemindersFn: IFunction): IRule {
+    return new Rule(this, 'ShiftRemindersScheduleRule', {
+      schedule: Schedule.cron({minute: '0', hour: '7-23'}),
+      targets: [new LambdaFunction(shiftRemindersFn)],
+    });
+  }
+
+  slackWeeklyDigestScheduleRule(slackWeeklyDigestFn: IFunction): IRule {
+    return new Rule(this, 'SlackWeeklyDigestScheduleRule', {
+      schedule: Schedule.cron({minute: '0', hour: '6', weekDay: 'MON'}),
+      targets: [new LambdaFunction(slackWeeklyDigestFn)],
+    });
+  }
+
+  coverageAlertScheduleRule(coverageAlertFn: IFunction): IRule {
+    return new Rule(this, 'CoverageAlertScheduleRule', {
+      schedule: Schedule.cron({minute: '0', hour: '8', weekDay: 'THU'}),
+      targets: [new LambdaFunction(coverageAlertFn)],
+    });
+  }
+
+  webhookRetryScheduleRule(webhookRetryFn: IFunction): IRule {
+    return new Rule(this, 'WebhookRetryScheduleRule', {
+      schedule: Schedule.rate(Duration.minutes(5)),
+      targets: [new LambdaFunction(webhookRetryFn)],
+    });
+  }
+
   heroTable(): ITable {
     return new dynamodb.Table(this, this.env.HERO_TABLE, {
       tableName: this.env.HERO_TABLE,
@@ -72,6 +163,17 @@ export class HeroOfTheDayStack extends Stack {
     });
   }
 
+  heroTemplateTable(): ITable {
+    return new dynamodb.Table(this, this.env.HERO_TEMPLATE_TABLE, {
+      tableName: this.env.HERO_TEMPLATE_TABLE,
+      partitionKey: {
+        name: 'name',
+        type: AttributeType.STRING
+      },
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+  }
+
   userTable(): ITable {
     return new dynamodb.Table(this, this.env.USER_TABLE, {
       tableName: this.env.USER_TABLE,
@@ -99,6 +201,21 @@ export class HeroOfTheDayStack extends Stack {
     return table;
   }
 
+  punchClockLedgerTable(): ITable {
+    return new dynamodb.Table(this, this.env.PUNCH_CLOCK_LEDGER_TABLE, {
+      tableName: this.env.PUNCH_CLOCK_LEDGER_TABLE,
+      partitionKey: {
+        name: 'hero',
+        type: AttributeType.STRING
+      },
+      sortKey: {
+        name: 'entry',
+        type: AttributeType.STRING
+      },
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+  }
+
   scheduleTable(): ITable {
     return new dynamodb.Table(this, this.env.SCHEDULE_TABLE, {
       tableName: this.env.SCHEDULE_TABLE,
@@ -114,7 +231,116 @@ export class HeroOfTheDayStack extends Stack {
     });
   }
 
-  createFn(id: string, name: string, timeout: Duration = Duration.seconds(3)): IFunction {
+  slackUserTable(): ITable {
+    return new dynamodb.Table(this, this.env.SLACK_USER_TABLE, {
+      tableName: this.env.SLACK_USER_TABLE,
+      partitionKey: {
+        name: 'email',
+        type: AttributeType.STRING
+      },
+      sortKey: {
+        name: 'workspace',
+        type: AttributeType.STRING
+      },
+      timeToLiveAttribute: 'expires_at',
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+  }
+
+  jobTable(): ITable {
+    return new dynamodb.Table(this, this.env.JOB_TABLE, {
+      tableName: this.env.JOB_TABLE,
+      partitionKey: {
+        name: 'id',
+        type: AttributeType.STRING
+      },
+      timeToLiveAttribute: 'expires_at',
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+  }
+
+  compensationTable(): ITable {
+    return new dynamodb.Table(this, this.env.COMPENSATION_TABLE, {
+      tableName: this.env.COMPENSATION_TABLE,
+      partitionKey: {
+        name: 'month',
+        type: AttributeType.STRING
+      },
+      sortKey: {
+        name: 'signed_off_at',
+        type: AttributeType.NUMBER
+      },
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+  }
+
+  // The visibility timeout has to exceed the timeout of the worker, failed jobs are retried twice.
+  recalculationQueue(): IQueue {
+    return new Queue(this, 'RecalculationQueue', {
+      queueName: `${this.env.APP_NAME}-recalculation`,
+      visibilityTimeout: Duration.minutes(16),
+      deadLetterQueue: {
+        maxReceiveCount: 3,
+        queue: new Queue(this, 'RecalculationDeadLetterQueue', {
+          queueName: `${this.env.APP_NAME}-recalculation-dead-letter`
+        })
+      }
+    });
+  }
+
+  webhookTable(): ITable {
+    return new dynamodb.Table(this, this.env.WEBHOOK_TABLE, {
+      tableName: this.env.WEBHOOK_TABLE,
+      partitionKey: {
+        name: 'hero',
+        type: AttributeType.STRING
+      },
+      sortKey: {
+        name: 'id',
+        type: AttributeType.STRING
+      },
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+  }
+
+  webhookDeliveryTable(): ITable {
+    let table = new dynamodb.Table(this, this.env.WEBHOOK_DELIVERY_TABLE, {
+      tableName: this.env.WEBHOOK_DELIVERY_TABLE,
+      partitionKey: {
+        name: 'subscription_id',
+        type: AttributeType.STRING
+      },
+      sortKey: {
+        name: 'delivery_id',
+        type: AttributeType.STRING
+      },
+      timeToLiveAttribute: 'expires_at',
+      billingMode: BillingMode.PAY_PER_REQUEST
+    });
+    // Only pending deliveries have a next attempt, the index holds just the ones to retry.
+    table.addGlobalSecondaryIndex({
+      indexName: 'status-next_attempt_at',
+      partitionKey: {
+        name: 'status',
+        type: AttributeType.STRING
+      },
+      sortKey: {
+        name: 'next_attempt_at',
+        type: AttributeType.NUMBER
+      }
+    });
+    return table;
+  }
+
+  // Tokens of further Slack workspaces are stored below the default token's parameter.
+  grantReadWorkspaceSlackTokens(fn: IFunction) {
+    fn.addToRolePolicy(new PolicyStatement({
+      actions: ['ssm:GetParameter'],
+      resources: [`arn:aws:ssm:${this.region}:${this.account}:parameter${this.env.SLACK_TOKEN_PARAMETER}/*`]
+    }));
+  }
+
+  createFn(id: string, name: string, timeout: Duration = Duration.seconds(3), environment: {[key: string]: string} = {}): IFunction {
     return new RustFunction(this, id, {
       manifestPath: `../lambdas/${name}`,
       functionName: `${this.env.APP_NAME}-${name}`,
@@ -122,12 +348,30 @@ export class HeroOfTheDayStack extends Stack {
       environment: {
         APP_NAME: this.env.APP_NAME,
         HERO_TABLE: this.env.HERO_TABLE,
+        HERO_TEMPLATE_TABLE: this.env.HERO_TEMPLATE_TABLE,
         USER_TABLE: this.env.USER_TABLE,
         PUNCH_CLOCK_TABLE: this.env.PUNCH_CLOCK_TABLE,
+        PUNCH_CLOCK_LEDGER_TABLE: this.env.PUNCH_CLOCK_LEDGER_TABLE,
         SCHEDULE_TABLE: this.env.SCHEDULE_TABLE,
+        SLACK_USER_TABLE: this.env.SLACK_USER_TABLE,
+        WEBHOOK_TABLE: this.env.WEBHOOK_TABLE,
+        WEBHOOK_DELIVERY_TABLE: this.env.WEBHOOK_DELIVERY_TABLE,
+        JOB_TABLE: this.env.JOB_TABLE,
+        COMPENSATION_TABLE: this.env.COMPENSATION_TABLE,
         HOSTED_DOMAIN: this.env.HOSTED_DOMAIN,
         MS_CLIENT_ID: this.env.MS_CLIENT_ID,
-        SLACK_TOKEN_PARAMETER: this.env.SLACK_TOKEN_PARAMETER
+        MS_TENANT_ID: this.env.MS_TENANT_ID,
+        SLACK_TOKEN_PARAMETER: this.env.SLACK_TOKEN_PARAMETER,
+        SLACK_SIGNING_SECRET_PARAMETER: this.env.SLACK_SIGNING_SECRET_PARAMETER,
+        TEAMS_CLIENT_SECRET_PARAMETER: this.env.TEAMS_CLIENT_SECRET_PARAMETER,
+        ADMIN_EMAILS: this.env.ADMIN_EMAILS,
+        SMTP_HOST: this.env.SMTP_HOST,
+        SMTP_PORT: this.env.SMTP_PORT,
+        SMTP_TLS: this.env.SMTP_TLS,
+        SMTP_FROM: this.env.SMTP_FROM,
+        SMTP_USERNAME: this.env.SMTP_USERNAME,
+        SMTP_PASSWORD_PARAMETER: this.env.SMTP_PASSWORD_PARAMETER,
+        ...environment
       }
     });
   }
@@ -151,9 +395,32 @@ export class HeroOfTheDayStack extends Stack {
     return fn;
   }
 
-  heroPut(table: ITable): IFunction {
-    let fn = this.createFn('HeroCreateFunction', 'hero-put');
+  heroPut(table: ITable, webhookTable: ITable, webhookDeliveryTable: ITable): IFunction {
+    let fn = this.createFn('HeroCreateFunction', 'hero-put', Duration.seconds(10));
     table.grantReadWriteData(fn);
+    webhookTable.grantReadData(fn);
+    webhookDeliveryTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  heroClone(heroTable: ITable, heroTemplateTable: ITable, scheduleTable: ITable): IFunction {
+    let fn = this.createFn('HeroCloneFunction', 'hero-clone', Duration.seconds(10));
+    heroTable.grantReadWriteData(fn);
+    heroTemplateTable.grantReadData(fn);
+    scheduleTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  heroTemplatePut(heroTable: ITable, heroTemplateTable: ITable): IFunction {
+    let fn = this.createFn('HeroTemplatePutFunction', 'hero-template-put');
+    heroTable.grantReadData(fn);
+    heroTemplateTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  heroTemplateList(heroTemplateTable: ITable): IFunction {
+    let fn = this.createFn('HeroTemplateListFunction', 'hero-template-list');
+    heroTemplateTable.grantReadData(fn);
     return fn;
   }
 
@@ -169,36 +436,134 @@ export class HeroOfTheDayStack extends Stack {
     return fn;
   } 
 
-  scheduleUpdate(scheduleTable: ITable, heroTable: ITable, slackParameter: IParameter): IFunction {
-    let fn = this.createFn('ScheduleUpdateFunction', 'schedule-update');
+  scheduleUpdate(scheduleTable: ITable, heroTable: ITable, userTable: ITable, slackUserTable: ITable, webhookTable: ITable, webhookDeliveryTable: ITable, slackParameter: IParameter, teamsClientSecretParameter: IParameter, smtpPasswordParameter: IParameter): IFunction {
+    let fn = this.createFn('ScheduleUpdateFunction', 'schedule-update', Duration.seconds(10));
     scheduleTable.grantReadWriteData(fn);
     heroTable.grantReadWriteData(fn);
+    userTable.grantReadData(fn);
+    smtpPasswordParameter.grantRead(fn);
+    slackUserTable.grantReadWriteData(fn);
+    webhookTable.grantReadData(fn);
+    webhookDeliveryTable.grantReadWriteData(fn);
     slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    teamsClientSecretParameter.grantRead(fn);
     return fn;
   }
 
-  slackUsergroupUsersUpdate(scheduleTable: ITable, heroTable: ITable, punchClockTable: ITable, slackParameter: IParameter): IFunction {
+  slackUsergroupUsersUpdate(scheduleTable: ITable, heroTable: ITable, userTable: ITable, punchClockTable: ITable, punchClockLedgerTable: ITable, slackUserTable: ITable, webhookTable: ITable, webhookDeliveryTable: ITable, slackParameter: IParameter, teamsClientSecretParameter: IParameter): IFunction {
     let fn = this.createFn('SlackUsergroupUsersUpdateFunction', 'slack-usergroup-users-update', Duration.seconds(50));
     scheduleTable.grantReadData(fn);
     heroTable.grantReadData(fn);
+    userTable.grantReadData(fn);
     slackParameter.grantRead(fn);
     punchClockTable.grantReadWriteData(fn);
+    punchClockLedgerTable.grantReadWriteData(fn);
+    slackUserTable.grantReadWriteData(fn);
+    webhookTable.grantReadData(fn);
+    webhookDeliveryTable.grantReadWriteData(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    teamsClientSecretParameter.grantRead(fn);
+    return fn;
+  }
+
+  shiftReminders(scheduleTable: ITable, heroTable: ITable, userTable: ITable, slackUserTable: ITable, slackParameter: IParameter, smtpPasswordParameter: IParameter): IFunction {
+    let fn = this.createFn('ShiftRemindersFunction', 'shift-reminders', Duration.seconds(50));
+    scheduleTable.grantReadWriteData(fn);
+    heroTable.grantReadData(fn);
+    userTable.grantReadData(fn);
+    smtpPasswordParameter.grantRead(fn);
+    slackUserTable.grantReadWriteData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    return fn;
+  }
+
+  slackWeeklyDigest(scheduleTable: ITable, heroTable: ITable, slackUserTable: ITable, slackParameter: IParameter): IFunction {
+    let fn = this.createFn('SlackWeeklyDigestFunction', 'slack-weekly-digest', Duration.seconds(50));
+    scheduleTable.grantReadData(fn);
+    heroTable.grantReadData(fn);
+    slackUserTable.grantReadWriteData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    return fn;
+  }
+
+  coverageGet(scheduleTable: ITable, heroTable: ITable): IFunction {
+    let fn = this.createFn('CoverageGetFunction', 'coverage-get');
+    scheduleTable.grantReadData(fn);
+    heroTable.grantReadData(fn);
     return fn;
   }
 
-  punchClockRecalculate(scheduleTable: ITable, punchClockTable: ITable, slackParameter: IParameter): IFunction {
+  coverageAlert(scheduleTable: ITable, heroTable: ITable, slackParameter: IParameter): IFunction {
+    let fn = this.createFn('CoverageAlertFunction', 'coverage-alert', Duration.seconds(50));
+    scheduleTable.grantReadData(fn);
+    heroTable.grantReadData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    return fn;
+  }
+
+  slackUserCacheRefresh(heroTable: ITable, slackUserTable: ITable, slackParameter: IParameter): IFunction {
+    let fn = this.createFn('SlackUserCacheRefreshFunction', 'slack-user-cache-refresh', Duration.seconds(50));
+    heroTable.grantReadData(fn);
+    slackUserTable.grantReadWriteData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    return fn;
+  }
+
+  slackUsergroupDrift(scheduleTable: ITable, heroTable: ITable, slackUserTable: ITable, slackParameter: IParameter, teamsClientSecretParameter: IParameter): IFunction {
+    let fn = this.createFn('SlackUsergroupDriftFunction', 'slack-usergroup-drift', Duration.seconds(50));
+    scheduleTable.grantReadData(fn);
+    heroTable.grantReadData(fn);
+    slackUserTable.grantReadWriteData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    teamsClientSecretParameter.grantRead(fn);
+    return fn;
+  }
+
+  punchClockRecalculate(heroTable: ITable, scheduleTable: ITable, punchClockTable: ITable, punchClockLedgerTable: ITable, slackParameter: IParameter): IFunction {
     let fn = this.createFn('PunchClockRecalculateFunction', 'punch-clock-recalculate');
+    heroTable.grantReadData(fn);
     scheduleTable.grantReadData(fn);
     slackParameter.grantRead(fn);
     punchClockTable.grantReadWriteData(fn);
+    punchClockLedgerTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  recalculatePunchClock(jobTable: ITable, recalculationQueue: IQueue): IFunction {
+    let fn = this.createFn('RecalculatePunchClockFunction', 'punch-clock-recalculate-all', Duration.seconds(3), {
+      RECALCULATION_QUEUE_URL: recalculationQueue.queueUrl
+    });
+    jobTable.grantReadWriteData(fn);
+    recalculationQueue.grantSendMessages(fn);
     return fn;
   }
 
-  recalculatePunchClock(heroTable: ITable, scheduleTable: ITable, punchClockTable: ITable): IFunction {
-    let fn = this.createFn('RecalculatePunchClockFunction', 'punch-clock-recalculate-all');
+  recalculatePunchClockWorker(heroTable: ITable, scheduleTable: ITable, punchClockTable: ITable, punchClockLedgerTable: ITable, jobTable: ITable, recalculationQueue: IQueue): IFunction {
+    let fn = this.createFn('RecalculatePunchClockWorkerFunction', 'punch-clock-recalculate-worker', Duration.minutes(15));
     heroTable.grantReadData(fn);
     scheduleTable.grantReadData(fn);
     punchClockTable.grantReadWriteData(fn);
+    punchClockLedgerTable.grantReadWriteData(fn);
+    jobTable.grantReadWriteData(fn);
+    fn.addEventSource(new SqsEventSource(recalculationQueue, { batchSize: 1 }));
+    return fn;
+  }
+
+  recalculatePunchClockStatus(jobTable: ITable): IFunction {
+    let fn = this.createFn('RecalculatePunchClockStatusFunction', 'punch-clock-recalculate-status');
+    jobTable.grantReadData(fn);
+    return fn;
+  }
+
+  punchClockLedger(punchClockLedgerTable: ITable): IFunction {
+    let fn = this.createFn('PunchClockLedgerFunction', 'punch-clock-ledger');
+    punchClockLedgerTable.grantReadData(fn);
     return fn;
   }
 
@@ -210,16 +575,68 @@ export class HeroOfTheDayStack extends Stack {
     return fn;
   }
 
-  heroMemeberDelete(heroTable: ITable): IFunction {
-    let fn = this.createFn('HeroMemberDelete', 'hero-delete-member');
+  heroMemeberDelete(heroTable: ITable, webhookTable: ITable, webhookDeliveryTable: ITable): IFunction {
+    let fn = this.createFn('HeroMemberDelete', 'hero-delete-member', Duration.seconds(10));
     heroTable.grantReadWriteData(fn);
+    webhookTable.grantReadData(fn);
+    webhookDeliveryTable.grantReadWriteData(fn);
     return fn;
   }
 
-  heroDelete(heroTable: ITable, scheduleTable: ITable): IFunction {
+  heroDelete(heroTable: ITable): IFunction {
     let fn = this.createFn('HeroDelete', 'hero-delete');
     heroTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  heroRestore(heroTable: ITable): IFunction {
+    let fn = this.createFn('HeroRestoreFunction', 'hero-restore');
+    heroTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  heroPurge(heroTable: ITable, scheduleTable: ITable, punchClockTable: ITable, punchClockLedgerTable: ITable, webhookTable: ITable): IFunction {
+    let fn = this.createFn('HeroPurgeFunction', 'hero-purge', Duration.minutes(1));
+    heroTable.grantReadWriteData(fn);
     scheduleTable.grantReadWriteData(fn);
+    punchClockTable.grantReadWriteData(fn);
+    punchClockLedgerTable.grantReadWriteData(fn);
+    webhookTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  webhookList(webhookTable: ITable): IFunction {
+    let fn = this.createFn('WebhookListFunction', 'webhook-list');
+    webhookTable.grantReadData(fn);
+    return fn;
+  }
+
+  webhookCreate(heroTable: ITable, webhookTable: ITable): IFunction {
+    let fn = this.createFn('WebhookCreateFunction', 'webhook-create');
+    heroTable.grantReadData(fn);
+    webhookTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  webhookDelete(heroTable: ITable, webhookTable: ITable): IFunction {
+    let fn = this.createFn('WebhookDeleteFunction', 'webhook-delete');
+    heroTable.grantReadData(fn);
+    webhookTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  webhookDeliveries(heroTable: ITable, webhookTable: ITable, webhookDeliveryTable: ITable): IFunction {
+    let fn = this.createFn('WebhookDeliveriesFunction', 'webhook-deliveries');
+    heroTable.grantReadData(fn);
+    webhookTable.grantReadData(fn);
+    webhookDeliveryTable.grantReadData(fn);
+    return fn;
+  }
+
+  webhookRetry(webhookTable: ITable, webhookDeliveryTable: ITable): IFunction {
+    let fn = this.createFn('WebhookRetryFunction', 'webhook-retry', Duration.seconds(50));
+    webhookTable.grantReadData(fn);
+    webhookDeliveryTable.grantReadWriteData(fn);
     return fn;
   }
 
@@ -229,12 +646,71 @@ export class HeroOfTheDayStack extends Stack {
     return fn;
   }
 
+  userPreferencesGet(userTable: ITable): IFunction {
+    let fn = this.createFn('UserPreferencesGetFunction', 'user-preferences-get');
+    userTable.grantReadData(fn);
+    return fn;
+  }
+
+  memberWorkload(heroTable: ITable, scheduleTable: ITable, punchClockTable: ITable): IFunction {
+    let fn = this.createFn('MemberWorkloadFunction', 'member-workload', Duration.seconds(10));
+    heroTable.grantReadData(fn);
+    scheduleTable.grantReadData(fn);
+    punchClockTable.grantReadData(fn);
+    return fn;
+  }
+
+  compensationGet(heroTable: ITable, scheduleTable: ITable, compensationTable: ITable): IFunction {
+    let fn = this.createFn('CompensationGetFunction', 'compensation-get', Duration.seconds(10));
+    heroTable.grantReadData(fn);
+    scheduleTable.grantReadData(fn);
+    compensationTable.grantReadData(fn);
+    return fn;
+  }
+
+  compensationSignOff(heroTable: ITable, scheduleTable: ITable, compensationTable: ITable): IFunction {
+    let fn = this.createFn('CompensationSignOffFunction', 'compensation-sign-off', Duration.seconds(10));
+    heroTable.grantReadData(fn);
+    scheduleTable.grantReadData(fn);
+    compensationTable.grantReadWriteData(fn);
+    return fn;
+  }
+
+  userPreferencesPut(userTable: ITable): IFunction {
+    let fn = this.createFn('UserPreferencesPutFunction', 'user-preferences-put');
+    userTable.grantReadWriteData(fn);
+    return fn;
+  }
+
   userGet(userTable: ITable): IFunction {
     let fn = this.createFn('UserGetFunction', 'user-get');
     userTable.grantReadData(fn);
     return fn;
   }
 
+  slackCommand(scheduleTable: ITable, heroTable: ITable, slackUserTable: ITable, slackParameter: IParameter, slackSigningSecretParameter: IParameter): IFunction {
+    let fn = this.createFn('SlackCommandFunction', 'slack-command');
+    scheduleTable.grantReadWriteData(fn);
+    heroTable.grantReadWriteData(fn);
+    slackUserTable.grantReadWriteData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    slackSigningSecretParameter.grantRead(fn);
+    return fn;
+  }
+
+  slackInteractivity(scheduleTable: ITable, heroTable: ITable, userTable: ITable, slackUserTable: ITable, slackParameter: IParameter, slackSigningSecretParameter: IParameter): IFunction {
+    let fn = this.createFn('SlackInteractivityFunction', 'slack-interactivity', Duration.seconds(10));
+    scheduleTable.grantReadWriteData(fn);
+    heroTable.grantReadData(fn);
+    userTable.grantReadData(fn);
+    slackUserTable.grantReadWriteData(fn);
+    slackParameter.grantRead(fn);
+    this.grantReadWorkspaceSlackTokens(fn);
+    slackSigningSecretParameter.grantRead(fn);
+    return fn;
+  }
+
   apiGateway(
     authorizerFn: IFunction,
     heroListFn: IFunction,
@@ -248,8 +724,29 @@ export class HeroOfTheDayStack extends Stack {
     punchClockRecalculateFn: IFunction,
     punchClockStatsFn: IFunction,
     recalculatePunchClockFn: IFunction,
+    recalculatePunchClockStatusFn: IFunction,
+    punchClockLedgerFn: IFunction,
     userUpdateSeenReleaseNotesFn: IFunction,
-    userGetFn: IFunction
+    userPreferencesGetFn: IFunction,
+    userPreferencesPutFn: IFunction,
+    userGetFn: IFunction,
+    memberWorkloadFn: IFunction,
+    slackCommandFn: IFunction,
+    slackInteractivityFn: IFunction,
+    coverageGetFn: IFunction,
+    slackUserCacheRefreshFn: IFunction,
+    slackUsergroupDriftFn: IFunction,
+    webhookListFn: IFunction,
+    webhookCreateFn: IFunction,
+    webhookDeleteFn: IFunction,
+    webhookDeliveriesFn: IFunction,
+    compensationGetFn: IFunction,
+    compensationSignOffFn: IFunction,
+    heroCloneFn: IFunction,
+    heroTemplatePutFn: IFunction,
+    heroTemplateListFn: IFunction,
+    heroRestoreFn: IFunction,
+    heroPurgeFn: IFunction
   ) {
     const api = new apigw.RestApi(this, `${this.env.APP_NAME}-api`, {
       description: this.env.APP_NAME,
@@ -269,6 +766,9 @@ export class HeroOfTheDayStack extends Stack {
     let userPath = api.root.addResource('user');
     let schedulePath = api.root.addResource('schedule');
     let punchClockPath = api.root.addResource('punch-clock');
+    let slackPath = api.root.addResource('slack');
+    let adminPath = api.root.addResource('admin');
+    let templatePath = api.root.addResource('template');
 
     let authorizer = new apigw.TokenAuthorizer(this, 'HeroOfTheDayCustomAuthorizer', {
       handler: authorizerFn,
@@ -309,6 +809,48 @@ export class HeroOfTheDayStack extends Stack {
       }
     )
 
+    heroHeroPathResource.addResource('restore').addMethod('POST',
+      new apigw.LambdaIntegration(heroRestoreFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    heroHeroPathResource.addResource('clone').addMethod('POST',
+      new apigw.LambdaIntegration(heroCloneFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    templatePath.addResource('list').addMethod('GET',
+      new apigw.LambdaIntegration(heroTemplateListFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    let templateTemplatePathResource = templatePath.addResource('{template}');
+    templateTemplatePathResource.addMethod('PUT',
+      new apigw.LambdaIntegration(heroTemplatePutFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    // The authorizer only lets admins save templates and clone them.
+    templateTemplatePathResource.addResource('clone').addMethod('POST',
+      new apigw.LambdaIntegration(heroCloneFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
     heroHeroPathResource.addResource('members').addResource('{member}').addMethod('DELETE',
       new apigw.LambdaIntegration(heroMemberDeleteFn, { proxy: true }), 
       {
@@ -317,6 +859,47 @@ export class HeroOfTheDayStack extends Stack {
       }
     )
 
+    heroHeroPathResource.addResource('coverage').addMethod('GET',
+      new apigw.LambdaIntegration(coverageGetFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    const webhooksResource = heroHeroPathResource.addResource('webhooks');
+    webhooksResource.addMethod('GET',
+      new apigw.LambdaIntegration(webhookListFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+    webhooksResource.addMethod('POST',
+      new apigw.LambdaIntegration(webhookCreateFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    // The authorizer allows DELETE and GET for everybody, these lambdas check the membership.
+    const webhookResource = webhooksResource.addResource('{webhook}');
+    webhookResource.addMethod('DELETE',
+      new apigw.LambdaIntegration(webhookDeleteFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+    webhookResource.addResource('deliveries').addMethod('GET',
+      new apigw.LambdaIntegration(webhookDeliveriesFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
     const punchClockResource = heroHeroPathResource.addResource('punch-clock');
 
     punchClockResource.addResource('recalculate').addMethod('POST',
@@ -335,6 +918,14 @@ export class HeroOfTheDayStack extends Stack {
       }
     )
 
+    punchClockResource.addResource('ledger').addMethod('GET',
+      new apigw.LambdaIntegration(punchClockLedgerFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
     const userSubPath = userPath.addResource('{user}');
     userSubPath.addMethod('PUT',
       new apigw.LambdaIntegration(userCreateFn, { proxy: true }), 
@@ -357,6 +948,29 @@ export class HeroOfTheDayStack extends Stack {
         authorizationType: apigw.AuthorizationType.CUSTOM
       }
     )
+    const preferencesResource = userSubPath.addResource('preferences');
+    preferencesResource.addMethod('GET',
+      new apigw.LambdaIntegration(userPreferencesGetFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+    preferencesResource.addMethod('PUT',
+      new apigw.LambdaIntegration(userPreferencesPutFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    userSubPath.addResource('workload').addMethod('GET',
+      new apigw.LambdaIntegration(memberWorkloadFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
 
     const heroResource = schedulePath.addResource('{hero}');
 
@@ -382,5 +996,61 @@ export class HeroOfTheDayStack extends Stack {
         authorizationType: apigw.AuthorizationType.CUSTOM
       }
     )
+
+    recalculatePunchClockResource.addResource('{job}').addMethod('GET', new apigw.LambdaIntegration(recalculatePunchClockStatusFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    // The authorizer only allows ADMIN_EMAILS on admin resources.
+    const adminSlackPath = adminPath.addResource('slack');
+    adminSlackPath.addResource('user-cache').addResource('refresh').addMethod('POST',
+      new apigw.LambdaIntegration(slackUserCacheRefreshFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+    adminSlackPath.addResource('usergroups').addResource('drift').addMethod('GET',
+      new apigw.LambdaIntegration(slackUsergroupDriftFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    adminPath.addResource('hero').addResource('{hero}').addMethod('DELETE',
+      new apigw.LambdaIntegration(heroPurgeFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    const compensationMonthResource = adminPath.addResource('compensation').addResource('{month}');
+    compensationMonthResource.addMethod('GET',
+      new apigw.LambdaIntegration(compensationGetFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+    compensationMonthResource.addResource('sign-off').addMethod('POST',
+      new apigw.LambdaIntegration(compensationSignOffFn, { proxy: true }),
+      {
+        authorizer,
+        authorizationType: apigw.AuthorizationType.CUSTOM
+      }
+    )
+
+    // Slack requests are authenticated by verifying their signature in the lambda.
+    slackPath.addResource('command').addMethod('POST',
+      new apigw.LambdaIntegration(slackCommandFn, { proxy: true })
+    )
+    slackPath.addResource('interactivity').addMethod('POST',
+      new apigw.LambdaIntegration(slackInteractivityFn, { proxy: true })
+    )
   }
 }
@@ -7,8 +7,9 @@ use jsonwebtoken::dangerous_insecure_decode;
 use lambda_runtime::{run, service_fn, Error, LambdaEvent};
 use model::time::secs_now;
 use repository::{hero::HeroRepository, user::UserRepository};
+use response::is_admin;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 use std::env;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -87,9 +88,11 @@ async fn main() -> Result<(), Error> {
                 }
                 Err(err) => {
                     tracing::error!("Error validating token: {:?}", err);
-                    Ok(policy(None, method_arn.clone(), Some(err.to_string()))(
-                        Effect::Deny,
-                    ))
+                    Ok(policy(
+                        None,
+                        method_arn.clone(),
+                        json!({ "error": err.to_string() }),
+                    )(Effect::Deny))
                 }
             }
         },
@@ -114,9 +117,31 @@ async fn check_user(
     let resource = parts[3];
     let sub_resource = parts[4];
 
-    let apply_policy = policy(Some(sub.clone()), method_arn.clone(), None);
+    // Lets the lambdas tell who is calling them.
+    let apply_policy = policy(
+        Some(sub.clone()),
+        method_arn.clone(),
+        json!({ "email": info.email }),
+    );
 
-    let value = if http_verb == "POST" || http_verb == "PUT" {
+    let value = if resource == "admin" {
+        if is_admin(&info.email) {
+            tracing::info!("ALLOW admin");
+            apply_policy(Effect::Allow)
+        } else {
+            tracing::info!("DENY admin");
+            apply_policy(Effect::Deny)
+        }
+    } else if resource == "template" && http_verb != "GET" {
+        // Templates are shared by all heroes and cloning one creates a hero.
+        if is_admin(&info.email) {
+            tracing::info!("ALLOW template");
+            apply_policy(Effect::Allow)
+        } else {
+            tracing::info!("DENY template");
+            apply_policy(Effect::Deny)
+        }
+    } else if http_verb == "POST" || http_verb == "PUT" {
         if resource == "user" {
             tracing::info!("ALLOW POST and PUT on user");
             apply_policy(Effect::Allow)
@@ -158,7 +183,7 @@ async fn logged_in(repository: &UserRepository, email: String) -> Result<(), Err
 fn policy(
     principal_id: Option<String>,
     method_arn: String,
-    context: Option<String>,
+    context: Value,
 ) -> impl Fn(Effect) -> ApiGatewayCustomAuthorizerResponse {
     move |effect| ApiGatewayCustomAuthorizerResponse {
         principal_id: principal_id.clone(),
@@ -175,7 +200,7 @@ fn policy(
                 }],
             }
         },
-        context: json!(context),
+        context: context.clone(),
         usage_identifier_key: None,
     }
 }
@@ -0,0 +1,95 @@
+use futures::future;
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use model::compensation::{lines, month_range, parse_month, Compensation, Rates};
+use model::hero::Hero;
+use model::schedule::Schedule;
+use model::time::secs_now;
+use repository::compensation::CompensationRepository;
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use response::{bad_request, csv, ok};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let compensation_repository_ref = &CompensationRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        let Some(month_label) = event.path_parameters().first("month").map(String::from) else {
+            return bad_request("Expected month".into());
+        };
+        let Ok(month) = parse_month(&month_label) else {
+            return bad_request("`month` has to be a month like 2023-07".into());
+        };
+        let query = event.query_string_parameters();
+        let csv_requested = match query.first("format") {
+            None | Some("json") => false,
+            Some("csv") => true,
+            Some(_) => return bad_request("`format` has to be one of json or csv".into()),
+        };
+
+        let sign_offs = compensation_repository_ref.get(month_label.clone()).await?;
+        // Once signed off, the month is paid with the rates of the first sign-off.
+        let rates = match sign_offs.first() {
+            Some(sign_off) => sign_off.rates,
+            None => match query_rates(&event) {
+                Ok(rates) => rates,
+                Err(err) => return bad_request(err),
+            },
+        };
+        if let Err(err) = rates.validate() {
+            return bad_request(err);
+        }
+
+        let now = secs_now() as i64;
+        let (start, end) = month_range(month);
+        let heroes = hero_repository_ref.list().await?;
+        let schedules: Vec<Vec<Schedule>> = future::try_join_all(
+            heroes
+                .iter()
+                .map(|hero| schedule_repository_ref.get_in_effect(hero.name.clone(), start, end)),
+        )
+        .await?;
+        let heroes: Vec<(Hero, Vec<Schedule>)> = heroes.into_iter().zip(schedules).collect();
+
+        let compensation =
+            Compensation::new(month, rates, lines(&heroes, month, &rates, now), sign_offs);
+        if csv_requested {
+            csv(
+                compensation.to_csv(),
+                &format!("compensation-{}.csv", month_label),
+            )
+        } else {
+            ok(compensation)
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+/// Rates given as query parameters, missing ones are zero.
+fn query_rates(event: &Request) -> Result<Rates, String> {
+    let query = event.query_string_parameters();
+    let rate = |name: &str| -> Result<f64, String> {
+        query
+            .first(name)
+            .map(str::parse::<f64>)
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(|_| format!("`{}` has to be a number", name))
+    };
+    Ok(Rates {
+        weekday: rate("weekday_rate")?,
+        weekend: rate("weekend_rate")?,
+        holiday: rate("holiday_rate")?,
+    })
+}
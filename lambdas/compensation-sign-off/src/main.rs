@@ -0,0 +1,103 @@
+use futures::future;
+use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
+use model::compensation::{lines, month_range, parse_month, Compensation, Rates, SignOff};
+use model::hero::Hero;
+use model::schedule::Schedule;
+use model::time::secs_now;
+use repository::compensation::CompensationRepository;
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use response::{bad_request, caller_email, ok, unauthorized};
+use serde::Deserialize;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let compensation_repository_ref = &CompensationRepository::new(&shared_config);
+
+    // Signs off the lines due of a past month: all of them the first time, which locks the month
+    // and its rates, the corrections since the last sign-off afterwards.
+    run(service_fn(move |event: Request| async move {
+        let Some(month_label) = event.path_parameters().first("month").map(String::from) else {
+            return bad_request("Expected month".into());
+        };
+        let Ok(month) = parse_month(&month_label) else {
+            return bad_request("`month` has to be a month like 2023-07".into());
+        };
+        // Signed off by whoever is signed in, as passed on by the authorizer.
+        let Some(signed_off_by) = caller_email(&event) else {
+            return unauthorized("Could not tell who signs off".into());
+        };
+        let payload = event.payload::<Payload>()?.unwrap_or_default();
+        let now = secs_now() as i64;
+        let (start, end) = month_range(month);
+        if end > now {
+            return bad_request("Only past months can be signed off".into());
+        }
+
+        let mut sign_offs = compensation_repository_ref.get(month_label.clone()).await?;
+        let rates = match (sign_offs.first(), payload.rates) {
+            (Some(sign_off), _) => sign_off.rates,
+            (None, Some(rates)) => rates,
+            (None, None) => {
+                return bad_request(
+                    "`rates` are required to sign off a month the first time".into(),
+                )
+            }
+        };
+        if let Err(err) = rates.validate() {
+            return bad_request(err);
+        }
+
+        let heroes = hero_repository_ref.list().await?;
+        let schedules: Vec<Vec<Schedule>> = future::try_join_all(
+            heroes
+                .iter()
+                .map(|hero| schedule_repository_ref.get_in_effect(hero.name.clone(), start, end)),
+        )
+        .await?;
+        let heroes: Vec<(Hero, Vec<Schedule>)> = heroes.into_iter().zip(schedules).collect();
+        let lines = lines(&heroes, month, &rates, now);
+
+        let compensation = Compensation::new(month, rates, lines.clone(), sign_offs.clone());
+        if compensation.locked && compensation.due().is_empty() {
+            return bad_request(format!("{} has no corrections to sign off", month_label));
+        }
+        let sign_off = SignOff {
+            month: month_label,
+            signed_off_at: now as u64,
+            signed_off_by,
+            rates,
+            lines: compensation.due().to_vec(),
+        };
+        if !compensation_repository_ref
+            .put(&sign_off, sign_offs.len())
+            .await?
+        {
+            return bad_request(format!(
+                "{} has been signed off meanwhile, please check it again",
+                sign_off.month
+            ));
+        }
+
+        sign_offs.push(sign_off);
+        ok(Compensation::new(month, rates, lines, sign_offs))
+    }))
+    .await?;
+    Ok(())
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+struct Payload {
+    /// Rates to lock the month with, ignored once it is signed off.
+    rates: Option<Rates>,
+}
@@ -0,0 +1,76 @@
+use chrono::{Datelike, Days};
+use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use model::coverage::analyze;
+use model::hero::Platform;
+use model::time::{business_days, day_of, end_of_day, secs_now, start_of_day};
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use serde::{Deserialize, Serialize};
+use slack::coverage::coverage_alert_message;
+use slack::workspace::Workspaces;
+
+#[derive(Serialize, Deserialize)]
+struct Request {}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let workspaces_ref = &Workspaces::new();
+
+    run(service_fn(move |_: LambdaEvent<Request>| async move {
+        // Analyzes the business days of the next week.
+        let today = day_of(secs_now() as i64);
+        let next_week = today + Days::new(7 - today.weekday().num_days_from_monday() as u64);
+        let days = business_days(next_week, 5);
+
+        for hero in hero_repository_ref.list_active().await?.into_iter() {
+            // The alert is a Slack message, on Teams `channel` is a webhook URL.
+            if hero.platform != Platform::Slack {
+                continue;
+            }
+            let channel = match hero.channel.as_ref() {
+                Some(channel) => channel,
+                None => continue,
+            };
+            let schedules = schedule_repository_ref
+                .get_in_effect(
+                    hero.name.clone(),
+                    start_of_day(days[0]),
+                    end_of_day(days[days.len() - 1]),
+                )
+                .await?;
+            let report = analyze(&hero, &schedules, &days);
+            println!("Coverage of {}: {:?}", hero.name, report);
+
+            if !report.is_covered() {
+                let result = match workspaces_ref.client(hero.workspace.as_deref()).await {
+                    Ok(client) => {
+                        client
+                            .post_message(channel, &coverage_alert_message(&report))
+                            .await
+                    }
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    eprintln!(
+                        "Error posting coverage alert for hero {} in channel {}: {:?}",
+                        hero.name, channel, err
+                    );
+                }
+            }
+        }
+
+        Ok::<(), Error>(())
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use model::coverage::analyze;
+use model::time::{business_days, day_of, end_of_day, secs_now, start_of_day};
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use response::{bad_request, ok};
+
+/// Number of business days analyzed if the `days` query parameter is missing.
+const DEFAULT_HORIZON_DAYS: usize = 5;
+const MAX_HORIZON_DAYS: usize = 60;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("hero") {
+            Some(hero) => {
+                let horizon = match event.query_string_parameters().first("days") {
+                    Some(days) => match days.parse::<usize>() {
+                        Ok(days) if (1..=MAX_HORIZON_DAYS).contains(&days) => days,
+                        _ => {
+                            return bad_request(format!(
+                                "`days` has to be a number between 1 and {}",
+                                MAX_HORIZON_DAYS
+                            ))
+                        }
+                    },
+                    None => DEFAULT_HORIZON_DAYS,
+                };
+
+                let hero = match hero_repository_ref.find(hero.to_string()).await? {
+                    Some(hero) => hero,
+                    None => return bad_request(format!("Hero {} does not exist", hero)),
+                };
+                let days = business_days(day_of(secs_now() as i64), horizon);
+                let schedules = schedule_repository_ref
+                    .get_in_effect(
+                        hero.name.clone(),
+                        start_of_day(days[0]),
+                        end_of_day(days[days.len() - 1]),
+                    )
+                    .await?;
+                ok(analyze(&hero, &schedules, &days))
+            }
+            _ => bad_request("Hero parameter missing".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use futures::future;
+use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
+use model::hero::Hero;
+use model::schedule::Schedule;
+use model::time::secs_now;
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use response::{bad_request, ok};
+use serde::{Deserialize, Serialize};
+
+const MAX_OFFSET_DAYS: i64 = 366;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let template_repository_ref =
+        &HeroRepository::new_with_table_name(&shared_config, "HERO_TEMPLATE_TABLE".to_string());
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+
+    // Creates a hero with the configuration and members of a hero, or of a template when called
+    // on a template.
+    run(service_fn(move |event: Request| async move {
+        let path_parameters = event.path_parameters();
+        let (source, template) = match (
+            path_parameters.first("hero"),
+            path_parameters.first("template"),
+        ) {
+            (Some(hero), _) => (hero_repository_ref.find(hero.to_string()).await?, false),
+            (None, Some(template)) => (
+                template_repository_ref.find(template.to_string()).await?,
+                true,
+            ),
+            (None, None) => return bad_request("Expected hero or template".into()),
+        };
+        let Some(source) = source else {
+            return bad_request("Unknown hero or template to clone".into());
+        };
+        let Some(payload) = event.payload::<Payload>()? else {
+            return bad_request("Could not parse JSON payload for the clone".into());
+        };
+
+        let name = payload.name.trim();
+        if name.is_empty() {
+            return bad_request("`name` is required".into());
+        }
+        if payload.offset_days.abs() > MAX_OFFSET_DAYS {
+            return bad_request(format!(
+                "`offset_days` has to be between -{} and {}",
+                MAX_OFFSET_DAYS, MAX_OFFSET_DAYS
+            ));
+        }
+        if payload.copy_schedules && template {
+            return bad_request("Templates have no schedules to copy".into());
+        }
+
+        // The Teams group belongs to the source, the clone starts without one.
+        let hero = Hero {
+            name: name.to_string(),
+            teams_group_id: None,
+            archived_at: None,
+            ..source.clone()
+        };
+        if !hero_repository_ref.create(&hero).await? {
+            return bad_request(format!("Hero {} already exists", name));
+        }
+
+        // Copies which would start in the past are left out, they would alter the punch clock.
+        let mut schedules: Vec<Schedule> = vec![];
+        if payload.copy_schedules {
+            let now = secs_now() as i64;
+            schedules = schedule_repository_ref
+                .get(source.name.clone(), Some((now, i64::MAX)))
+                .await?
+                .iter()
+                .map(|schedule| schedule.copy_for(name, payload.offset_days))
+                .filter(|schedule| schedule.shift_start_time >= now)
+                .collect();
+            future::try_join_all(
+                schedules
+                    .iter()
+                    .map(|schedule| schedule_repository_ref.put(schedule)),
+            )
+            .await?;
+        }
+
+        ok(Response { hero, schedules })
+    }))
+    .await?;
+    Ok(())
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Payload {
+    name: String,
+    /// Copies the schedules of the hero which did not start yet.
+    #[serde(default)]
+    copy_schedules: bool,
+    /// Days the copied schedules are moved by.
+    #[serde(default)]
+    offset_days: i64,
+}
+
+#[derive(Serialize)]
+struct Response {
+    hero: Hero,
+    schedules: Vec<Schedule>,
+}
@@ -1,8 +1,11 @@
 use email_address::EmailAddress;
 use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use model::webhook::EventType;
 use repository::hero::HeroRepository;
 use response::{bad_request, ok};
+use serde_json::json;
 use std::str::FromStr;
+use webhook::Dispatcher;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -15,19 +18,33 @@ async fn main() -> Result<(), Error> {
 
     let shared_config = aws_config::load_from_env().await;
     let repository_ref = &HeroRepository::new(&shared_config);
+    let dispatcher_ref = &Dispatcher::new(&shared_config);
 
     run(service_fn(move |event: Request| async move {
         match event.path_parameters().first("hero") {
             Some(name) => match event.path_parameters().first("member") {
                 Some(member) => match EmailAddress::from_str(member) {
                     Ok(member) => {
+                        let was_member = repository_ref
+                            .find(name.to_string())
+                            .await?
+                            .is_some_and(|hero| hero.members.contains(&member.to_string()));
                         let members = repository_ref
                             .update_members(
                                 name.to_string(),
-                                vec![member],
+                                vec![member.clone()],
                                 repository::hero::UpdateOperation::Delete,
                             )
                             .await?;
+                        if was_member {
+                            dispatcher_ref
+                                .emit(
+                                    name,
+                                    EventType::MemberRemoved,
+                                    json!({ "members": [member.to_string()] }),
+                                )
+                                .await;
+                        }
                         ok(members)
                     }
                     Err(err) => {
@@ -1,5 +1,6 @@
 use lambda_http::{run, service_fn, Error, Request, RequestExt};
-use repository::{hero::HeroRepository, schedule::ScheduleRepository};
+use model::time::secs_now;
+use repository::hero::HeroRepository;
 use response::{bad_request, ok};
 
 #[tokio::main]
@@ -13,13 +14,17 @@ async fn main() -> Result<(), Error> {
 
     let shared_config = aws_config::load_from_env().await;
     let hero_repository_ref = &HeroRepository::new(&shared_config);
-    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
 
+    // Archives the hero, its schedules, punch clocks and webhooks are kept until it is purged.
     run(service_fn(move |event: Request| async move {
         match event.path_parameters().first("hero") {
             Some(name) => {
-                schedule_repository_ref.delete(name.to_string()).await?;
-                hero_repository_ref.delete(name.to_string()).await?;
+                if hero_repository_ref.find(name.to_string()).await?.is_none() {
+                    return bad_request(format!("Unknown hero {}", name));
+                }
+                hero_repository_ref
+                    .archive(name.to_string(), secs_now())
+                    .await?;
                 ok(())
             }
             _ => bad_request("Expected hero".into()),
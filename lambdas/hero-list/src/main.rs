@@ -1,4 +1,4 @@
-use lambda_http::{run, service_fn, Error, Request};
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
 use model::hero::Hero;
 use repository::hero::HeroRepository;
 use response::ok;
@@ -21,9 +21,24 @@ async fn main() -> Result<(), Error> {
     let shared_config = aws_config::load_from_env().await;
     let repository_ref = &HeroRepository::new(&shared_config);
 
-    run(service_fn(move |_: Request| async move {
+    // Lists the active heroes, or the archived ones with `archived=true`. With `tag`, repeatable,
+    // only the heroes having all of the tags.
+    run(service_fn(move |event: Request| async move {
+        let query = event.query_string_parameters();
+        let archived = query.first("archived") == Some("true");
+        let tags: Vec<String> = query
+            .all("tag")
+            .unwrap_or_default()
+            .into_iter()
+            .map(String::from)
+            .collect();
         tracing::info!("Fetching heroes...");
-        let heroes: Vec<Hero> = repository_ref.list().await?;
+        let heroes: Vec<Hero> = repository_ref
+            .list()
+            .await?
+            .into_iter()
+            .filter(|hero| hero.is_archived() == archived && hero.has_tags(&tags))
+            .collect();
         tracing::info!("Fetched {} heroes.", heroes.len());
         ok(heroes)
     }))
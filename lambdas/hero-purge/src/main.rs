@@ -0,0 +1,60 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use repository::hero::HeroRepository;
+use repository::punch_clock::{PunchClockLedgerRepository, PunchClockRepository};
+use repository::schedule::ScheduleRepository;
+use repository::webhook::WebhookRepository;
+use response::{bad_request, ok};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let punch_clock_repository_ref = &PunchClockRepository::new(&shared_config);
+    let punch_clock_ledger_repository_ref = &PunchClockLedgerRepository::new(&shared_config);
+    let webhook_repository_ref = &WebhookRepository::new(&shared_config);
+
+    // Deletes an archived hero with all its data. The hero goes last, so a failed purge can be
+    // run again.
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("hero") {
+            Some(name) => {
+                match hero_repository_ref.find(name.to_string()).await? {
+                    Some(hero) if hero.is_archived() => {}
+                    Some(_) => {
+                        return bad_request(format!(
+                            "Hero {} has to be archived before it is purged",
+                            name
+                        ))
+                    }
+                    None => return bad_request(format!("Unknown hero {}", name)),
+                }
+                schedule_repository_ref.delete(name.to_string()).await?;
+                punch_clock_repository_ref
+                    .delete_all(name.to_string())
+                    .await?;
+                punch_clock_ledger_repository_ref
+                    .delete_all(name.to_string())
+                    .await?;
+                // Deliveries of the removed webhooks expire with the delivery log.
+                for subscription in webhook_repository_ref.list(name.to_string()).await? {
+                    webhook_repository_ref
+                        .delete(subscription.hero, subscription.id)
+                        .await?;
+                }
+                hero_repository_ref.delete(name.to_string()).await?;
+                ok(())
+            }
+            _ => bad_request("Expected hero".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
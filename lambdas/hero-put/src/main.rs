@@ -1,8 +1,10 @@
 use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
-use model::hero::Hero;
+use model::hero::HeroUpdate;
+use model::webhook::EventType;
 use repository::hero::HeroRepository;
 use response::{bad_request, ok};
-use serde::Deserialize;
+use serde_json::json;
+use webhook::Dispatcher;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -15,18 +17,53 @@ async fn main() -> Result<(), Error> {
 
     let shared_config = aws_config::load_from_env().await;
     let repository_ref = &HeroRepository::new(&shared_config);
+    let dispatcher_ref = &Dispatcher::new(&shared_config);
 
     run(service_fn(move |event: Request| async move {
         match event.path_parameters().first("hero") {
             Some(name) => {
-                match event.payload::<Payload>()? {
-                    Some(payload) => {
-                        let hero = Hero {
-                            name: name.to_string(),
-                            members: payload.members,
-                            channel: None,
-                        };
+                match event.payload::<HeroUpdate>()? {
+                    Some(update) => {
+                        let previous = repository_ref.find(name.to_string()).await?;
+                        let previous_members = previous
+                            .as_ref()
+                            .map(|hero| hero.members.clone())
+                            .unwrap_or_default();
+                        let hero = update.apply(name, previous);
+                        if let Err(err) = hero.weights.validate() {
+                            return bad_request(err);
+                        }
+                        if let Err(err) = hero.validate_settings() {
+                            return bad_request(err);
+                        }
+                        if let Err(err) = hero.validate_metadata() {
+                            return bad_request(err);
+                        }
                         repository_ref.put(&hero).await?;
+
+                        let added: Vec<&String> = hero
+                            .members
+                            .iter()
+                            .filter(|member| !previous_members.contains(member))
+                            .collect();
+                        if !added.is_empty() {
+                            dispatcher_ref
+                                .emit(name, EventType::MemberAdded, json!({ "members": added }))
+                                .await;
+                        }
+                        let removed: Vec<&String> = previous_members
+                            .iter()
+                            .filter(|member| !hero.members.contains(member))
+                            .collect();
+                        if !removed.is_empty() {
+                            dispatcher_ref
+                                .emit(
+                                    name,
+                                    EventType::MemberRemoved,
+                                    json!({ "members": removed }),
+                                )
+                                .await;
+                        }
                         // slack::Client::new(slack::get_slack_token().await?).create_usergroup(&name.to_string());
                         ok(())
                     }
@@ -39,8 +76,3 @@ async fn main() -> Result<(), Error> {
     .await?;
     Ok(())
 }
-
-#[derive(Deserialize, Debug, Clone)]
-struct Payload {
-    members: Vec<String>,
-}
@@ -0,0 +1,32 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use repository::hero::HeroRepository;
+use response::{bad_request, ok};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("hero") {
+            Some(name) => match hero_repository_ref.find(name.to_string()).await? {
+                Some(hero) if hero.is_archived() => {
+                    hero_repository_ref.restore(name.to_string()).await?;
+                    ok(())
+                }
+                Some(_) => bad_request(format!("Hero {} is not archived", name)),
+                None => bad_request(format!("Unknown hero {}", name)),
+            },
+            _ => bad_request("Expected hero".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use lambda_http::{run, service_fn, Error, Request};
+use model::hero::Hero;
+use repository::hero::HeroRepository;
+use response::ok;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let template_repository_ref =
+        &HeroRepository::new_with_table_name(&shared_config, "HERO_TEMPLATE_TABLE".to_string());
+
+    run(service_fn(move |_: Request| async move {
+        let templates: Vec<Hero> = template_repository_ref.list().await?;
+        ok(templates)
+    }))
+    .await?;
+    Ok(())
+}
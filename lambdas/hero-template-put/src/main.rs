@@ -0,0 +1,52 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
+use model::hero::Hero;
+use repository::hero::HeroRepository;
+use response::{bad_request, ok};
+use serde::Deserialize;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let template_repository_ref =
+        &HeroRepository::new_with_table_name(&shared_config, "HERO_TEMPLATE_TABLE".to_string());
+
+    // Saves the configuration and members of a hero as a template, replacing an existing one.
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("template") {
+            Some(name) => match event.payload::<Payload>()? {
+                Some(payload) => {
+                    let Some(hero) = hero_repository_ref.find(payload.hero.clone()).await? else {
+                        return bad_request(format!("Unknown hero {}", payload.hero));
+                    };
+                    // Heroes created from the template get their own Teams group.
+                    let template = Hero {
+                        name: name.to_string(),
+                        teams_group_id: None,
+                        archived_at: None,
+                        ..hero
+                    };
+                    template_repository_ref.put(&template).await?;
+                    ok(template)
+                }
+                None => bad_request("Could not parse JSON payload for the template".into()),
+            },
+            None => bad_request("Expected template".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct Payload {
+    /// Hero the template is taken from.
+    hero: String,
+}
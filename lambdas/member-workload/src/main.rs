@@ -0,0 +1,72 @@
+use futures::future;
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use model::punch_clock::PunchClock;
+use model::time::secs_now;
+use model::workload::{upcoming_shifts, workload, Shift};
+use repository::hero::HeroRepository;
+use repository::punch_clock::PunchClockRepository;
+use repository::schedule::ScheduleRepository;
+use response::{bad_request, ok};
+
+/// Number of calendar days ahead upcoming shifts are reported if `days` is missing.
+const DEFAULT_HORIZON_DAYS: i64 = 30;
+const MAX_HORIZON_DAYS: i64 = 365;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let punch_clock_repository_ref = &PunchClockRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("user") {
+            Some(email) => {
+                let horizon = match event.query_string_parameters().first("days") {
+                    Some(days) => match days.parse::<i64>() {
+                        Ok(days) if (1..=MAX_HORIZON_DAYS).contains(&days) => days,
+                        _ => {
+                            return bad_request(format!(
+                                "`days` has to be a number between 1 and {}",
+                                MAX_HORIZON_DAYS
+                            ))
+                        }
+                    },
+                    None => DEFAULT_HORIZON_DAYS,
+                };
+                let now = secs_now() as i64;
+                let until = now + horizon * 24 * 60 * 60;
+
+                let heroes = hero_repository_ref.list().await?;
+                let punch_clocks: Vec<PunchClock> = future::try_join_all(
+                    heroes
+                        .iter()
+                        .map(|hero| punch_clock_repository_ref.get(&hero.name, email.to_string())),
+                )
+                .await?
+                .into_iter()
+                .flatten()
+                .collect();
+                let shifts: Vec<Shift> = future::try_join_all(heroes.iter().map(|hero| {
+                    schedule_repository_ref.get_in_effect(hero.name.clone(), now, until)
+                }))
+                .await?
+                .iter()
+                .flat_map(|schedules| upcoming_shifts(email, schedules, now, until))
+                .collect();
+
+                ok(workload(email, &punch_clocks, shifts))
+            }
+            _ => bad_request("Expected user".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
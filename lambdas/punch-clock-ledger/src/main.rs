@@ -0,0 +1,65 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use model::punch_clock::{totals, LedgerEntry, PunchClock};
+use repository::punch_clock::PunchClockLedgerRepository;
+use response::{bad_request, ok};
+use serde::Serialize;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let punch_clock_ledger_repository_ref = &PunchClockLedgerRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("hero") {
+            Some(hero) => {
+                let query = event.query_string_parameters();
+                let mut between = (0, i64::MAX);
+                for (name, bound) in [("from", &mut between.0), ("to", &mut between.1)] {
+                    if let Some(value) = query.first(name) {
+                        match value.parse::<i64>() {
+                            Ok(value) => *bound = value,
+                            Err(_) => {
+                                return bad_request(format!(
+                                    "`{}` has to be seconds since the epoch",
+                                    name
+                                ))
+                            }
+                        }
+                    }
+                }
+                if between.0 > between.1 {
+                    return bad_request("`from` has to be before `to`".into());
+                }
+
+                let entries = punch_clock_ledger_repository_ref
+                    .get(
+                        hero.to_string(),
+                        query.first("member").map(|member| member.to_string()),
+                        Some(between),
+                    )
+                    .await?;
+                ok(Response {
+                    totals: totals(&entries),
+                    entries,
+                })
+            }
+            None => bad_request("Hero parameter missing".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+/// Shifts starting in the requested period and the punch clocks they add up to.
+#[derive(Serialize)]
+struct Response {
+    entries: Vec<LedgerEntry>,
+    totals: Vec<PunchClock>,
+}
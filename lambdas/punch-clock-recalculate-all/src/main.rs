@@ -1,12 +1,10 @@
 use lambda_http::{run, service_fn, Error, Request};
-use model::hero::Hero;
-use model::punch_clock::recalculate_punch_time;
+use model::job::{Job, JobRequest, JOB_RETENTION_SECS};
 use model::time::secs_now;
-use repository::hero::HeroRepository;
-use repository::punch_clock::PunchClockRepository;
-use repository::schedule::ScheduleRepository;
+use repository::job::JobRepository;
 use response::ok;
-use serde::Deserialize;
+use std::env;
+use uuid::Uuid;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -18,27 +16,25 @@ async fn main() -> Result<(), Error> {
         .init();
 
     let shared_config = aws_config::load_from_env().await;
-    let hero_repository_ref = &HeroRepository::new(&shared_config);
-    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
-    let punch_clock_repository_ref = &PunchClockRepository::new(&shared_config);
+    let job_repository_ref = &JobRepository::new(&shared_config);
+    let sqs_client_ref = &aws_sdk_sqs::Client::new(&shared_config);
+    let queue_url_ref = &env::var("RECALCULATION_QUEUE_URL")?;
 
+    // The recalculation of all heroes takes too long for an HTTP request, it is queued for
+    // punch-clock-recalculate-worker and its progress is reported by punch-clock-recalculate-status.
     run(service_fn(move |_event: Request| async move {
-        let heroes: Vec<Hero> = hero_repository_ref.list().await?;
-
-        for hero in heroes.into_iter() {
-            let schedules = schedule_repository_ref
-                .get(hero.name.to_string().clone(), Some((0, secs_now() as i64)))
-                .await?;
-            let recalculated = recalculate_punch_time(hero.name.to_string().clone(), schedules);
-            for punch_clock in recalculated.into_iter() {
-                punch_clock_repository_ref.put(&punch_clock).await?;
-            }
-        }
-        ok(())
+        let job = Job::new(Uuid::new_v4().to_string(), secs_now());
+        job_repository_ref.put(&job, JOB_RETENTION_SECS).await?;
+        sqs_client_ref
+            .send_message()
+            .queue_url(queue_url_ref)
+            .message_body(serde_json::to_string(&JobRequest {
+                job_id: job.id.clone(),
+            })?)
+            .send()
+            .await?;
+        ok(job)
     }))
     .await?;
     Ok(())
 }
-
-#[derive(Deserialize, Debug, Clone)]
-struct Payload {}
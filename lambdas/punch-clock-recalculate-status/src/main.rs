@@ -0,0 +1,28 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use repository::job::JobRepository;
+use response::{bad_request, ok};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let job_repository_ref = &JobRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("job") {
+            Some(job) => match job_repository_ref.get(job.to_string()).await? {
+                Some(job) => ok(job),
+                None => bad_request(format!("Unknown job {}", job)),
+            },
+            _ => bad_request("Expected job".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use aws_lambda_events::event::sqs::SqsEvent;
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use futures::stream;
+use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use model::hero::Hero;
+use model::job::{JobRequest, JobStatus};
+use model::punch_clock::{ledger_entries, totals};
+use model::time::secs_now;
+use repository::hero::HeroRepository;
+use repository::job::JobRepository;
+use repository::punch_clock::{PunchClockLedgerRepository, PunchClockRepository};
+use repository::schedule::ScheduleRepository;
+
+/// Heroes recalculated at the same time, bounding the load on the tables.
+const MAX_CONCURRENT_HEROES: usize = 8;
+
+struct Repositories {
+    hero: HeroRepository,
+    schedule: ScheduleRepository,
+    punch_clock: PunchClockRepository,
+    punch_clock_ledger: PunchClockLedgerRepository,
+    job: JobRepository,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let repositories_ref = &Repositories {
+        hero: HeroRepository::new(&shared_config),
+        schedule: ScheduleRepository::new(&shared_config),
+        punch_clock: PunchClockRepository::new(&shared_config),
+        punch_clock_ledger: PunchClockLedgerRepository::new(&shared_config),
+        job: JobRepository::new(&shared_config),
+    };
+
+    run(service_fn(move |event: LambdaEvent<SqsEvent>| async move {
+        for record in event.payload.records {
+            let request: JobRequest =
+                serde_json::from_str(record.body.as_deref().unwrap_or_default())?;
+            if let Err(err) = recalculate_all(repositories_ref, &request.job_id).await {
+                repositories_ref
+                    .job
+                    .finish(request.job_id, JobStatus::Failed, secs_now())
+                    .await?;
+                return Err(err);
+            }
+        }
+        Ok::<(), Error>(())
+    }))
+    .await?;
+    Ok(())
+}
+
+async fn recalculate_all(repositories: &Repositories, job_id: &str) -> Result<(), Error> {
+    let heroes = repositories.hero.list().await?;
+    repositories
+        .job
+        .start(job_id.to_string(), heroes.len() as u64, secs_now())
+        .await?;
+    let now = secs_now() as i64;
+
+    let recalculations: Vec<BoxFuture<Result<(), Error>>> = heroes
+        .iter()
+        .map(|hero| {
+            async move {
+                let result = recalculate(repositories, hero, now).await;
+                if let Err(err) = &result {
+                    eprintln!(
+                        "Error recalculating the punch clocks of {}: {:?}",
+                        hero.name, err
+                    );
+                }
+                repositories
+                    .job
+                    .hero_done(job_id.to_string(), &hero.name, result.is_err(), secs_now())
+                    .await
+            }
+            .boxed()
+        })
+        .collect();
+    stream::iter(recalculations)
+        .buffer_unordered(MAX_CONCURRENT_HEROES)
+        .try_collect::<Vec<()>>()
+        .await?;
+
+    repositories
+        .job
+        .finish(job_id.to_string(), JobStatus::Done, secs_now())
+        .await
+}
+
+async fn recalculate(repositories: &Repositories, hero: &Hero, now: i64) -> Result<(), Error> {
+    let schedules = repositories
+        .schedule
+        .get(hero.name.to_string(), Some((0, now)))
+        .await?;
+    let entries = ledger_entries(hero, &schedules, now);
+    repositories
+        .punch_clock_ledger
+        .replace(hero.name.to_string(), &entries)
+        .await?;
+    repositories.punch_clock.put_all(&totals(&entries)).await
+}
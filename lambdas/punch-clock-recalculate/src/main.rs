@@ -1,7 +1,8 @@
 use lambda_http::{run, service_fn, Error, Request, RequestExt};
-use model::punch_clock::recalculate_punch_time;
+use model::punch_clock::{ledger_entries, totals};
 use model::time::secs_now;
-use repository::punch_clock::PunchClockRepository;
+use repository::hero::HeroRepository;
+use repository::punch_clock::{PunchClockLedgerRepository, PunchClockRepository};
 use repository::schedule::ScheduleRepository;
 use response::{bad_request, ok};
 use serde::Deserialize;
@@ -16,19 +17,28 @@ async fn main() -> Result<(), Error> {
         .init();
 
     let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
     let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
     let punch_clock_repository_ref = &PunchClockRepository::new(&shared_config);
+    let punch_clock_ledger_repository_ref = &PunchClockLedgerRepository::new(&shared_config);
 
     run(service_fn(move |event: Request| async move {
         match event.path_parameters().first("hero") {
             Some(hero) => {
+                let Some(hero) = hero_repository_ref.find(hero.to_string()).await? else {
+                    return bad_request(format!("Unknown hero {}", hero));
+                };
+                let now = secs_now() as i64;
                 let schedules = schedule_repository_ref
-                    .get(hero.to_string().clone(), Some((0, secs_now() as i64)))
+                    .get(hero.name.clone(), Some((0, now)))
+                    .await?;
+                let entries = ledger_entries(&hero, &schedules, now);
+                punch_clock_ledger_repository_ref
+                    .replace(hero.name.clone(), &entries)
+                    .await?;
+                punch_clock_repository_ref
+                    .put_all(&totals(&entries))
                     .await?;
-                let recalculated = recalculate_punch_time(hero.to_string().clone(), schedules);
-                for punch_clock in recalculated.into_iter() {
-                    punch_clock_repository_ref.put(&punch_clock).await?;
-                }
                 ok(())
             }
             None => bad_request("Could not parse JSON payload for schedule update".into()),
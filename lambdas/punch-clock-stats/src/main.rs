@@ -1,11 +1,18 @@
+use chrono::{Days, NaiveDate};
 use lambda_http::{run, service_fn, Error, Request, RequestExt};
 use model::punch_clock::PunchClock;
 use model::schedule::Schedule;
-use model::time::secs_now;
+use model::stats::{series, GroupBy, Series};
+use model::time::{day_of, end_of_day, secs_now, start_of_day};
 use repository::punch_clock::PunchClockRepository;
 use repository::schedule::ScheduleRepository;
 use response::{bad_request, ok};
 use serde::Serialize;
+use std::str::FromStr;
+
+/// Length of the range if only `to` or no end of the range is given.
+const DEFAULT_RANGE_DAYS: u64 = 365;
+const MAX_RANGE_DAYS: i64 = 10 * 366;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -23,6 +30,59 @@ async fn main() -> Result<(), Error> {
     run(service_fn(move |event: Request| async move {
         match event.path_parameters().first("hero") {
             Some(hero) => {
+                let query = event.query_string_parameters();
+                let mut range = None;
+                if ["from", "to", "group_by"]
+                    .iter()
+                    .any(|name| query.first(name).is_some())
+                {
+                    let date = |name: &str| {
+                        query
+                            .first(name)
+                            .map(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+                            .transpose()
+                    };
+                    let (from, to) = match (date("from"), date("to")) {
+                        (Ok(from), Ok(to)) => {
+                            let to = to.unwrap_or_else(|| day_of(secs_now() as i64));
+                            let from =
+                                from.unwrap_or_else(|| to - Days::new(DEFAULT_RANGE_DAYS - 1));
+                            (from, to)
+                        }
+                        _ => {
+                            return bad_request(
+                                "`from` and `to` have to be dates like 2023-07-01".into(),
+                            )
+                        }
+                    };
+                    if from > to || (to - from).num_days() >= MAX_RANGE_DAYS {
+                        return bad_request(format!(
+                            "`from` has to be before `to` and at most {} days apart",
+                            MAX_RANGE_DAYS
+                        ));
+                    }
+                    let group_by = match query.first("group_by").map(GroupBy::from_str) {
+                        Some(Ok(group_by)) => group_by,
+                        Some(Err(_)) => {
+                            return bad_request(
+                                "`group_by` has to be one of month, quarter or year".into(),
+                            )
+                        }
+                        None => GroupBy::default(),
+                    };
+                    range = Some((from, to, group_by));
+                }
+
+                let series = match range {
+                    Some((from, to, group_by)) => {
+                        let schedules = schedule_repository_ref
+                            .get_in_effect(hero.to_string(), start_of_day(from), end_of_day(to))
+                            .await?;
+                        Some(series(&schedules, from, to, group_by, secs_now() as i64))
+                    }
+                    None => None,
+                };
+
                 let hero_string = hero.to_string();
                 let punch_cards: Vec<PunchClock> =
                     punch_clock_repository_ref.get_all(hero_string).await?;
@@ -34,10 +94,12 @@ async fn main() -> Result<(), Error> {
                     None => ok(Response {
                         punch_cards,
                         current_schedule: None,
+                        series,
                     }),
                     Some(schedule) => ok(Response {
                         punch_cards,
                         current_schedule: Some(schedule),
+                        series,
                     }),
                 }
             }
@@ -52,4 +114,7 @@ async fn main() -> Result<(), Error> {
 struct Response {
     punch_cards: Vec<PunchClock>,
     current_schedule: Option<Schedule>,
+    /// Days served per member and period, only if a range or grouping was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<Vec<Series>>,
 }
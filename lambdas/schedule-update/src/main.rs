@@ -1,16 +1,27 @@
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use email_address::EmailAddress;
 use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
+use model::hero::Hero;
+use model::sync::SyncMode;
+use model::user::NotificationChannel;
+use model::webhook::EventType;
+use notifier::email::{Mailer, Notice};
+use notifier::Notifiers;
 use repository::hero::HeroRepository;
 use repository::schedule::{Operation, ScheduleRepository};
+use repository::slack_user::SlackUserRepository;
+use repository::user::UserRepository;
 use response::{bad_request, ok};
 use serde::de::{SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use serde_json::json;
+use slack::workspace::Workspaces;
+use webhook::Dispatcher;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -24,6 +35,10 @@ async fn main() -> Result<(), Error> {
     let shared_config = aws_config::load_from_env().await;
     let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
     let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+    let dispatcher_ref = &Dispatcher::new(&shared_config);
+    let user_repository_ref = &UserRepository::new(&shared_config);
+    let mailer_ref = &Mailer::from_env().await?;
 
     run(service_fn(move |event: Request| async move {
         match event.path_parameters().first("hero") {
@@ -67,20 +82,60 @@ async fn main() -> Result<(), Error> {
 
                             println!("Updated the schedule: {:?}", schedule_option);
 
+                            let assignees: Vec<String> = payload.assignees.iter().map(|a| a.to_string()).collect();
+                            dispatcher_ref
+                                .emit(
+                                    hero,
+                                    EventType::ScheduleChanged,
+                                    json!({
+                                        "operation": payload.operation,
+                                        "shift_start_time": shift_start_time.timestamp(),
+                                        "assignees": assignees,
+                                    }),
+                                )
+                                .await;
+
+                            let hero_entry = hero_repository_ref.find(hero.to_string()).await?;
+                            if let (Some(mailer), Some(hero_entry)) = (mailer_ref, &hero_entry) {
+                                let notice = match operation {
+                                    Operation::Add => Notice::Assignment,
+                                    Operation::Delete => Notice::Cancellation,
+                                };
+                                mail_assignees(user_repository_ref, mailer, notice, hero_entry, &assignees, shift_start_time.timestamp()).await;
+                            }
+
                             // If it is an ADD operation, update the hero table to include the e-mail address to the members list.
                             if let Operation::Add = operation {
+                                let members = hero_entry.map(|hero| hero.members).unwrap_or_default();
                                 hero_repository_ref.update_members(hero.to_string(), payload.assignees, repository::hero::UpdateOperation::Add).await?;
+                                let added: Vec<&String> = assignees.iter().filter(|a| !members.contains(a)).collect();
+                                if !added.is_empty() {
+                                    dispatcher_ref.emit(hero, EventType::MemberAdded, json!({ "members": added })).await;
+                                }
                             }
 
                             if duration == 0 {
                                 // Need to load the rest of the users for that day
                                 if let Some(schedule) = schedule_repository_ref.get_first_before(hero.to_string(), shift_start_time.timestamp() as u64).await? {
-                                    let client = slack::Client::new(slack::get_slack_token().await?);
-                                    client.usergroups_users_update_with_schedules(vec!(schedule.clone())).await?;
                                     let hero = hero_repository_ref.get(schedule.hero.clone()).await?;
-                                    if let Some(channel) = hero.channel {
-                                        client.post_message(&channel, &schedule.hero, schedule.assignees.clone()).await?
-                                    }
+                                    let notifier = Notifiers::new(Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone()))
+                                        .for_hero(&hero)
+                                        .await?;
+                                    let heroes = vec!((hero, schedule));
+                                    let usergroup_updates = notifier.sync_members(&heroes, SyncMode::Replace).await?;
+                                    println!("Usergroup updates: {:?}", usergroup_updates);
+                                    let (hero, schedule) = &heroes[0];
+                                    notifier.post_handover(hero, schedule).await?;
+                                    dispatcher_ref
+                                        .emit(
+                                            &hero.name,
+                                            EventType::ShiftStarted,
+                                            json!({
+                                                "shift_start_time": schedule.shift_start_time,
+                                                "assignees": schedule.assignees,
+                                            }),
+                                        )
+                                        .await;
                                 }
                             }
 
@@ -96,9 +151,34 @@ async fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Mails the notice to the assignees who chose e-mail. Failures are logged, the schedule is
+/// updated regardless.
+async fn mail_assignees(
+    user_repository: &UserRepository,
+    mailer: &Mailer,
+    notice: Notice,
+    hero: &Hero,
+    assignees: &[String],
+    shift_start_time: i64,
+) {
+    for assignee in assignees {
+        match user_repository.preferences(assignee.to_string()).await {
+            Ok(preferences) if preferences.allows(NotificationChannel::Email) => {
+                if let Err(err) = mailer.send(notice, assignee, hero, shift_start_time).await {
+                    eprintln!("Could not mail {:?} to {}: {:?}", notice, assignee, err);
+                }
+            }
+            Ok(_) => {}
+            Err(err) => eprintln!("Could not get the user {}: {:?}", assignee, err),
+        }
+    }
+}
+
 fn midnight(timezone: &str) -> DateTime<Tz> {
     let tz: Tz = timezone.parse().unwrap();
-    Utc::now().with_timezone(&tz).date().and_hms(0, 0, 0)
+    let today = Utc::now().with_timezone(&tz).date_naive();
+    tz.from_local_datetime(&today.and_hms_opt(0, 0, 0).unwrap())
+        .unwrap()
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -0,0 +1,187 @@
+use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use model::hero::{Hero, Platform};
+use model::schedule::Schedule;
+use model::time::secs_now;
+use model::user::{NotificationChannel, Preferences};
+use notifier::email::{Mailer, Notice};
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use repository::slack_user::SlackUserRepository;
+use repository::user::UserRepository;
+use serde::{Deserialize, Serialize};
+use slack::workspace::Workspaces;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Upper bound of calendar days covering the longest supported reminder lead time.
+const MAX_LOOKAHEAD_DAYS: i64 = 60;
+
+#[derive(Serialize, Deserialize)]
+struct Request {}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+    let workspaces_ref = &Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone());
+    let user_repository_ref = &UserRepository::new(&shared_config);
+    let mailer_ref = &Mailer::from_env().await?;
+
+    run(service_fn(move |_: LambdaEvent<Request>| async move {
+        let now = secs_now() as i64;
+        let heroes = hero_repository_ref.list_active().await?;
+        // Assignees usually show up in several schedules.
+        let mut preferences: HashMap<String, Preferences> = HashMap::new();
+
+        for hero in heroes.iter() {
+            let schedules = schedule_repository_ref
+                .get(
+                    hero.name.clone(),
+                    Some((now, now + MAX_LOOKAHEAD_DAYS * 24 * 60 * 60)),
+                )
+                .await?;
+
+            for schedule in schedules.iter() {
+                for assignee in schedule
+                    .assignees
+                    .iter()
+                    .filter(|assignee| !schedule.reminded.contains(assignee))
+                {
+                    if !preferences.contains_key(assignee) {
+                        match user_repository_ref.preferences(assignee.clone()).await {
+                            Ok(assignee_preferences) => {
+                                preferences.insert(assignee.clone(), assignee_preferences);
+                            }
+                            Err(err) => {
+                                eprintln!(
+                                    "Error getting the preferences of {}: {:?}",
+                                    assignee, err
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                    let assignee_preferences = &preferences[assignee];
+                    // The lead time of the assignee wins over the one of the hero.
+                    let Some(reminder_days) =
+                        assignee_preferences.reminder_days.or(hero.reminder_days)
+                    else {
+                        continue;
+                    };
+                    if !schedule.is_reminder_due(reminder_days, now) {
+                        continue;
+                    }
+                    if let Err(err) = remind(
+                        schedule_repository_ref,
+                        workspaces_ref,
+                        mailer_ref.as_ref(),
+                        (hero, schedule),
+                        assignee.clone(),
+                        assignee_preferences,
+                        now,
+                    )
+                    .await
+                    {
+                        eprintln!(
+                            "Error reminding {} of {}: {:?}",
+                            assignee, schedule.hero, err
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok::<(), Error>(())
+    }))
+    .await?;
+    Ok(())
+}
+
+/// Sends a direct message and a mail, as far as the assignee wants them, unless another run
+/// already did. The reminder only counts as sent if one of them got through. The job runs every
+/// hour from 07:00 to 23:00 UTC, a direct message held back by quiet hours is sent by the first
+/// run after them, as long as the reminder is still due.
+async fn remind(
+    schedule_repository: &ScheduleRepository,
+    workspaces: &Workspaces,
+    mailer: Option<&Mailer>,
+    (hero, schedule): (&Hero, &Schedule),
+    assignee: String,
+    preferences: &Preferences,
+    now: i64,
+) -> Result<(), Error> {
+    // Direct messages only exist on Slack, assignees of Teams heroes are only mailed.
+    let direct_message = hero.platform == Platform::Slack
+        && preferences.allows(NotificationChannel::SlackDm)
+        && !preferences.is_quiet(now);
+    let mailer = mailer.filter(|_| preferences.allows(NotificationChannel::Email));
+    if !direct_message && mailer.is_none() {
+        return Ok(());
+    }
+
+    if !schedule_repository
+        .mark_reminded(&schedule.hero, schedule.shift_start_time, &assignee)
+        .await?
+    {
+        return Ok(());
+    }
+
+    let message = slack::handover::reminder_message(schedule);
+    let messaged = direct_message
+        && match post_direct_message(workspaces, hero, assignee.clone(), &message).await {
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!(
+                    "Error reminding {} of {}: {:?}",
+                    assignee, schedule.hero, err
+                );
+                false
+            }
+        };
+    let mailed = match mailer {
+        Some(mailer) => {
+            match mailer
+                .send(Notice::Reminder, &assignee, hero, schedule.shift_start_time)
+                .await
+            {
+                Ok(_) => true,
+                Err(err) => {
+                    eprintln!(
+                        "Error mailing the reminder to {} of {}: {:?}",
+                        assignee, schedule.hero, err
+                    );
+                    false
+                }
+            }
+        }
+        None => false,
+    };
+
+    if messaged || mailed {
+        println!("Reminded {} of {}", assignee, schedule.hero);
+        Ok(())
+    } else {
+        schedule_repository
+            .unmark_reminded(&schedule.hero, schedule.shift_start_time, &assignee)
+            .await
+    }
+}
+
+async fn post_direct_message(
+    workspaces: &Workspaces,
+    hero: &Hero,
+    email: String,
+    message: &slack::blocks::Message,
+) -> Result<(), Error> {
+    let client = workspaces.client(hero.workspace.as_deref()).await?;
+    Ok(client.post_direct_message(email, message).await?)
+}
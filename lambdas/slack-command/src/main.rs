@@ -0,0 +1,367 @@
+use chrono::{Days, NaiveDate};
+use email_address::EmailAddress;
+use lambda_http::{run, service_fn, Error, Request, RequestPayloadExt};
+use model::hero::Platform;
+use model::time::{day_of, secs_now, start_of_day};
+use repository::hero::{HeroRepository, UpdateOperation};
+use repository::schedule::{Operation, ScheduleRepository};
+use repository::slack_user::SlackUserRepository;
+use response::{bad_request, ok, unauthorized};
+use serde::Deserialize;
+use slack::blocks::{mention, Block, Message};
+use slack::signature::verify_signature;
+use slack::workspace::Workspaces;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+    let signing_secret_ref = &slack::get_slack_signing_secret().await?;
+    let workspaces_ref = &Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone());
+
+    run(service_fn(move |event: Request| async move {
+        let timestamp = header(&event, "X-Slack-Request-Timestamp");
+        let signature = header(&event, "X-Slack-Signature");
+        let body = std::str::from_utf8(event.body().as_ref())?;
+        if let Err(err) =
+            verify_signature(signing_secret_ref, timestamp, body, signature, secs_now())
+        {
+            tracing::warn!("Rejected slash command: {}", err);
+            return unauthorized("Invalid Slack signature".into());
+        }
+
+        match event.payload::<SlashCommand>()? {
+            Some(slash_command) => {
+                // Commands are answered through the workspace they were sent from.
+                let mut workspaces: Vec<Option<String>> = hero_repository_ref
+                    .list_active()
+                    .await?
+                    .into_iter()
+                    .map(|hero| hero.workspace)
+                    .collect();
+                workspaces.sort();
+                workspaces.dedup();
+                let client = workspaces_ref
+                    .for_team(&slash_command.team_id, &workspaces)
+                    .await?;
+                let context = Context {
+                    schedule_repository: schedule_repository_ref,
+                    hero_repository: hero_repository_ref,
+                    workspaces: workspaces_ref,
+                    client: &client,
+                };
+                let today = day_of(secs_now() as i64);
+                let message = match Command::parse(&slash_command.text, today) {
+                    Command::Who(hero) => context.who(hero).await?,
+                    Command::Take(hero, day) => {
+                        context
+                            .take(hero, day, today, slash_command.user_id)
+                            .await?
+                    }
+                    Command::Past(day) => past(day),
+                    Command::List => context.list().await?,
+                    Command::Help => help(),
+                };
+                ok(message)
+            }
+            None => bad_request("Could not parse slash command payload".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+fn header<'a>(event: &'a Request, name: &str) -> &'a str {
+    event
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+}
+
+/// Form encoded payload Slack sends for slash commands.
+#[derive(Deserialize, Debug)]
+struct SlashCommand {
+    team_id: String,
+    user_id: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Who(String),
+    Take(String, NaiveDate),
+    /// Taking a day before today.
+    Past(NaiveDate),
+    List,
+    Help,
+}
+
+impl Command {
+    fn parse(text: &str, today: NaiveDate) -> Command {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match words.as_slice() {
+            ["who", hero] => Command::Who(hero.to_string()),
+            ["take", hero] => Command::Take(hero.to_string(), today),
+            ["take", hero, day] => match parse_day(day, today) {
+                Some(day) if day < today => Command::Past(day),
+                Some(day) => Command::Take(hero.to_string(), day),
+                None => Command::Help,
+            },
+            ["list"] => Command::List,
+            _ => Command::Help,
+        }
+    }
+}
+
+fn parse_day(day: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match day {
+        "today" => Some(today),
+        "tomorrow" => today.checked_add_days(Days::new(1)),
+        _ => NaiveDate::from_str(day).ok(),
+    }
+}
+
+struct Context<'a> {
+    schedule_repository: &'a ScheduleRepository,
+    hero_repository: &'a HeroRepository,
+    workspaces: &'a Workspaces,
+    /// Client of the workspace the command was sent from.
+    client: &'a slack::Client,
+}
+
+impl Context<'_> {
+    async fn who(&self, hero: String) -> Result<Message, Error> {
+        if !self.hero_exists(&hero).await? {
+            return Ok(unknown_hero(&hero));
+        }
+        let text = match self
+            .schedule_repository
+            .get_first_before(hero.clone(), secs_now())
+            .await?
+        {
+            Some(schedule) => {
+                let mut mentions = Vec::new();
+                for assignee in schedule.assignees {
+                    mentions.push(self.mention(assignee).await);
+                }
+                format!("*{}*: {}", hero, mentions.join(", "))
+            }
+            None => format!("Nobody is scheduled for *{}*.", hero),
+        };
+        Ok(Message::ephemeral(text.clone(), vec![Block::section(text)]))
+    }
+
+    async fn take(
+        &self,
+        hero: String,
+        day: NaiveDate,
+        today: NaiveDate,
+        user_id: String,
+    ) -> Result<Message, Error> {
+        if !self.hero_exists(&hero).await? {
+            return Ok(unknown_hero(&hero));
+        }
+
+        let email = match self.client.lookup_email(user_id.clone()).await {
+            Ok(email) => EmailAddress::from_str(&email)?,
+            Err(err) => {
+                tracing::warn!(
+                    "Could not look up the e-mail address of {}: {}",
+                    user_id,
+                    err
+                );
+                let text =
+                    "Could not look up your e-mail address in Slack, please try again later.";
+                return Ok(Message::ephemeral(
+                    text.to_string(),
+                    vec![Block::section(text)],
+                ));
+            }
+        };
+        let shift_start_time = start_of_day(day);
+        let schedule_option = self
+            .schedule_repository
+            .update_assignees(
+                &Operation::Add,
+                &hero,
+                shift_start_time,
+                vec![email.clone()],
+            )
+            .await?;
+        tracing::info!("Updated the schedule: {:?}", schedule_option);
+        self.hero_repository
+            .update_members(hero.clone(), vec![email], UpdateOperation::Add)
+            .await?;
+
+        if day == today {
+            if let Some(schedule) = self
+                .schedule_repository
+                .get_first_before(hero.clone(), shift_start_time as u64)
+                .await?
+            {
+                let hero = self.hero_repository.get(schedule.hero.clone()).await?;
+                // Groups and channels on Teams are left to the nightly sync.
+                if hero.platform == Platform::Slack {
+                    let client = self.workspaces.client(hero.workspace.as_deref()).await?;
+                    let usergroup_updates = client
+                        .usergroups_users_update_with_schedules(vec![schedule.clone()])
+                        .await?;
+                    tracing::info!("Usergroup updates: {:?}", usergroup_updates);
+                    if let Some(channel) = hero.channel {
+                        client.post_handover(&channel, &schedule).await?
+                    }
+                }
+            }
+        }
+
+        let text = format!("{} took *{}* on {}.", mention(&user_id), hero, day);
+        Ok(Message::ephemeral(text.clone(), vec![Block::section(text)]))
+    }
+
+    async fn list(&self) -> Result<Message, Error> {
+        let mut names: Vec<String> = self
+            .hero_repository
+            .list_active()
+            .await?
+            .into_iter()
+            .map(|hero| hero.name)
+            .collect();
+        names.sort();
+        let text = if names.is_empty() {
+            "There are no heroes yet.".to_string()
+        } else {
+            names
+                .iter()
+                .map(|name| format!("• {}", name))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        Ok(Message::ephemeral(
+            text.clone(),
+            vec![Block::section("*Heroes*"), Block::section(text)],
+        ))
+    }
+
+    /// Whether the hero exists and is not archived.
+    async fn hero_exists(&self, hero: &str) -> Result<bool, Error> {
+        Ok(self
+            .hero_repository
+            .find(hero.to_string())
+            .await?
+            .is_some_and(|hero| !hero.is_archived()))
+    }
+
+    /// Mentions the Slack user behind the e-mail address, falling back to the address itself.
+    async fn mention(&self, email: String) -> String {
+        match self.client.user_id_by_email(email.clone()).await {
+            Ok(user_id) => mention(&user_id),
+            Err(_) => email,
+        }
+    }
+}
+
+fn unknown_hero(hero: &str) -> Message {
+    let text = format!("There is no hero called *{}*.", hero);
+    Message::ephemeral(text.clone(), vec![Block::section(text)])
+}
+
+fn past(day: NaiveDate) -> Message {
+    let text = format!(
+        "Provided date is {}. You cannot change the past. Even batman can't.",
+        day
+    );
+    Message::ephemeral(text.clone(), vec![Block::section(text)])
+}
+
+fn help() -> Message {
+    let text = [
+        "`/hero who <hero>` shows who is the hero right now.",
+        "`/hero take <hero> [today|tomorrow|YYYY-MM-DD]` makes you the hero on that day.",
+        "`/hero list` lists all heroes.",
+    ]
+    .join("\n");
+    Message::ephemeral(
+        text.clone(),
+        vec![Block::section(text), Block::context("Hero of the day")],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: &str) -> NaiveDate {
+        NaiveDate::from_str(day).unwrap()
+    }
+
+    #[test]
+    fn parses_commands() {
+        let today = day("2024-02-28");
+        let cases = [
+            ("who oncall", Command::Who("oncall".to_string())),
+            ("  who   oncall ", Command::Who("oncall".to_string())),
+            ("take oncall", Command::Take("oncall".to_string(), today)),
+            (
+                "take oncall today",
+                Command::Take("oncall".to_string(), today),
+            ),
+            (
+                "take oncall tomorrow",
+                Command::Take("oncall".to_string(), day("2024-02-29")),
+            ),
+            (
+                "take oncall 2024-03-04",
+                Command::Take("oncall".to_string(), day("2024-03-04")),
+            ),
+            ("take oncall 2024-02-27", Command::Past(day("2024-02-27"))),
+            ("take oncall 2023-12-31", Command::Past(day("2023-12-31"))),
+            ("list", Command::List),
+            ("help", Command::Help),
+            ("", Command::Help),
+            ("who", Command::Help),
+            ("who oncall now", Command::Help),
+            ("take", Command::Help),
+            ("take oncall yesterday", Command::Help),
+            ("take oncall 2024-02-30", Command::Help),
+            ("take oncall 04.03.2024", Command::Help),
+            ("take oncall 2024-03-04 2024-03-05", Command::Help),
+            ("list oncall", Command::Help),
+            ("WHO oncall", Command::Help),
+        ];
+        for (text, command) in cases {
+            assert_eq!(Command::parse(text, today), command, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn parses_days() {
+        let today = day("2023-12-31");
+        let cases = [
+            ("today", Some(today)),
+            ("tomorrow", Some(day("2024-01-01"))),
+            ("2024-01-15", Some(day("2024-01-15"))),
+            ("2023-01-01", Some(day("2023-01-01"))),
+            ("2023-02-29", None),
+            ("2024-13-01", None),
+            ("15.01.2024", None),
+            ("yesterday", None),
+            ("", None),
+        ];
+        for (text, parsed) in cases {
+            assert_eq!(parse_day(text, today), parsed, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use email_address::EmailAddress;
+use lambda_http::{run, service_fn, Error, Request, RequestPayloadExt};
+use model::hero::Platform;
+use model::schedule::Schedule;
+use model::time::secs_now;
+use model::user::NotificationChannel;
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use repository::slack_user::SlackUserRepository;
+use repository::user::UserRepository;
+use response::{bad_request, ok, unauthorized};
+use slack::blocks::{Block, Message};
+use slack::handover::{escalation_message, HandoverRef, ACKNOWLEDGE_ACTION_ID, DECLINE_ACTION_ID};
+use slack::interactivity::{BlockActions, InteractionRequest};
+use slack::signature::verify_signature;
+use slack::workspace::Workspaces;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+    let user_repository_ref = &UserRepository::new(&shared_config);
+    let signing_secret_ref = &slack::get_slack_signing_secret().await?;
+    let workspaces_ref = &Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone());
+
+    run(service_fn(move |event: Request| async move {
+        let timestamp = header(&event, "X-Slack-Request-Timestamp");
+        let signature = header(&event, "X-Slack-Signature");
+        let body = std::str::from_utf8(event.body().as_ref())?;
+        if let Err(err) =
+            verify_signature(signing_secret_ref, timestamp, body, signature, secs_now())
+        {
+            tracing::warn!("Rejected interaction: {}", err);
+            return unauthorized("Invalid Slack signature".into());
+        }
+
+        let block_actions: BlockActions = match event.payload::<InteractionRequest>()? {
+            Some(request) => serde_json::from_str(&request.payload)?,
+            None => return bad_request("Could not parse interaction payload".into()),
+        };
+
+        // The user is looked up in the workspace the interaction came from.
+        let mut workspaces: Vec<Option<String>> = hero_repository_ref
+            .list_active()
+            .await?
+            .into_iter()
+            .map(|hero| hero.workspace)
+            .collect();
+        workspaces.sort();
+        workspaces.dedup();
+        let client = workspaces_ref
+            .for_team(&block_actions.team.id, &workspaces)
+            .await?;
+        let email =
+            EmailAddress::from_str(&client.lookup_email(block_actions.user.id.clone()).await?)?;
+
+        for action in block_actions.actions.iter() {
+            let handover = match action
+                .value
+                .as_deref()
+                .and_then(|value| HandoverRef::from_str(value).ok())
+            {
+                Some(handover) => handover,
+                None => {
+                    tracing::warn!("Ignoring unknown action {:?}", action);
+                    continue;
+                }
+            };
+
+            let reply = match action.action_id.as_str() {
+                ACKNOWLEDGE_ACTION_ID => {
+                    match schedule_repository_ref
+                        .acknowledge(&handover.hero, handover.shift_start_time, &email)
+                        .await?
+                    {
+                        Some(_) => format!("Thanks for acknowledging *{}*.", handover.hero),
+                        None => not_assigned(&handover),
+                    }
+                }
+                DECLINE_ACTION_ID => {
+                    match schedule_repository_ref
+                        .decline(&handover.hero, handover.shift_start_time, &email)
+                        .await?
+                    {
+                        Some(schedule) => {
+                            escalate(
+                                hero_repository_ref,
+                                schedule_repository_ref,
+                                user_repository_ref,
+                                workspaces_ref,
+                                &schedule,
+                            )
+                            .await?;
+                            format!(
+                                "The owners of *{}* were asked to find a replacement.",
+                                handover.hero
+                            )
+                        }
+                        None => not_assigned(&handover),
+                    }
+                }
+                _ => {
+                    tracing::warn!("Ignoring unknown action {:?}", action);
+                    continue;
+                }
+            };
+
+            if let Some(response_url) = block_actions.response_url.as_ref() {
+                client
+                    .respond(
+                        response_url,
+                        &Message::ephemeral(reply.clone(), vec![Block::section(reply)]),
+                    )
+                    .await?;
+            }
+        }
+
+        ok(())
+    }))
+    .await?;
+    Ok(())
+}
+
+fn header<'a>(event: &'a Request, name: &str) -> &'a str {
+    event
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+}
+
+fn not_assigned(handover: &HandoverRef) -> String {
+    format!(
+        "You are not assigned to *{}* for this shift.",
+        handover.hero
+    )
+}
+
+/// Asks the owners of the hero, and the hero's channel, to find a replacement, like the nightly
+/// escalation does. If all owners who want direct messages are in their quiet hours, the nightly
+/// escalation takes over.
+async fn escalate(
+    hero_repository: &HeroRepository,
+    schedule_repository: &ScheduleRepository,
+    user_repository: &UserRepository,
+    workspaces: &Workspaces,
+    schedule: &Schedule,
+) -> Result<(), Error> {
+    let now = secs_now() as i64;
+    if !schedule.needs_escalation(now) {
+        return Ok(());
+    }
+    let Some(hero) = hero_repository.find(schedule.hero.clone()).await? else {
+        return Ok(());
+    };
+    if hero.platform != Platform::Slack {
+        return Ok(());
+    }
+    let client = workspaces.client(hero.workspace.as_deref()).await?;
+    let mut reachable = vec![];
+    let mut held_back = false;
+    for owner in hero.owners.iter() {
+        let preferences = match user_repository.preferences(owner.clone()).await {
+            Ok(preferences) => preferences,
+            Err(err) => {
+                tracing::error!("Error getting the preferences of {}: {:?}", owner, err);
+                Default::default()
+            }
+        };
+        if !preferences.allows(NotificationChannel::SlackDm) {
+            continue;
+        }
+        if preferences.is_quiet(now) {
+            held_back = true;
+            continue;
+        }
+        reachable.push(owner);
+    }
+    if held_back && reachable.is_empty() {
+        return Ok(());
+    }
+    // Marked first, so a failing message or a second decline does not escalate it again.
+    if !schedule_repository
+        .mark_escalated(&schedule.hero, schedule.shift_start_time, now)
+        .await?
+    {
+        return Ok(());
+    }
+    let message = escalation_message(schedule);
+    for owner in reachable {
+        if let Err(err) = client.post_direct_message(owner.clone(), &message).await {
+            tracing::error!(
+                "Error escalating the handover of {} to {}: {:?}",
+                hero.name,
+                owner,
+                err
+            );
+        }
+    }
+    if let Some(channel) = hero.channel.as_ref() {
+        if let Err(err) = client.post_message(channel, &message).await {
+            tracing::error!(
+                "Error posting the escalation of {} to {}: {:?}",
+                hero.name,
+                channel,
+                err
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use lambda_http::{run, service_fn, Error, Request};
+use model::hero::Hero;
+use model::slack_user::SlackUser;
+use model::time::secs_now;
+use repository::hero::HeroRepository;
+use repository::slack_user::SlackUserRepository;
+use response::ok;
+use serde::Serialize;
+use slack::cache::USER_ID_TTL_SECS;
+use slack::workspace::{workspace_name, Workspaces};
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Serialize, Debug, Default)]
+struct Refresh {
+    workspace: String,
+    cached: usize,
+    changed: usize,
+    removed: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_repository_ref = &SlackUserRepository::new(&shared_config);
+
+    run(service_fn(move |_event: Request| async move {
+        let heroes = hero_repository_ref.list_active().await?;
+        let cached = slack_user_repository_ref.list().await?;
+
+        let mut workspaces: BTreeSet<Option<&str>> = heroes
+            .iter()
+            .map(|hero| hero.workspace.as_deref())
+            .collect();
+        workspaces.insert(None);
+
+        let clients = Workspaces::new();
+        let mut refreshes = vec![];
+        for workspace in workspaces {
+            let client = clients.client(workspace).await?;
+            refreshes.push(
+                refresh(
+                    slack_user_repository_ref,
+                    &client,
+                    workspace,
+                    &heroes,
+                    &cached,
+                )
+                .await?,
+            );
+        }
+
+        ok(refreshes)
+    }))
+    .await?;
+    Ok(())
+}
+
+/// Rebuilds the cached Slack user ids of a workspace from its user list.
+async fn refresh(
+    slack_user_repository: &SlackUserRepository,
+    client: &slack::Client,
+    workspace: Option<&str>,
+    heroes: &[Hero],
+    cached: &[SlackUser],
+) -> Result<Refresh, Error> {
+    let name = workspace_name(workspace);
+
+    // Slack ids by lower-cased e-mail address, as Slack does not preserve its case.
+    let user_ids: HashMap<String, String> = client
+        .users_list()
+        .await?
+        .into_iter()
+        .filter(|user| !user.deleted)
+        .filter_map(|user| Some((user.profile?.email?.to_lowercase(), user.id)))
+        .collect();
+
+    let cached: Vec<&SlackUser> = cached
+        .iter()
+        .filter(|slack_user| slack_user.workspace == name)
+        .collect();
+    let mut emails: BTreeSet<String> = heroes
+        .iter()
+        .filter(|hero| workspace_name(hero.workspace.as_deref()) == name)
+        .flat_map(|hero| hero.members.clone())
+        .collect();
+    emails.extend(cached.iter().map(|slack_user| slack_user.email.clone()));
+
+    let now = secs_now();
+    let mut refresh = Refresh {
+        workspace: name.to_string(),
+        ..Default::default()
+    };
+    for email in emails {
+        let previous = cached
+            .iter()
+            .find(|slack_user| slack_user.email == email)
+            .map(|slack_user| slack_user.slack_user_id.as_str());
+        match user_ids.get(&email.to_lowercase()) {
+            Some(user_id) => {
+                if previous.is_some_and(|previous| previous != user_id) {
+                    println!("Slack user of {} changed to {} in {}", email, user_id, name);
+                    refresh.changed += 1;
+                }
+                slack_user_repository
+                    .put(&SlackUser {
+                        email,
+                        workspace: name.to_string(),
+                        slack_user_id: user_id.clone(),
+                        updated_at: now,
+                        expires_at: now + USER_ID_TTL_SECS,
+                    })
+                    .await?;
+                refresh.cached += 1;
+            }
+            // The e-mail address is gone from Slack, most likely it was changed there.
+            None if previous.is_some() => {
+                println!("No Slack user with email {} in {} anymore", email, name);
+                slack_user_repository
+                    .delete(email.clone(), name.to_string())
+                    .await?;
+                refresh.removed.push(email);
+            }
+            None => println!("No Slack user with email {} in {}", email, name),
+        }
+    }
+
+    Ok(refresh)
+}
@@ -0,0 +1,57 @@
+use lambda_http::{run, service_fn, Error, Request};
+use model::hero::Hero;
+use model::schedule::Schedule;
+use model::sync::SyncMode;
+use model::time::secs_now;
+use notifier::{notifier_key, Notifiers};
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use repository::slack_user::SlackUserRepository;
+use response::ok;
+use slack::workspace::Workspaces;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+
+    // Reports how every hero's usergroup differs from its current assignees without updating it.
+    run(service_fn(move |_event: Request| async move {
+        let heroes = hero_repository_ref.list_active().await?;
+        let now = secs_now();
+        let mut schedules: BTreeMap<String, Vec<(Hero, Schedule)>> = BTreeMap::new();
+        for hero in heroes.into_iter() {
+            if let Some(schedule) = schedule_repository_ref
+                .get_first_before(hero.name.clone(), now)
+                .await?
+            {
+                schedules
+                    .entry(notifier_key(&hero))
+                    .or_default()
+                    .push((hero, schedule));
+            }
+        }
+
+        let notifiers =
+            Notifiers::new(Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone()));
+        let mut updates = vec![];
+        for heroes in schedules.into_values() {
+            let notifier = notifiers.for_hero(&heroes[0].0).await?;
+            updates.extend(notifier.sync_members(&heroes, SyncMode::DryRun).await?);
+        }
+        ok(updates)
+    }))
+    .await?;
+    Ok(())
+}
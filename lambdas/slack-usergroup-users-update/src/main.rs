@@ -1,14 +1,26 @@
 use futures::prelude::*;
 use lambda_runtime::{run, service_fn, Error, LambdaEvent};
-use model::hero::Hero;
-use model::punch_clock::PunchClock;
+use model::hero::{Hero, Platform};
+use model::punch_clock::{ledger_entries, totals};
 use model::schedule::Schedule;
+use model::sync::SyncMode;
+use model::sync::{UsergroupUpdate, UsergroupUpdateOutcome};
 use model::time::{days_diff, secs_now};
+use model::user::NotificationChannel;
+use model::webhook::EventType;
+use notifier::{notifier_key, Notifiers};
 use repository::hero::HeroRepository;
-use repository::punch_clock::PunchClockRepository;
+use repository::punch_clock::{PunchClockLedgerRepository, PunchClockRepository};
 use repository::schedule::{LastTwoSchedules, ScheduleRepository};
+use repository::slack_user::SlackUserRepository;
+use repository::user::UserRepository;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use slack::workspace::Workspaces;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
 use std::time::SystemTime;
+use webhook::Dispatcher;
 
 #[derive(Serialize, Deserialize)]
 struct Request {}
@@ -17,22 +29,29 @@ struct Request {}
 async fn main() -> Result<(), Error> {
     // required to enable CloudWatch error logging by the runtime
     tracing_subscriber::fmt()
+        .json()
         .with_max_level(tracing::Level::INFO)
         // disabling time is handy because CloudWatch will add the ingestion time.
         .without_time()
+        // remove the name of the function from every log entry
+        .with_target(false)
         .init();
 
     let shared_config = aws_config::load_from_env().await;
     let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
     let hero_repository_ref = &HeroRepository::new(&shared_config);
     let punch_clock_repository_ref = &PunchClockRepository::new(&shared_config);
+    let punch_clock_ledger_repository_ref = &PunchClockLedgerRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+    let dispatcher_ref = &Dispatcher::new(&shared_config);
+    let user_repository_ref = &UserRepository::new(&shared_config);
 
     run(service_fn(move |_: LambdaEvent<Request>| async move {
         let secs = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)?
             .as_secs();
 
-        let hero_names = hero_repository_ref.list().await?;
+        let hero_names = hero_repository_ref.list_active().await?;
 
         let repeating_schedules: Vec<Schedule> =
             future::try_join_all(hero_names.iter().map(|hero| {
@@ -55,17 +74,17 @@ async fn main() -> Result<(), Error> {
         .flatten()
         .collect();
 
-        update_schedules_according_to_previous(punch_clock_repository_ref, &schedules_last_two)
-            .await;
+        update_schedules_according_to_previous(
+            punch_clock_repository_ref,
+            punch_clock_ledger_repository_ref,
+            &hero_names,
+            &schedules_last_two,
+            secs as i64,
+        )
+        .await;
 
         let schedules: Vec<Schedule> = schedules_last_two.into_iter().map(|s| s.last).collect();
 
-        let client = slack::Client::new(slack::get_slack_token().await?);
-
-        client
-            .usergroups_users_update_with_schedules(schedules.clone())
-            .await?;
-
         let heroes: Vec<(Hero, Schedule)> =
             future::join_all(schedules.into_iter().map(|schedule| {
                 hero_repository_ref
@@ -77,115 +96,274 @@ async fn main() -> Result<(), Error> {
             .flatten()
             .collect();
 
-        for (hero, schedule) in heroes.into_iter() {
-            if let Some(ref channel) = hero.channel {
-                if days_diff(secs_now() as i64, schedule.shift_start_time) == 0 {
-                    match client
-                        .post_message(&channel, &hero.name, schedule.assignees.clone())
+        // Clients are created per invocation, as each workspace's token may have been rotated.
+        let notifiers =
+            Notifiers::new(Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone()));
+
+        for (key, heroes) in heroes_per_notifier(&heroes) {
+            let synced = match notifiers.for_hero(&heroes[0].0).await {
+                Ok(notifier) => {
+                    notifier
+                        .sync_members(&heroes, SyncMode::SkipUnchanged)
                         .await
-                    {
-                        Ok(_) => {}
-                        Err(err) => {
-                            eprintln!(
-                                "Error posting slack message for hero {:?} in channel {}: {:?}",
-                                hero, channel, err
-                            );
-                        }
-                    }
+                }
+                Err(err) => Err(err),
+            };
+            match synced {
+                Ok(updates) => updates.iter().for_each(log_usergroup_update),
+                Err(err) => eprintln!("Could not sync the groups of {}: {:?}", key, err),
+            }
+        }
+
+        for (hero, schedule) in heroes.iter() {
+            if days_diff(secs_now() as i64, schedule.shift_start_time) != 0 {
+                continue;
+            }
+            dispatcher_ref
+                .emit(
+                    &hero.name,
+                    EventType::ShiftStarted,
+                    json!({
+                        "shift_start_time": schedule.shift_start_time,
+                        "assignees": schedule.assignees,
+                    }),
+                )
+                .await;
+            if hero.channel.is_some() {
+                let posted = match notifiers.for_hero(hero).await {
+                    Ok(notifier) => notifier.post_handover(hero, schedule).await,
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = posted {
+                    eprintln!(
+                        "Error posting the handover of hero {:?} on {}: {:?}",
+                        hero,
+                        notifier_key(hero),
+                        err
+                    );
                 }
             }
         }
 
+        escalate_unacknowledged_handovers(
+            schedule_repository_ref,
+            user_repository_ref,
+            notifiers.slack(),
+            &heroes,
+        )
+        .await;
+
         Ok::<(), Error>(())
     }))
     .await?;
     Ok(())
 }
 
+/// Groups the heroes by the notifier serving them, see `notifier_key`.
+fn heroes_per_notifier(heroes: &[(Hero, Schedule)]) -> BTreeMap<String, Vec<(Hero, Schedule)>> {
+    let mut groups: BTreeMap<String, Vec<(Hero, Schedule)>> = BTreeMap::new();
+    for (hero, schedule) in heroes.iter() {
+        groups
+            .entry(notifier_key(hero))
+            .or_default()
+            .push((hero.clone(), schedule.clone()));
+    }
+    groups
+}
+
+/// Logs the outcome and drift of a usergroup as fields of a structured log entry.
+fn log_usergroup_update(update: &UsergroupUpdate) {
+    let drift = update.drift.clone().unwrap_or_default();
+    let outcome = serde_json::to_string(&update.outcome).unwrap_or_default();
+    match update.outcome {
+        UsergroupUpdateOutcome::Updated
+        | UsergroupUpdateOutcome::Unchanged
+        | UsergroupUpdateOutcome::DryRun => tracing::info!(
+            hero = %update.hero,
+            usergroup_id = ?update.usergroup_id,
+            outcome = %outcome,
+            drifted = !drift.is_empty(),
+            missing = ?drift.missing,
+            unexpected = ?drift.unexpected,
+            "Usergroup synced"
+        ),
+        _ => tracing::warn!(
+            hero = %update.hero,
+            usergroup_id = ?update.usergroup_id,
+            outcome = %outcome,
+            "Usergroup not synced"
+        ),
+    }
+}
+
+/// Asks the owners of every hero whose current handover is still not acknowledged, and the hero's
+/// channel, to find a replacement. If all owners who want direct messages are in their quiet
+/// hours, the escalation is retried by the next run.
+async fn escalate_unacknowledged_handovers(
+    schedule_repository: &ScheduleRepository,
+    user_repository: &UserRepository,
+    workspaces: &Workspaces,
+    heroes: &[(Hero, Schedule)],
+) {
+    let now = secs_now() as i64;
+    for (hero, schedule) in heroes.iter() {
+        if !schedule.needs_escalation(now) {
+            continue;
+        }
+        // Handovers on Teams cannot be acknowledged, there is nothing to escalate.
+        if hero.platform != Platform::Slack {
+            continue;
+        }
+        let client = match workspaces.client(hero.workspace.as_deref()).await {
+            Ok(client) => client,
+            Err(err) => {
+                eprintln!("Error escalating the handover of {}: {:?}", hero.name, err);
+                continue;
+            }
+        };
+        let mut reachable = vec![];
+        let mut held_back = false;
+        for owner in hero.owners.iter() {
+            let preferences = match user_repository.preferences(owner.clone()).await {
+                Ok(preferences) => preferences,
+                Err(err) => {
+                    eprintln!("Error getting the preferences of {}: {:?}", owner, err);
+                    Default::default()
+                }
+            };
+            if !preferences.allows(NotificationChannel::SlackDm) {
+                continue;
+            }
+            if preferences.is_quiet(now) {
+                held_back = true;
+                continue;
+            }
+            reachable.push(owner);
+        }
+        if held_back && reachable.is_empty() {
+            println!(
+                "Holding back the escalation of {} during quiet hours",
+                hero.name
+            );
+            continue;
+        }
+        // Marked first, a handover declined in Slack meanwhile is only escalated once.
+        match schedule_repository
+            .mark_escalated(&schedule.hero, schedule.shift_start_time, now)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                eprintln!(
+                    "Error marking the handover of {} as escalated: {:?}",
+                    hero.name, err
+                );
+                continue;
+            }
+        }
+        let message = slack::handover::escalation_message(schedule);
+        for owner in reachable {
+            if let Err(err) = client.post_direct_message(owner.clone(), &message).await {
+                eprintln!(
+                    "Error escalating the handover of {} to {}: {:?}",
+                    hero.name, owner, err
+                );
+            }
+        }
+        if let Some(channel) = hero.channel.as_ref() {
+            if let Err(err) = client.post_message(channel, &message).await {
+                eprintln!(
+                    "Error posting the escalation of {} to {}: {:?}",
+                    hero.name, channel, err
+                );
+            }
+        }
+    }
+}
+
 async fn update_schedules_according_to_previous(
     punch_clock_repository: &PunchClockRepository,
+    punch_clock_ledger_repository: &PunchClockLedgerRepository,
+    heroes: &[Hero],
     last_two_schedules_vec: &[LastTwoSchedules],
+    now: i64,
 ) {
-    future::join_all(last_two_schedules_vec.iter().map(|last_two_schedules| {
-        update_according_to_previous(punch_clock_repository, &last_two_schedules.previous_to_last)
-    }))
+    future::join_all(
+        last_two_schedules_vec
+            .iter()
+            .filter_map(|last_two_schedules| {
+                heroes
+                    .iter()
+                    .find(|hero| hero.name == last_two_schedules.last.hero)
+                    .map(|hero| (hero, last_two_schedules))
+            })
+            .map(|(hero, last_two_schedules)| {
+                update_according_to_previous(
+                    punch_clock_repository,
+                    punch_clock_ledger_repository,
+                    hero,
+                    last_two_schedules,
+                    now,
+                )
+            }),
+    )
     .await;
 }
 
 async fn update_according_to_previous(
     punch_clock_repository: &PunchClockRepository,
-    previous_to_last: &Option<Schedule>,
+    punch_clock_ledger_repository: &PunchClockLedgerRepository,
+    hero: &Hero,
+    last_two_schedules: &LastTwoSchedules,
+    now: i64,
 ) {
-    match previous_to_last {
-        Some(previous) => {
-            let hero = &previous.hero;
-            let shift_start_time = &previous.shift_start_time;
-            future::join_all(previous.assignees.clone().into_iter().map(|member| {
-                get_punch_clock_and_update(
-                    punch_clock_repository,
-                    hero.clone(),
-                    member.clone(),
-                    *shift_start_time,
-                )
-            }))
-            .await;
-        }
-        None => {} // TODO
+    // Closes the previous shift and credits the open one up to now.
+    let schedules: Vec<Schedule> = last_two_schedules
+        .previous_to_last
+        .iter()
+        .chain([&last_two_schedules.last])
+        .cloned()
+        .collect();
+    let entries = ledger_entries(hero, &schedules, now);
+    if let Err(err) = future::try_join_all(
+        entries
+            .iter()
+            .map(|entry| punch_clock_ledger_repository.put(entry)),
+    )
+    .await
+    {
+        eprintln!("Error recording the shifts of {}: {:?}", hero.name, err);
+        return;
     }
+
+    let members: BTreeSet<&String> = entries.iter().map(|entry| &entry.member).collect();
+    future::join_all(members.into_iter().map(|member| {
+        get_punch_clock_and_update(
+            punch_clock_repository,
+            punch_clock_ledger_repository,
+            &hero.name,
+            member,
+        )
+    }))
+    .await
+    .into_iter()
+    .filter_map(Result::err)
+    .for_each(|err| eprintln!("Error updating the punch clock: {:?}", err));
 }
 
+/// Derives the punch clock of a member from the ledger.
 async fn get_punch_clock_and_update(
     punch_clock_repository: &PunchClockRepository,
-    hero: String,
-    member: String,
-    shift_start_time: i64,
+    punch_clock_ledger_repository: &PunchClockLedgerRepository,
+    hero: &str,
+    member: &str,
 ) -> Result<(), Error> {
-    let days = days_diff(shift_start_time, secs_now() as i64) as u64;
-    match punch_clock_repository.get(&hero, member.clone()).await? {
-        None => {
-            update_punch_clock(
-                punch_clock_repository,
-                hero.clone(),
-                member.clone(),
-                days,
-                shift_start_time,
-                shift_start_time,
-            )
-            .await
-        }
-        Some(punch_clock) => {
-            if punch_clock.last_punch != shift_start_time {
-                update_punch_clock(
-                    punch_clock_repository,
-                    hero.clone(),
-                    member.clone(),
-                    days + punch_clock.days,
-                    punch_clock.first_punch,
-                    shift_start_time,
-                )
-                .await
-            } else {
-                Ok(())
-            }
-        }
+    let entries = punch_clock_ledger_repository
+        .get(hero.to_string(), Some(member.to_string()), None)
+        .await?;
+    for punch_clock in totals(&entries) {
+        punch_clock_repository.put(&punch_clock).await?;
     }
-}
-
-async fn update_punch_clock(
-    punch_clock_repository: &PunchClockRepository,
-    hero: String,
-    member: String,
-    days: u64,
-    first_punch: i64,
-    last_punch: i64,
-) -> Result<(), Error> {
-    punch_clock_repository
-        .put(&PunchClock {
-            hero,
-            member,
-            days,
-            first_punch,
-            last_punch,
-        })
-        .await
+    Ok(())
 }
@@ -0,0 +1,126 @@
+use chrono::{Datelike, Days, NaiveDate};
+use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use model::hero::{Hero, Platform};
+use model::rotation::assignees_per_day;
+use model::time::{day_of, end_of_day, secs_now, start_of_day};
+use repository::hero::HeroRepository;
+use repository::schedule::ScheduleRepository;
+use repository::slack_user::SlackUserRepository;
+use serde::{Deserialize, Serialize};
+use slack::blocks::mention;
+use slack::digest::{digest_message, DigestEntry};
+use slack::workspace::Workspaces;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize)]
+struct Request {}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let schedule_repository_ref = &ScheduleRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let slack_user_cache_ref = &Arc::new(SlackUserRepository::new(&shared_config));
+    let workspaces_ref = &Workspaces::new().with_user_id_cache(slack_user_cache_ref.clone());
+
+    run(service_fn(move |_: LambdaEvent<Request>| async move {
+        let today = day_of(secs_now() as i64);
+        let week_start = today - Days::new(today.weekday().num_days_from_monday() as u64);
+        let days: Vec<NaiveDate> = (0..5).map(|n| week_start + Days::new(n)).collect();
+
+        // Channel ids are only unique within a workspace.
+        let mut channels: BTreeMap<(Option<String>, String), Vec<Hero>> = BTreeMap::new();
+        for hero in hero_repository_ref.list_active().await?.into_iter() {
+            // The digest is a Slack message, on Teams `channel` is a webhook URL.
+            if hero.platform != Platform::Slack {
+                continue;
+            }
+            if let Some(channel) = hero.channel.clone() {
+                channels
+                    .entry((hero.workspace.clone(), channel))
+                    .or_default()
+                    .push(hero);
+            }
+        }
+
+        let mut mentions: HashMap<(String, String), String> = HashMap::new();
+
+        for ((workspace, channel), mut heroes) in channels.into_iter() {
+            let client = match workspaces_ref.client(workspace.as_deref()).await {
+                Ok(client) => client,
+                Err(err) => {
+                    eprintln!(
+                        "Error posting the weekly digest in channel {}: {:?}",
+                        channel, err
+                    );
+                    continue;
+                }
+            };
+            heroes.sort_by(|a, b| a.name.cmp(&b.name));
+            let mut entries = Vec::new();
+            for hero in heroes.into_iter() {
+                let schedules = schedule_repository_ref
+                    .get_in_effect(
+                        hero.name.clone(),
+                        start_of_day(week_start),
+                        end_of_day(week_start + Days::new(6)),
+                    )
+                    .await?;
+                let mut assigned_days = Vec::new();
+                for (day, assignees) in assignees_per_day(&schedules, &days, hero.shift_days) {
+                    let mut users = Vec::new();
+                    for assignee in assignees {
+                        users.push(mention_of(&client, &mut mentions, assignee).await);
+                    }
+                    assigned_days.push((day, users));
+                }
+                entries.push(DigestEntry {
+                    hero: hero.name,
+                    template: hero.digest_template,
+                    days: assigned_days,
+                });
+            }
+
+            if let Err(err) = client
+                .post_message(&channel, &digest_message(week_start, &entries))
+                .await
+            {
+                eprintln!(
+                    "Error posting the weekly digest in channel {}: {:?}",
+                    channel, err
+                );
+            }
+        }
+
+        Ok::<(), Error>(())
+    }))
+    .await?;
+    Ok(())
+}
+
+/// Mentions the Slack user behind the e-mail address, falling back to the address itself. User
+/// ids differ between workspaces, so mentions are remembered per workspace.
+async fn mention_of(
+    client: &slack::Client,
+    mentions: &mut HashMap<(String, String), String>,
+    email: String,
+) -> String {
+    let key = (client.workspace().to_string(), email.clone());
+    if let Some(mention) = mentions.get(&key) {
+        return mention.clone();
+    }
+    let resolved = match client.user_id_by_email(email.clone()).await {
+        Ok(user_id) => mention(&user_id),
+        Err(_) => email.clone(),
+    };
+    mentions.insert(key, resolved.clone());
+    resolved
+}
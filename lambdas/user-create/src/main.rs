@@ -22,6 +22,7 @@ async fn main() -> Result<(), Error> {
                     email: email.into(),
                     last_login: None,
                     last_seen_release_notes: None,
+                    preferences: Default::default(),
                 };
                 ok(repository_ref.put(&user).await?)
             }
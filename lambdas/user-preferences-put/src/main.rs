@@ -0,0 +1,44 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
+use model::user::Preferences;
+use repository::user::UserRepository;
+use response::{bad_request, caller_email, forbidden, is_admin, ok};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let repository_ref = &UserRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("user") {
+            Some(email) => {
+                // Only admins may change the preferences of somebody else.
+                let caller = caller_email(&event).unwrap_or_default();
+                if !caller.eq_ignore_ascii_case(email) && !is_admin(&caller) {
+                    return forbidden("Preferences can only be changed by their user".into());
+                }
+                match event.payload::<Preferences>()? {
+                    Some(preferences) => match preferences.validate() {
+                        Ok(()) => {
+                            repository_ref
+                                .update_preferences(email.to_string(), &preferences)
+                                .await?;
+                            ok(preferences)
+                        }
+                        Err(err) => bad_request(err),
+                    },
+                    None => bad_request("Could not parse JSON payload for preferences".into()),
+                }
+            }
+            _ => bad_request("Expected user".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt, RequestPayloadExt};
+use model::time::secs_now;
+use model::webhook::{EventType, Subscription};
+use repository::hero::HeroRepository;
+use repository::webhook::WebhookRepository;
+use response::{bad_request, ok};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+    let webhook_repository_ref = &WebhookRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match event.path_parameters().first("hero") {
+            Some(hero) => match event.payload::<Payload>()? {
+                Some(payload) => {
+                    if !payload.url.starts_with("https://") {
+                        return bad_request("Webhook url has to use https".into());
+                    }
+                    if payload.events.is_empty() {
+                        return bad_request("Expected at least one event".into());
+                    }
+                    if hero_repository_ref.find(hero.to_string()).await?.is_none() {
+                        return bad_request(format!("Hero {} does not exist", hero));
+                    }
+
+                    let subscription = Subscription {
+                        hero: hero.to_string(),
+                        id: Uuid::new_v4().to_string(),
+                        url: payload.url,
+                        secret: webhook::new_secret(),
+                        events: payload.events,
+                        created_at: secs_now(),
+                    };
+                    webhook_repository_ref.put(&subscription).await?;
+                    ok(Created {
+                        secret: subscription.secret.clone(),
+                        subscription,
+                    })
+                }
+                None => bad_request("Could not parse JSON payload for webhook".into()),
+            },
+            _ => bad_request("Expected hero".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
+
+#[derive(Deserialize, Debug)]
+struct Payload {
+    url: String,
+    events: Vec<EventType>,
+}
+
+/// The secret is only returned once, listing the webhooks leaves it out.
+#[derive(Serialize, Debug)]
+struct Created {
+    #[serde(flatten)]
+    subscription: Subscription,
+    secret: String,
+}
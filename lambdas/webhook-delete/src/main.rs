@@ -0,0 +1,45 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use repository::hero::HeroRepository;
+use repository::webhook::WebhookRepository;
+use response::{bad_request, caller_email, forbidden, ok};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let repository_ref = &WebhookRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match (
+            event.path_parameters().first("hero"),
+            event.path_parameters().first("webhook"),
+        ) {
+            (Some(hero), Some(webhook)) => {
+                // Only members of the hero manage its webhooks.
+                let is_member = match hero_repository_ref.find(hero.to_string()).await? {
+                    Some(hero) => {
+                        caller_email(&event).is_some_and(|email| hero.members.contains(&email))
+                    }
+                    None => false,
+                };
+                if !is_member {
+                    return forbidden(format!("Only members of {} manage its webhooks", hero));
+                }
+                repository_ref
+                    .delete(hero.to_string(), webhook.to_string())
+                    .await?;
+                ok(())
+            }
+            _ => bad_request("Expected hero and webhook".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
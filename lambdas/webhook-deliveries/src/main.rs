@@ -0,0 +1,69 @@
+use lambda_http::{run, service_fn, Error, Request, RequestExt};
+use repository::hero::HeroRepository;
+use repository::webhook::{WebhookDeliveryRepository, WebhookRepository};
+use response::{bad_request, caller_email, forbidden, ok};
+
+const DEFAULT_LIMIT: i32 = 50;
+const MAX_LIMIT: i32 = 200;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let webhook_repository_ref = &WebhookRepository::new(&shared_config);
+    let delivery_repository_ref = &WebhookDeliveryRepository::new(&shared_config);
+    let hero_repository_ref = &HeroRepository::new(&shared_config);
+
+    run(service_fn(move |event: Request| async move {
+        match (
+            event.path_parameters().first("hero"),
+            event.path_parameters().first("webhook"),
+        ) {
+            (Some(hero), Some(webhook)) => {
+                // Only members of the hero manage its webhooks.
+                let is_member = match hero_repository_ref.find(hero.to_string()).await? {
+                    Some(hero) => {
+                        caller_email(&event).is_some_and(|email| hero.members.contains(&email))
+                    }
+                    None => false,
+                };
+                if !is_member {
+                    return forbidden(format!("Only members of {} manage its webhooks", hero));
+                }
+                let limit = match event.query_string_parameters().first("limit") {
+                    Some(limit) => match limit.parse::<i32>() {
+                        Ok(limit) if (1..=MAX_LIMIT).contains(&limit) => limit,
+                        _ => {
+                            return bad_request(format!(
+                                "`limit` has to be a number between 1 and {}",
+                                MAX_LIMIT
+                            ))
+                        }
+                    },
+                    None => DEFAULT_LIMIT,
+                };
+
+                // Deliveries are keyed by webhook only, make sure it belongs to the hero.
+                if webhook_repository_ref
+                    .get(hero.to_string(), webhook.to_string())
+                    .await?
+                    .is_none()
+                {
+                    return bad_request(format!("Hero {} has no webhook {}", hero, webhook));
+                }
+                ok(delivery_repository_ref
+                    .list(webhook.to_string(), limit)
+                    .await?)
+            }
+            _ => bad_request("Expected hero and webhook".into()),
+        }
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use lambda_runtime::{run, service_fn, Error, LambdaEvent};
+use serde::{Deserialize, Serialize};
+use webhook::Dispatcher;
+
+#[derive(Serialize, Deserialize)]
+struct Request {}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    // required to enable CloudWatch error logging by the runtime
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        // disabling time is handy because CloudWatch will add the ingestion time.
+        .without_time()
+        .init();
+
+    let shared_config = aws_config::load_from_env().await;
+    let dispatcher_ref = &Dispatcher::new(&shared_config);
+
+    run(service_fn(move |_: LambdaEvent<Request>| async move {
+        let retried = dispatcher_ref.retry_due().await?;
+        println!("Retried {} webhook deliveries", retried);
+        Ok::<(), Error>(())
+    }))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,501 @@
+use crate::hero::Hero;
+use crate::schedule::Schedule;
+use crate::time::{day_of, start_of_day};
+use anyhow::anyhow;
+use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// Allowance paid per day on call.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct Rates {
+    pub weekday: f64,
+    pub weekend: f64,
+    /// Applies to the holidays of the hero instead of the weekday or weekend rate.
+    pub holiday: f64,
+}
+
+impl Rates {
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.weekday, self.weekend, self.holiday]
+            .iter()
+            .any(|rate| !rate.is_finite() || *rate < 0.0)
+        {
+            return Err("Rates have to be positive numbers".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Kind of a day on call, a member on call for several heroes on the same day is paid the
+/// highest one once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum DayKind {
+    Weekday,
+    Weekend,
+    Holiday,
+}
+
+/// Days a member was on call in a month and the allowance for them. In corrections the numbers
+/// are the difference to what has been paid and may be negative.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Line {
+    pub member: String,
+    pub weekday_days: i64,
+    pub weekend_days: i64,
+    pub holiday_days: i64,
+    pub amount: f64,
+}
+
+impl Line {
+    fn new(member: &str, weekday_days: i64, weekend_days: i64, holiday_days: i64) -> Line {
+        Line {
+            member: member.to_owned(),
+            weekday_days,
+            weekend_days,
+            holiday_days,
+            amount: 0.0,
+        }
+    }
+
+    fn priced(mut self, rates: &Rates) -> Line {
+        self.amount = self.weekday_days as f64 * rates.weekday
+            + self.weekend_days as f64 * rates.weekend
+            + self.holiday_days as f64 * rates.holiday;
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.weekday_days == 0 && self.weekend_days == 0 && self.holiday_days == 0
+    }
+}
+
+/// Lines of a month handed over to be paid. The first sign-off locks the month and its rates,
+/// signing it off again pays the corrections since.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignOff {
+    pub month: String,
+    pub signed_off_at: u64,
+    pub signed_off_by: String,
+    pub rates: Rates,
+    pub lines: Vec<Line>,
+}
+
+/// Compensation report of a month.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Compensation {
+    pub month: String,
+    pub rates: Rates,
+    pub locked: bool,
+    /// Days on call according to the current schedules.
+    pub lines: Vec<Line>,
+    /// Sum of all sign-offs.
+    pub paid: Vec<Line>,
+    /// Difference between the current lines and what has been paid, empty until the month is
+    /// locked.
+    pub corrections: Vec<Line>,
+    pub sign_offs: Vec<SignOff>,
+}
+
+impl Compensation {
+    /// Report of `month` with the given `lines`. Once signed off, the rates of the first sign-off
+    /// apply and the lines are expected to be priced with them.
+    pub fn new(month: NaiveDate, rates: Rates, lines: Vec<Line>, sign_offs: Vec<SignOff>) -> Self {
+        let paid = paid(&sign_offs);
+        let corrections = if sign_offs.is_empty() {
+            vec![]
+        } else {
+            corrections(&lines, &paid)
+        };
+        Compensation {
+            month: month_label(month),
+            rates: sign_offs.first().map(|s| s.rates).unwrap_or(rates),
+            locked: !sign_offs.is_empty(),
+            lines,
+            paid,
+            corrections,
+            sign_offs,
+        }
+    }
+
+    /// Lines due with the next sign-off: all of them until the month is locked, the corrections
+    /// afterwards.
+    pub fn due(&self) -> &[Line] {
+        if self.locked {
+            &self.corrections
+        } else {
+            &self.lines
+        }
+    }
+
+    /// Spreadsheet of the report, one row per member and status: `open` before the sign-off,
+    /// `paid` and `correction` afterwards.
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("month,member,status,weekday_days,weekend_days,holiday_days,amount\n");
+        let rows: Vec<(&str, &Line)> = if self.locked {
+            self.paid
+                .iter()
+                .map(|line| ("paid", line))
+                .chain(self.corrections.iter().map(|line| ("correction", line)))
+                .collect()
+        } else {
+            self.lines.iter().map(|line| ("open", line)).collect()
+        };
+        for (status, line) in rows {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.2}\n",
+                self.month,
+                csv_field(&line.member),
+                status,
+                line.weekday_days,
+                line.weekend_days,
+                line.holiday_days,
+                line.amount
+            ));
+        }
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Parses a month given as `YYYY-MM` into its first day.
+pub fn parse_month(month: &str) -> anyhow::Result<NaiveDate> {
+    Ok(NaiveDate::parse_from_str(
+        &format!("{}-01", month),
+        "%Y-%m-%d",
+    )?)
+}
+
+pub fn month_label(month: NaiveDate) -> String {
+    month.format("%Y-%m").to_string()
+}
+
+/// Start of `month` and of the month after, in seconds.
+pub fn month_range(month: NaiveDate) -> (i64, i64) {
+    let next = month
+        .checked_add_months(chrono::Months::new(1))
+        .expect("Invalid date");
+    (start_of_day(month), start_of_day(next))
+}
+
+/// Counts the days each member was on call in `month` across all heroes, until `now`. The
+/// schedules of a hero have to be sorted and start with the one in effect at the start of the
+/// month. Each shift lasts until the next schedule starts and counts the days like the punch
+/// clock: the day the shift starts on up to the day it ends on.
+pub fn lines(
+    heroes: &[(Hero, Vec<Schedule>)],
+    month: NaiveDate,
+    rates: &Rates,
+    now: i64,
+) -> Vec<Line> {
+    let (month_start, month_end) = month_range(month);
+    let mut days: BTreeMap<&str, BTreeMap<NaiveDate, DayKind>> = BTreeMap::new();
+    for (hero, schedules) in heroes.iter() {
+        for (index, schedule) in schedules.iter().enumerate() {
+            let start = schedule.shift_start_time.max(month_start);
+            let end = schedules
+                .get(index + 1)
+                .map(|next| next.shift_start_time)
+                .unwrap_or(now)
+                .min(month_end)
+                .min(now);
+            if start >= end {
+                continue;
+            }
+            let end_day = day_of(end);
+            for day in day_of(start).iter_days().take_while(|day| *day < end_day) {
+                let kind = if hero.weights.holidays.contains(&day) {
+                    DayKind::Holiday
+                } else if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                    DayKind::Weekend
+                } else {
+                    DayKind::Weekday
+                };
+                for assignee in schedule.assignees.iter() {
+                    let served = days.entry(assignee.as_str()).or_default();
+                    let known = served.entry(day).or_insert(kind);
+                    *known = (*known).max(kind);
+                }
+            }
+        }
+    }
+
+    days.into_iter()
+        .map(|(member, days)| {
+            let count = |kind: DayKind| days.values().filter(|k| **k == kind).count() as i64;
+            Line::new(
+                member,
+                count(DayKind::Weekday),
+                count(DayKind::Weekend),
+                count(DayKind::Holiday),
+            )
+            .priced(rates)
+        })
+        .collect()
+}
+
+/// Sums up the lines of all sign-offs per member.
+pub fn paid(sign_offs: &[SignOff]) -> Vec<Line> {
+    let mut paid: BTreeMap<&str, Line> = BTreeMap::new();
+    for line in sign_offs.iter().flat_map(|sign_off| sign_off.lines.iter()) {
+        let sum = paid
+            .entry(line.member.as_str())
+            .or_insert_with(|| Line::new(&line.member, 0, 0, 0));
+        sum.weekday_days += line.weekday_days;
+        sum.weekend_days += line.weekend_days;
+        sum.holiday_days += line.holiday_days;
+        sum.amount += line.amount;
+    }
+    paid.into_values().collect()
+}
+
+/// Difference of the current lines to what has been paid, members without a difference are left
+/// out.
+pub fn corrections(lines: &[Line], paid: &[Line]) -> Vec<Line> {
+    let mut members: Vec<&str> = lines
+        .iter()
+        .chain(paid.iter())
+        .map(|line| line.member.as_str())
+        .collect();
+    members.sort();
+    members.dedup();
+
+    let find = |lines: &[Line], member: &str| -> Line {
+        lines
+            .iter()
+            .find(|line| line.member == member)
+            .cloned()
+            .unwrap_or_else(|| Line::new(member, 0, 0, 0))
+    };
+    members
+        .into_iter()
+        .map(|member| {
+            let (current, paid) = (find(lines, member), find(paid, member));
+            Line {
+                member: member.to_owned(),
+                weekday_days: current.weekday_days - paid.weekday_days,
+                weekend_days: current.weekend_days - paid.weekend_days,
+                holiday_days: current.holiday_days - paid.holiday_days,
+                amount: current.amount - paid.amount,
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for Line {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let number = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_n().ok())
+                .cloned()
+                .ok_or_else(|| anyhow!("{} attribute is missing in the compensation line", name))
+        };
+
+        Ok(Line {
+            member: item
+                .get("member")
+                .and_then(|attr| attr.as_s().ok())
+                .cloned()
+                .ok_or_else(|| anyhow!("member attribute is missing in the compensation line"))?,
+            weekday_days: i64::from_str(&number("weekday_days")?)?,
+            weekend_days: i64::from_str(&number("weekend_days")?)?,
+            holiday_days: i64::from_str(&number("holiday_days")?)?,
+            amount: f64::from_str(&number("amount")?)?,
+        })
+    }
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for SignOff {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .cloned()
+                .ok_or_else(|| anyhow!("{} attribute is missing in the sign-off entry", name))
+        };
+        let number = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_n().ok())
+                .cloned()
+                .ok_or_else(|| anyhow!("{} attribute is missing in the sign-off entry", name))
+        };
+
+        Ok(SignOff {
+            month: string("month")?,
+            signed_off_at: u64::from_str(&number("signed_off_at")?)?,
+            signed_off_by: string("signed_off_by")?,
+            rates: Rates {
+                weekday: f64::from_str(&number("weekday_rate")?)?,
+                weekend: f64::from_str(&number("weekend_rate")?)?,
+                holiday: f64::from_str(&number("holiday_rate")?)?,
+            },
+            lines: item
+                .get("lines")
+                .and_then(|attr| attr.as_l().ok())
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .filter_map(|line| line.as_m().ok())
+                        .map(Line::try_from)
+                        .collect::<anyhow::Result<Vec<Line>>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hero::ShiftWeights;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, m, d).unwrap()
+    }
+
+    fn hero(name: &str, holidays: Vec<NaiveDate>) -> Hero {
+        Hero {
+            name: name.to_string(),
+            weights: ShiftWeights {
+                holidays,
+                ..ShiftWeights::default()
+            },
+            ..Hero::default()
+        }
+    }
+
+    fn schedule(hero: &str, day: NaiveDate, assignees: Vec<&str>) -> Schedule {
+        Schedule {
+            hero: hero.to_string(),
+            shift_start_time: start_of_day(day),
+            assignees: assignees.into_iter().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    const RATES: Rates = Rates {
+        weekday: 10.0,
+        weekend: 20.0,
+        holiday: 50.0,
+    };
+
+    #[test]
+    fn counts_days_once_across_heroes() {
+        // Friday, 2022-12-23 until Wednesday, 2022-12-28 with Christmas on Sunday and Monday.
+        let heroes = vec![
+            (
+                hero("oncall", vec![day(12, 25), day(12, 26)]),
+                vec![
+                    schedule("oncall", day(12, 23), vec!["a"]),
+                    schedule("oncall", day(12, 28), vec!["b"]),
+                ],
+            ),
+            (
+                hero("release", vec![]),
+                vec![
+                    schedule("release", day(12, 22), vec!["a"]),
+                    schedule("release", day(12, 25), vec!["b"]),
+                ],
+            ),
+        ];
+
+        let lines = lines(&heroes, day(12, 1), &RATES, start_of_day(day(12, 29)));
+
+        assert_eq!(
+            lines[0],
+            Line {
+                member: "a".to_string(),
+                weekday_days: 3,
+                weekend_days: 1,
+                holiday_days: 2,
+                amount: 150.0
+            }
+        );
+        // Christmas is no holiday of the release hero.
+        assert_eq!(lines[1].weekend_days, 1);
+        assert_eq!(lines[1].holiday_days, 0);
+        assert_eq!(lines[1].weekday_days, 3);
+    }
+
+    #[test]
+    fn reports_corrections_after_sign_off() {
+        let heroes = vec![(
+            hero("oncall", vec![]),
+            vec![
+                schedule("oncall", day(11, 28), vec!["a"]),
+                schedule("oncall", day(12, 5), vec!["b"]),
+            ],
+        )];
+        let now = start_of_day(day(12, 1));
+        let signed = lines(&heroes, day(11, 1), &RATES, now);
+        let sign_off = SignOff {
+            month: "2022-11".to_string(),
+            signed_off_at: now as u64,
+            signed_off_by: "hr@example.com".to_string(),
+            rates: RATES,
+            lines: signed,
+        };
+
+        // The shift of b is moved forward into November afterwards.
+        let edited = vec![(
+            hero("oncall", vec![]),
+            vec![
+                schedule("oncall", day(11, 28), vec!["a"]),
+                schedule("oncall", day(11, 30), vec!["b"]),
+            ],
+        )];
+        let compensation = Compensation::new(
+            day(11, 1),
+            Rates::default(),
+            lines(&edited, day(11, 1), &RATES, now),
+            vec![sign_off],
+        );
+
+        assert!(compensation.locked);
+        assert_eq!(compensation.rates, RATES);
+        assert_eq!(compensation.paid[0].weekday_days, 3);
+        assert_eq!(
+            compensation.corrections,
+            vec![
+                Line {
+                    member: "a".to_string(),
+                    weekday_days: -1,
+                    weekend_days: 0,
+                    holiday_days: 0,
+                    amount: -10.0
+                },
+                Line {
+                    member: "b".to_string(),
+                    weekday_days: 1,
+                    weekend_days: 0,
+                    holiday_days: 0,
+                    amount: 10.0
+                },
+            ]
+        );
+        assert_eq!(
+            compensation.to_csv(),
+            "month,member,status,weekday_days,weekend_days,holiday_days,amount\n\
+             2022-11,a,paid,3,0,0,30.00\n\
+             2022-11,a,correction,-1,0,0,-10.00\n\
+             2022-11,b,correction,1,0,0,10.00\n"
+        );
+    }
+}
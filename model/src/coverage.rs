@@ -0,0 +1,99 @@
+use crate::hero::Hero;
+use crate::rotation::assignees_per_day;
+use crate::schedule::Schedule;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CoverageReport {
+    pub hero: String,
+    pub min_assignees: u32,
+    /// Days without any assignee.
+    pub gaps: Vec<NaiveDate>,
+    /// Days with fewer than `min_assignees` assignees.
+    pub understaffed: Vec<UnderstaffedDay>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct UnderstaffedDay {
+    pub day: NaiveDate,
+    pub assignees: Vec<String>,
+}
+
+impl CoverageReport {
+    pub fn is_covered(&self) -> bool {
+        self.gaps.is_empty() && self.understaffed.is_empty()
+    }
+}
+
+/// Reports the days of the horizon the hero is not or not sufficiently covered. Expects the
+/// schedules to be sorted by `shift_start_time` and to include the one in effect on the first day.
+pub fn analyze(hero: &Hero, schedules: &[Schedule], days: &[NaiveDate]) -> CoverageReport {
+    let min_assignees = hero.min_assignees.unwrap_or(1);
+    let mut gaps = Vec::new();
+    let mut understaffed = Vec::new();
+
+    for (day, assignees) in assignees_per_day(schedules, days, hero.shift_days) {
+        if assignees.is_empty() {
+            gaps.push(day);
+        } else if (assignees.len() as u32) < min_assignees {
+            understaffed.push(UnderstaffedDay { day, assignees });
+        }
+    }
+
+    CoverageReport {
+        hero: hero.name.clone(),
+        min_assignees,
+        gaps,
+        understaffed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coverage::{analyze, UnderstaffedDay};
+    use crate::hero::Hero;
+    use crate::schedule::Schedule;
+    use crate::time::start_of_day;
+    use chrono::NaiveDate;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 12, d).unwrap()
+    }
+
+    fn schedule(d: u32, assignees: Vec<&str>) -> Schedule {
+        Schedule {
+            hero: "hero".to_string(),
+            shift_start_time: start_of_day(day(d)),
+            assignees: assignees.into_iter().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gaps_and_understaffed_days() {
+        let hero = Hero {
+            name: "hero".to_string(),
+            shift_days: Some(1),
+            min_assignees: Some(2),
+            ..Default::default()
+        };
+        let schedules = vec![
+            schedule(19, vec!["a@b.de", "c@d.de"]),
+            schedule(21, vec!["a@b.de"]),
+        ];
+        let days: Vec<NaiveDate> = (19..=21).map(day).collect();
+
+        let report = analyze(&hero, &schedules, &days);
+
+        assert!(!report.is_covered());
+        assert_eq!(report.gaps, vec![day(20)]);
+        assert_eq!(
+            report.understaffed,
+            vec![UnderstaffedDay {
+                day: day(21),
+                assignees: vec!["a@b.de".to_string()]
+            }]
+        );
+    }
+}
@@ -1,13 +1,310 @@
+use crate::time::day_of;
+use crate::user::MAX_REMINDER_DAYS;
 use anyhow::anyhow;
 use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{Datelike, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Chat platform a hero's group and handovers live in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    #[default]
+    Slack,
+    Teams,
+}
+
+impl FromStr for Platform {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slack" => Ok(Platform::Slack),
+            "teams" => Ok(Platform::Teams),
+            _ => Err(anyhow!("unknown platform: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Platform::Slack => write!(f, "slack"),
+            Platform::Teams => write!(f, "teams"),
+        }
+    }
+}
+
+/// Points a day of a shift is worth. The defaults only count weekdays, like the business days of
+/// the punch clock.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ShiftWeights {
+    pub weekday: f64,
+    pub weekend: f64,
+    /// Applies to the days in `holidays` instead of their weekday or weekend weight.
+    pub holiday: f64,
+    /// Added to every day if the hero also covers the night after it.
+    pub night: Option<f64>,
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl Default for ShiftWeights {
+    fn default() -> Self {
+        ShiftWeights {
+            weekday: 1.0,
+            weekend: 0.0,
+            holiday: 1.0,
+            night: None,
+            holidays: vec![],
+        }
+    }
+}
+
+impl ShiftWeights {
+    /// Points of a shift from `start` until `end`, counting the days in Berlin like `days_diff`:
+    /// the day the shift starts on up to the day it ends on.
+    pub fn points(&self, start: i64, end: i64) -> f64 {
+        let (first_day, end_day) = (day_of(start), day_of(end));
+        first_day
+            .iter_days()
+            .take_while(|day| *day < end_day)
+            .map(|day| self.points_of(day))
+            .sum()
+    }
+
+    fn points_of(&self, day: NaiveDate) -> f64 {
+        let weight = if self.holidays.contains(&day) {
+            self.holiday
+        } else if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            self.weekend
+        } else {
+            self.weekday
+        };
+        weight + self.night.unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.weekday,
+            self.weekend,
+            self.holiday,
+            self.night.unwrap_or(0.0),
+        ];
+        if weights
+            .iter()
+            .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err("Weights have to be positive numbers".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Link to a runbook of the hero.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub title: String,
+    pub url: String,
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for Link {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .cloned()
+                .ok_or_else(|| anyhow!("{} attribute is missing in the runbook link", name))
+        };
+        Ok(Link {
+            title: string("title")?,
+            url: string("url")?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Hero {
     pub name: String,
     pub members: Vec<String>,
     pub channel: Option<String>,
+    /// E-mail addresses of the people unacknowledged handovers are escalated to.
+    #[serde(default)]
+    pub owners: Vec<String>,
+    /// Number of business days before a shift its assignees get a reminder.
+    pub reminder_days: Option<u32>,
+    /// Template of the hero's section in the weekly digest, see `slack::digest`.
+    pub digest_template: Option<String>,
+    /// Number of business days a schedule lasts. Without it a schedule lasts until the next one.
+    pub shift_days: Option<u32>,
+    /// Days with fewer assignees are reported as understaffed. Defaults to one.
+    pub min_assignees: Option<u32>,
+    /// Slack workspace the hero's usergroup and channel live in. Without it the default one.
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub platform: Platform,
+    /// Azure AD group replaced with the assignees on Teams. On Teams `channel` is the incoming
+    /// webhook URL of the channel handovers are posted to.
+    pub teams_group_id: Option<String>,
+    #[serde(default)]
+    pub weights: ShiftWeights,
+    /// When the hero was archived. Archived heroes are hidden and not synced, but keep their
+    /// schedules and punch clocks until they are purged.
+    pub archived_at: Option<u64>,
+    pub description: Option<String>,
+    /// Team owning the hero.
+    pub team: Option<String>,
+    /// Labels like "on-call" or "release" to find heroes by, stored in lowercase.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub runbooks: Vec<Link>,
+    /// Who to contact when the assignees cannot be reached, e.g. an e-mail address or a phone
+    /// number.
+    pub escalation_contact: Option<String>,
+}
+
+impl Hero {
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Whether the hero has all of the given tags, ignoring case.
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter()
+            .all(|tag| self.tags.contains(&tag.trim().to_lowercase()))
+    }
+
+    /// Trims the tags, lowercases them and removes duplicates.
+    pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+        let mut tags: Vec<String> = tags
+            .into_iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn validate_settings(&self) -> Result<(), String> {
+        if let Some(reminder_days) = self.reminder_days {
+            if reminder_days > MAX_REMINDER_DAYS {
+                return Err(format!(
+                    "reminder_days has to be at most {}",
+                    MAX_REMINDER_DAYS
+                ));
+            }
+        }
+        if self.shift_days == Some(0) {
+            return Err("shift_days has to be at least 1".to_string());
+        }
+        if self.min_assignees == Some(0) {
+            return Err("min_assignees has to be at least 1".to_string());
+        }
+        if let (Platform::Teams, Some(channel)) = (&self.platform, &self.channel) {
+            if !channel.starts_with("https://") {
+                return Err(
+                    "channel has to be the https URL of an incoming webhook on Teams".to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_metadata(&self) -> Result<(), String> {
+        if let Some(link) = self.runbooks.iter().find(|link| {
+            link.title.trim().is_empty()
+                || !(link.url.starts_with("https://") || link.url.starts_with("http://"))
+        }) {
+            return Err(format!(
+                "Runbook {:?} needs a title and an http or https URL",
+                link.url
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Settings sent to `PUT /hero/{hero}`. Omitted fields keep the value of the stored hero, `null`
+/// removes optional ones.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct HeroUpdate {
+    pub members: Vec<String>,
+    /// Slack channel id, or the incoming webhook URL on Teams. An empty channel removes it.
+    #[serde(default, deserialize_with = "nullable")]
+    pub channel: Option<Option<String>>,
+    pub owners: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub reminder_days: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub digest_template: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub shift_days: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub min_assignees: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub workspace: Option<Option<String>>,
+    pub platform: Option<Platform>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub teams_group_id: Option<Option<String>>,
+    pub weights: Option<ShiftWeights>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub team: Option<Option<String>>,
+    pub tags: Option<Vec<String>>,
+    pub runbooks: Option<Vec<Link>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub escalation_contact: Option<Option<String>>,
+}
+
+/// Tells an omitted field, `None`, from an explicit `null`, `Some(None)`.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl HeroUpdate {
+    /// The hero `name` with the update applied to `previous`, the stored hero if there is one.
+    /// Archiving and restoring have their own endpoints, `archived_at` is always kept.
+    pub fn apply(self, name: &str, previous: Option<Hero>) -> Hero {
+        let previous = previous.unwrap_or_default();
+        Hero {
+            name: name.to_string(),
+            members: self.members,
+            channel: match self.channel {
+                Some(channel) => channel
+                    .map(|channel| channel.trim().to_string())
+                    .filter(|channel| !channel.is_empty()),
+                None => previous.channel,
+            },
+            owners: self.owners.unwrap_or(previous.owners),
+            reminder_days: self.reminder_days.unwrap_or(previous.reminder_days),
+            digest_template: self.digest_template.unwrap_or(previous.digest_template),
+            shift_days: self.shift_days.unwrap_or(previous.shift_days),
+            min_assignees: self.min_assignees.unwrap_or(previous.min_assignees),
+            workspace: self.workspace.unwrap_or(previous.workspace),
+            platform: self.platform.unwrap_or(previous.platform),
+            teams_group_id: self.teams_group_id.unwrap_or(previous.teams_group_id),
+            weights: self.weights.unwrap_or(previous.weights),
+            archived_at: previous.archived_at,
+            description: self.description.unwrap_or(previous.description),
+            team: self.team.unwrap_or(previous.team),
+            tags: Hero::normalize_tags(self.tags.unwrap_or(previous.tags)),
+            runbooks: self.runbooks.unwrap_or(previous.runbooks),
+            escalation_contact: self
+                .escalation_contact
+                .unwrap_or(previous.escalation_contact),
+        }
+    }
 }
 
 impl TryFrom<&HashMap<String, AttributeValue>> for Hero {
@@ -25,10 +322,341 @@ impl TryFrom<&HashMap<String, AttributeValue>> for Hero {
             .get("channel")
             .map(|attr| attr.as_s().unwrap_or(&"".to_string()).to_owned());
 
+        let owners = value
+            .get("owners")
+            .map(|attr| attr.as_ss().unwrap_or(&Vec::new()).to_owned())
+            .unwrap_or_default();
+
+        let days = |name: &str| -> anyhow::Result<Option<u32>> {
+            value
+                .get(name)
+                .map(|attr| -> anyhow::Result<u32> {
+                    let number = attr
+                        .as_n()
+                        .map_err(|_| anyhow!("{} should be a number", name))?;
+                    Ok(u32::from_str(number)?)
+                })
+                .transpose()
+        };
+
+        let reminder_days = days("reminder_days")?;
+
+        let digest_template = value
+            .get("digest_template")
+            .map(|attr| attr.as_s().unwrap_or(&"".to_string()).to_owned());
+
+        let shift_days = days("shift_days")?;
+
+        let min_assignees = days("min_assignees")?;
+
+        let workspace = value
+            .get("workspace")
+            .map(|attr| attr.as_s().unwrap_or(&"".to_string()).to_owned());
+
+        let platform = value
+            .get("platform")
+            .map(|attr| Platform::from_str(attr.as_s().unwrap_or(&"".to_string())))
+            .transpose()?
+            .unwrap_or_default();
+
+        let teams_group_id = value
+            .get("teams_group_id")
+            .map(|attr| attr.as_s().unwrap_or(&"".to_string()).to_owned());
+
+        let weight = |name: &str| -> anyhow::Result<Option<f64>> {
+            value
+                .get(name)
+                .map(|attr| -> anyhow::Result<f64> {
+                    let number = attr
+                        .as_n()
+                        .map_err(|_| anyhow!("{} should be a number", name))?;
+                    Ok(f64::from_str(number)?)
+                })
+                .transpose()
+        };
+        let defaults = ShiftWeights::default();
+        let weights = ShiftWeights {
+            weekday: weight("weekday_weight")?.unwrap_or(defaults.weekday),
+            weekend: weight("weekend_weight")?.unwrap_or(defaults.weekend),
+            holiday: weight("holiday_weight")?.unwrap_or(defaults.holiday),
+            night: weight("night_weight")?,
+            holidays: value
+                .get("holidays")
+                .and_then(|attr| attr.as_ss().ok())
+                .map(|days| {
+                    days.iter()
+                        .map(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d"))
+                        .collect::<Result<Vec<NaiveDate>, _>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        let archived_at = value
+            .get("archived_at")
+            .map(|attr| -> anyhow::Result<u64> {
+                let number = attr
+                    .as_n()
+                    .map_err(|_| anyhow!("archived_at should be a number"))?;
+                Ok(u64::from_str(number)?)
+            })
+            .transpose()?;
+
+        let optional_string = |name: &str| {
+            value
+                .get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .map(|value| value.to_owned())
+        };
+
+        let runbooks = value
+            .get("runbooks")
+            .and_then(|attr| attr.as_l().ok())
+            .map(|links| {
+                links
+                    .iter()
+                    .filter_map(|link| link.as_m().ok())
+                    .map(Link::try_from)
+                    .collect::<anyhow::Result<Vec<Link>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
         Ok(Hero {
             name,
             members,
             channel,
+            owners,
+            reminder_days,
+            digest_template,
+            shift_days,
+            min_assignees,
+            workspace,
+            platform,
+            teams_group_id,
+            weights,
+            archived_at,
+            description: optional_string("description"),
+            team: optional_string("team"),
+            tags: value
+                .get("tags")
+                .and_then(|attr| attr.as_ss().ok())
+                .cloned()
+                .unwrap_or_default(),
+            runbooks,
+            escalation_contact: optional_string("escalation_contact"),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{days_diff, start_of_day};
+
+    fn at(d: u32) -> i64 {
+        start_of_day(NaiveDate::from_ymd_opt(2022, 12, d).unwrap())
+    }
+
+    #[test]
+    fn default_weights_count_business_days() {
+        // Friday, 2022-12-16, until Wednesday, 2022-12-21.
+        assert_eq!(
+            ShiftWeights::default().points(at(16), at(21)),
+            days_diff(at(16), at(21)) as f64
+        );
+    }
+
+    #[test]
+    fn weights_of_weekends_holidays_and_nights() {
+        let weights = ShiftWeights {
+            weekday: 1.0,
+            weekend: 2.0,
+            holiday: 3.0,
+            night: Some(0.5),
+            holidays: vec![NaiveDate::from_ymd_opt(2022, 12, 26).unwrap()],
+        };
+
+        // Friday, 2022-12-23, until Tuesday, 2022-12-27: Friday, the weekend and a holiday.
+        assert_eq!(
+            weights.points(at(23), at(27)),
+            1.0 + 2.0 + 2.0 + 3.0 + 4.0 * 0.5
+        );
+        assert!(ShiftWeights {
+            weekend: -1.0,
+            ..ShiftWeights::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn archived_hero_from_item() {
+        let mut item = HashMap::from([
+            ("name".to_string(), AttributeValue::S("hero".to_string())),
+            (
+                "members".to_string(),
+                AttributeValue::Ss(vec!["a@b.de".to_string()]),
+            ),
+        ]);
+        assert!(!Hero::try_from(&item).unwrap().is_archived());
+
+        item.insert(
+            "archived_at".to_string(),
+            AttributeValue::N("1671404400".to_string()),
+        );
+        let hero = Hero::try_from(&item).unwrap();
+
+        assert!(hero.is_archived());
+        assert_eq!(hero.archived_at, Some(1671404400));
+    }
+
+    #[test]
+    fn metadata_from_item() {
+        let item = HashMap::from([
+            ("name".to_string(), AttributeValue::S("hero".to_string())),
+            (
+                "members".to_string(),
+                AttributeValue::Ss(vec!["a@b.de".to_string()]),
+            ),
+            (
+                "team".to_string(),
+                AttributeValue::S("platform".to_string()),
+            ),
+            (
+                "tags".to_string(),
+                AttributeValue::Ss(vec!["on-call".to_string(), "release".to_string()]),
+            ),
+            (
+                "runbooks".to_string(),
+                AttributeValue::L(vec![AttributeValue::M(HashMap::from([
+                    ("title".to_string(), AttributeValue::S("Outage".to_string())),
+                    (
+                        "url".to_string(),
+                        AttributeValue::S("https://wiki/outage".to_string()),
+                    ),
+                ]))]),
+            ),
+        ]);
+
+        let hero = Hero::try_from(&item).unwrap();
+
+        assert_eq!(hero.team, Some("platform".to_string()));
+        assert_eq!(hero.description, None);
+        assert_eq!(hero.runbooks[0].title, "Outage");
+        assert!(hero.has_tags(&[" On-Call ".to_string()]));
+        assert!(!hero.has_tags(&["on-call".to_string(), "nightly".to_string()]));
+        assert!(hero.validate_metadata().is_ok());
+        assert_eq!(
+            Hero::normalize_tags(vec![
+                "Release ".to_string(),
+                "release".to_string(),
+                " ".to_string()
+            ]),
+            vec!["release"]
+        );
+    }
+
+    #[test]
+    fn invalid_days_from_item() {
+        let item = HashMap::from([
+            ("name".to_string(), AttributeValue::S("hero".to_string())),
+            (
+                "members".to_string(),
+                AttributeValue::Ss(vec!["a@b.de".to_string()]),
+            ),
+            (
+                "shift_days".to_string(),
+                AttributeValue::S("seven".to_string()),
+            ),
+        ]);
+
+        assert!(Hero::try_from(&item).is_err());
+    }
+
+    #[test]
+    fn validate_settings() {
+        let hero = Hero {
+            reminder_days: Some(MAX_REMINDER_DAYS),
+            shift_days: Some(1),
+            min_assignees: Some(1),
+            ..Hero::default()
+        };
+        assert!(hero.validate_settings().is_ok());
+        assert!(Hero {
+            reminder_days: Some(MAX_REMINDER_DAYS + 1),
+            ..hero.clone()
+        }
+        .validate_settings()
+        .is_err());
+        assert!(Hero {
+            shift_days: Some(0),
+            ..hero.clone()
+        }
+        .validate_settings()
+        .is_err());
+        assert!(Hero {
+            min_assignees: Some(0),
+            ..hero.clone()
+        }
+        .validate_settings()
+        .is_err());
+        assert!(Hero {
+            platform: Platform::Teams,
+            channel: Some("http://example.webhook.office.com".to_string()),
+            ..hero
+        }
+        .validate_settings()
+        .is_err());
+    }
+
+    #[test]
+    fn members_only_update_keeps_other_settings() {
+        let previous = Hero {
+            name: "oncall".to_string(),
+            members: vec!["a@b.de".to_string()],
+            channel: Some("https://example.webhook.office.com/x".to_string()),
+            owners: vec!["a@b.de".to_string()],
+            reminder_days: Some(2),
+            platform: Platform::Teams,
+            teams_group_id: Some("group".to_string()),
+            tags: vec!["payments".to_string()],
+            escalation_contact: Some("lead@b.de".to_string()),
+            archived_at: Some(1),
+            ..Default::default()
+        };
+        let update: HeroUpdate =
+            serde_json::from_str(r#"{"members": ["a@b.de", "c@d.de"]}"#).unwrap();
+
+        let hero = update.apply("oncall", Some(previous.clone()));
+
+        assert_eq!(
+            hero,
+            Hero {
+                members: vec!["a@b.de".to_string(), "c@d.de".to_string()],
+                ..previous
+            }
+        );
+    }
+
+    #[test]
+    fn update_removes_settings_sent_as_null() {
+        let previous = Hero {
+            name: "oncall".to_string(),
+            channel: Some("C1".to_string()),
+            reminder_days: Some(2),
+            team: Some("payments".to_string()),
+            ..Default::default()
+        };
+        let update: HeroUpdate = serde_json::from_str(
+            r#"{"members": [], "channel": "", "reminder_days": null, "team": "core"}"#,
+        )
+        .unwrap();
+
+        let hero = update.apply("oncall", Some(previous));
+
+        assert_eq!(hero.channel, None);
+        assert_eq!(hero.reminder_days, None);
+        assert_eq!(hero.team.as_deref(), Some("core"));
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::anyhow;
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Jobs are kept for this long to report their outcome.
+pub const JOB_RETENTION_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "done" => Ok(JobStatus::Done),
+            "failed" => Ok(JobStatus::Failed),
+            _ => Err(anyhow!("unknown job status: {}", s)),
+        }
+    }
+}
+
+/// Recalculation of the punch clocks of all heroes, run by a worker off the job queue.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    /// Number of heroes to recalculate, known once the worker started.
+    pub heroes_total: u64,
+    pub heroes_done: u64,
+    /// Heroes whose recalculation failed, they count as done.
+    pub heroes_failed: Vec<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Job {
+    pub fn new(id: String, now: u64) -> Job {
+        Job {
+            id,
+            status: JobStatus::Queued,
+            heroes_total: 0,
+            heroes_done: 0,
+            heroes_failed: vec![],
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Message on the job queue asking the worker to run a job.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JobRequest {
+    pub job_id: String,
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for Job {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .map(|value| value.to_owned())
+                .ok_or_else(|| anyhow!("{} attribute is missing in the job entry", name))
+        };
+        let number = |name: &str| -> u64 {
+            item.get(name)
+                .and_then(|attr| attr.as_n().ok())
+                .and_then(|value| u64::from_str(value).ok())
+                .unwrap_or_default()
+        };
+
+        Ok(Job {
+            id: string("id")?,
+            status: JobStatus::from_str(&string("status")?)?,
+            heroes_total: number("heroes_total"),
+            heroes_done: number("heroes_done"),
+            heroes_failed: item
+                .get("heroes_failed")
+                .and_then(|attr| attr.as_ss().ok())
+                .cloned()
+                .unwrap_or_default(),
+            created_at: number("created_at"),
+            updated_at: number("updated_at"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn job_from_item_without_failed_heroes() {
+        let item = HashMap::from([
+            ("id".to_string(), AttributeValue::S("42".to_string())),
+            (
+                "status".to_string(),
+                AttributeValue::S("running".to_string()),
+            ),
+            (
+                "heroes_total".to_string(),
+                AttributeValue::N("3".to_string()),
+            ),
+            (
+                "heroes_done".to_string(),
+                AttributeValue::N("1".to_string()),
+            ),
+            (
+                "created_at".to_string(),
+                AttributeValue::N("1671404400".to_string()),
+            ),
+        ]);
+
+        let job = Job::try_from(&item).unwrap();
+
+        assert_eq!(job.status, JobStatus::Running);
+        assert_eq!(job.heroes_total, 3);
+        assert_eq!(job.heroes_done, 1);
+        assert!(job.heroes_failed.is_empty());
+        assert_eq!(job.updated_at, 0);
+    }
+}
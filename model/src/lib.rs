@@ -1,5 +1,14 @@
+pub mod compensation;
+pub mod coverage;
 pub mod hero;
+pub mod job;
 pub mod punch_clock;
+pub mod rotation;
 pub mod schedule;
+pub mod slack_user;
+pub mod stats;
+pub mod sync;
 pub mod time;
 pub mod user;
+pub mod webhook;
+pub mod workload;
@@ -1,15 +1,20 @@
+use crate::hero::{Hero, ShiftWeights};
 use crate::schedule::Schedule;
 use crate::time::days_diff;
+use anyhow::anyhow;
 use aws_sdk_dynamodb::types::AttributeValue;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PunchClock {
     pub hero: String,
     pub member: String,
     pub days: u64,
+    /// Days weighted by the hero's `ShiftWeights`.
+    #[serde(default)]
+    pub points: f64,
     pub first_punch: i64,
     pub last_punch: i64,
 }
@@ -28,6 +33,11 @@ impl PunchClock {
             days: u64::from_str(item["days"].as_n().unwrap_or(&"0".to_string()))
                 .expect("Days was not a number")
                 .to_owned(),
+            points: item
+                .get("points")
+                .and_then(|points| points.as_n().ok())
+                .map(|points| f64::from_str(points).expect("Points was not a number"))
+                .unwrap_or_default(),
             first_punch: i64::from_str(item["first_punch"].as_n().unwrap_or(&"0".to_string()))
                 .expect("First punch was not a number")
                 .to_owned(),
@@ -38,42 +48,367 @@ impl PunchClock {
     }
 }
 
-pub fn recalculate_punch_time(hero: String, schedules: Vec<Schedule>) -> Vec<PunchClock> {
-    let mut punch_cards: HashMap<String, PunchClock> = HashMap::new();
-
-    if !schedules.is_empty() {
-        let mut previous: Schedule = schedules.first().unwrap().clone();
-
-        schedules.into_iter().skip(1).for_each(|schedule| {
-            let days = days_diff(previous.shift_start_time, schedule.shift_start_time) as u64;
-            if days > 0 {
-                previous.assignees.clone().into_iter().for_each(|assignee| {
-                    match punch_cards.get(assignee.as_str()) {
-                        None => {
-                            let punch_card = PunchClock {
-                                hero: hero.clone(),
-                                member: assignee.clone(),
-                                days,
-                                first_punch: previous.shift_start_time,
-                                last_punch: previous.shift_start_time,
-                            };
-                            punch_cards.insert(assignee.clone(), punch_card);
-                        }
-                        Some(punched) => {
-                            let old = punched.clone();
-                            let punch_card = PunchClock {
-                                days: days + old.days,
-                                last_punch: previous.shift_start_time,
-                                ..old
-                            };
-                            punch_cards.insert(assignee.clone(), punch_card);
-                        }
-                    }
-                });
-            }
-            previous = schedule.clone();
-        });
+/// One shift served by a member, the punch clock totals are derived from these entries.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LedgerEntry {
+    pub hero: String,
+    pub member: String,
+    pub shift_start: i64,
+    /// Start of the following schedule, which ended this shift, or the time of the last update
+    /// while the shift is still open.
+    pub shift_end: i64,
+    pub business_days: u64,
+    #[serde(default)]
+    pub points: f64,
+}
+
+impl LedgerEntry {
+    pub fn new(
+        hero: &str,
+        member: &str,
+        shift_start: i64,
+        shift_end: i64,
+        weights: &ShiftWeights,
+    ) -> LedgerEntry {
+        LedgerEntry {
+            hero: hero.to_owned(),
+            member: member.to_owned(),
+            shift_start,
+            shift_end,
+            business_days: days_diff(shift_start, shift_end).max(0) as u64,
+            points: weights.points(shift_start, shift_end),
+        }
+    }
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for LedgerEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .map(|value| value.to_owned())
+                .ok_or_else(|| anyhow!("{} attribute is missing in the ledger entry", name))
+        };
+        let number = |name: &str| -> anyhow::Result<i64> {
+            item.get(name)
+                .and_then(|attr| attr.as_n().ok())
+                .ok_or_else(|| anyhow!("{} attribute is missing in the ledger entry", name))
+                .and_then(|value| Ok(i64::from_str(value)?))
+        };
+
+        Ok(LedgerEntry {
+            hero: string("hero")?,
+            member: string("member")?,
+            shift_start: number("shift_start")?,
+            shift_end: number("shift_end")?,
+            business_days: number("business_days")? as u64,
+            points: item
+                .get("points")
+                .and_then(|attr| attr.as_n().ok())
+                .map(|value| f64::from_str(value))
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Ledger entries of the shifts of the given schedules up to `now`, the schedules have to be
+/// sorted. A shift lasts until the next schedule starts, the shift of the last schedule which
+/// started is open and credited up to `now`. Recording the entries of the last two schedules
+/// every day hence adds up to the same ledger as the entries of all schedules, as long as every
+/// schedule is the last one during at least one of the recordings. A shift shorter than a day or a
+/// missed night leaves the shift before credited only up to the previous recording, until the
+/// punch clock is recalculated.
+pub fn ledger_entries(hero: &Hero, schedules: &[Schedule], now: i64) -> Vec<LedgerEntry> {
+    let started: Vec<&Schedule> = schedules
+        .iter()
+        .filter(|schedule| schedule.shift_start_time <= now)
+        .collect();
+
+    started
+        .iter()
+        .enumerate()
+        .flat_map(|(index, schedule)| {
+            let shift_end = started
+                .get(index + 1)
+                .map(|next| next.shift_start_time)
+                .unwrap_or(now);
+            schedule.assignees.iter().map(move |assignee| {
+                LedgerEntry::new(
+                    &hero.name,
+                    assignee,
+                    schedule.shift_start_time,
+                    shift_end,
+                    &hero.weights,
+                )
+            })
+        })
+        .filter(|entry| entry.business_days > 0 || entry.points > 0.0)
+        .collect()
+}
+
+/// Records entries in the ledger, replacing the entry of the same shift and member like the
+/// ledger table does.
+pub fn record(ledger: &mut Vec<LedgerEntry>, entries: Vec<LedgerEntry>) {
+    for entry in entries {
+        match ledger.iter_mut().find(|recorded| {
+            recorded.hero == entry.hero
+                && recorded.member == entry.member
+                && recorded.shift_start == entry.shift_start
+        }) {
+            Some(recorded) => *recorded = entry,
+            None => ledger.push(entry),
+        }
+    }
+}
+
+/// Sums up the ledger entries per member.
+pub fn totals(entries: &[LedgerEntry]) -> Vec<PunchClock> {
+    let mut punch_cards: BTreeMap<(&str, &str), PunchClock> = BTreeMap::new();
+
+    for entry in entries {
+        punch_cards
+            .entry((entry.hero.as_str(), entry.member.as_str()))
+            .and_modify(|punch_card| {
+                punch_card.days += entry.business_days;
+                punch_card.points += entry.points;
+                punch_card.first_punch = punch_card.first_punch.min(entry.shift_start);
+                punch_card.last_punch = punch_card.last_punch.max(entry.shift_start);
+            })
+            .or_insert_with(|| PunchClock {
+                hero: entry.hero.clone(),
+                member: entry.member.clone(),
+                days: entry.business_days,
+                points: entry.points,
+                first_punch: entry.shift_start,
+                last_punch: entry.shift_start,
+            });
+    }
+
+    punch_cards.into_values().collect()
+}
+
+pub fn recalculate_punch_time(hero: &Hero, schedules: Vec<Schedule>, now: i64) -> Vec<PunchClock> {
+    totals(&ledger_entries(hero, &schedules, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn schedule(shift_start_time: i64, assignees: &[&str]) -> Schedule {
+        Schedule {
+            hero: "oncall".to_string(),
+            shift_start_time,
+            assignees: assignees.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn hero(weights: ShiftWeights) -> Hero {
+        Hero {
+            name: "oncall".to_string(),
+            weights,
+            ..Default::default()
+        }
     }
 
-    punch_cards.values().cloned().collect()
+    fn entry(member: &str, shift_start: i64, shift_end: i64) -> LedgerEntry {
+        LedgerEntry::new(
+            "oncall",
+            member,
+            shift_start,
+            shift_end,
+            &ShiftWeights::default(),
+        )
+    }
+
+    #[test]
+    fn ledger_credits_open_shift_up_to_now() {
+        // Monday, Monday an hour later, Wednesday and Friday of the same week, and Thursday of
+        // the week after.
+        let schedules = vec![
+            schedule(1671404400, &["a"]),
+            schedule(1671408000, &["b"]),
+            schedule(1671577200, &["a", "c"]),
+            schedule(1671750000, &["b"]),
+            schedule(1672268400, &["a"]),
+        ];
+
+        // Tuesday of the week after.
+        let entries = ledger_entries(&hero(ShiftWeights::default()), &schedules, 1672095600);
+
+        assert_eq!(
+            entries,
+            vec![
+                entry("b", 1671408000, 1671577200),
+                entry("a", 1671577200, 1671750000),
+                entry("c", 1671577200, 1671750000),
+                entry("b", 1671750000, 1672095600),
+            ]
+        );
+        assert_eq!(entries[0].business_days, 2);
+        assert_eq!(entries[3].business_days, 2);
+    }
+
+    #[test]
+    fn record_replaces_entry_of_open_shift() {
+        let mut ledger = vec![entry("a", 1671404400, 1671577200)];
+
+        record(
+            &mut ledger,
+            vec![
+                entry("a", 1671404400, 1671750000),
+                entry("b", 1671750000, 1672095600),
+            ],
+        );
+
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger[0].business_days, 4);
+    }
+
+    #[test]
+    fn totals_sum_up_entries_per_member() {
+        let entries = vec![
+            entry("a", 1671404400, 1671577200),
+            entry("b", 1671577200, 1671663600),
+            entry("a", 1671663600, 1671750000),
+        ];
+
+        let totals = totals(&entries);
+
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].member, "a");
+        assert_eq!(totals[0].days, 3);
+        assert_eq!(totals[0].first_punch, 1671404400);
+        assert_eq!(totals[0].last_punch, 1671663600);
+        assert_eq!(totals[1].days, 1);
+    }
+
+    #[test]
+    fn ledger_credits_weighted_weekend_shift() {
+        // Friday evening until Monday.
+        let schedules = vec![schedule(1671814800, &["a"]), schedule(1672009200, &["b"])];
+        let weights = ShiftWeights {
+            weekend: 1.5,
+            ..ShiftWeights::default()
+        };
+
+        let totals = recalculate_punch_time(&hero(weights), schedules, 1672009200);
+
+        assert_eq!(totals[0].days, 1);
+        assert_eq!(totals[0].points, 4.0);
+    }
+
+    const MEMBERS: [&str; 3] = ["a", "b", "c"];
+
+    /// Sorted schedules starting on Monday, 2022-12-19, and the sorted times at which the nightly
+    /// update runs, independent of the shifts. The last of these times is now.
+    fn schedules_and_runs() -> impl Strategy<Value = (Vec<Schedule>, Vec<i64>)> {
+        (
+            prop::collection::vec(
+                (
+                    60 * 60..10 * 24 * 60 * 60i64,
+                    prop::sample::subsequence(MEMBERS.to_vec(), 0..=MEMBERS.len()),
+                ),
+                1..20,
+            ),
+            prop::collection::vec(0.0..1.0f64, 1..40),
+        )
+            .prop_map(|(shifts, runs)| {
+                let first = 1671404400;
+                let mut start = first;
+                let mut schedules = vec![];
+                for (length, assignees) in shifts {
+                    schedules.push(schedule(start, &assignees));
+                    start += length;
+                }
+                let mut runs: Vec<i64> = runs
+                    .into_iter()
+                    .map(|run| first + ((start - first) as f64 * run) as i64)
+                    .collect();
+                runs.sort();
+                (schedules, runs)
+            })
+    }
+
+    /// Whether every schedule after the first which started by now was the last one during a run.
+    fn every_shift_has_a_run(schedules: &[Schedule], runs: &[i64]) -> bool {
+        let now = *runs.last().unwrap();
+        schedules
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, schedule)| schedule.shift_start_time <= now)
+            .all(|(index, schedule)| {
+                let end = schedules
+                    .get(index + 1)
+                    .map(|next| next.shift_start_time)
+                    .unwrap_or(i64::MAX);
+                runs.iter()
+                    .any(|run| schedule.shift_start_time <= *run && *run < end)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn incremental_updates_equal_recalculation((schedules, runs) in schedules_and_runs()) {
+            let hero = hero(ShiftWeights {
+                weekend: 1.5,
+                night: Some(0.25),
+                ..ShiftWeights::default()
+            });
+            let now = *runs.last().unwrap();
+            let mut ledger = vec![];
+            for &run in runs.iter() {
+                let started: Vec<Schedule> = schedules
+                    .iter()
+                    .filter(|schedule| schedule.shift_start_time <= run)
+                    .cloned()
+                    .collect();
+                let last_two = &started[started.len().saturating_sub(2)..];
+                record(&mut ledger, ledger_entries(&hero, last_two, run));
+            }
+
+            let incremental = totals(&ledger);
+            let recalculated = recalculate_punch_time(&hero, schedules.clone(), now);
+            if every_shift_has_a_run(&schedules, &runs) {
+                prop_assert_eq!(incremental, recalculated);
+            } else {
+                // Shifts without a run are not closed, the ledger falls short until recalculated.
+                for punch_clock in incremental.iter() {
+                    let full = recalculated
+                        .iter()
+                        .find(|full| full.member == punch_clock.member)
+                        .unwrap();
+                    prop_assert!(punch_clock.days <= full.days);
+                    prop_assert!(punch_clock.points <= full.points + 1e-9);
+                }
+            }
+        }
+
+        #[test]
+        fn single_assignees_add_up_to_all_days((schedules, runs) in schedules_and_runs()) {
+            let now = *runs.last().unwrap();
+            let schedules: Vec<Schedule> = schedules
+                .into_iter()
+                .map(|schedule| Schedule {
+                    assignees: vec![MEMBERS[schedule.shift_start_time as usize % 3].to_string()],
+                    ..schedule
+                })
+                .collect();
+            let first = schedules[0].shift_start_time;
+
+            let punch_clocks =
+                recalculate_punch_time(&hero(ShiftWeights::default()), schedules, now);
+            let days: u64 = punch_clocks.iter().map(|punch_clock| punch_clock.days).sum();
+            let points: f64 = punch_clocks.iter().map(|punch_clock| punch_clock.points).sum();
+
+            prop_assert_eq!(days, days_diff(first, now) as u64);
+            prop_assert_eq!(points, days as f64);
+        }
+    }
 }
@@ -0,0 +1,91 @@
+use crate::schedule::Schedule;
+use crate::time::{day_of, days_diff, start_of_day};
+use chrono::NaiveDate;
+
+/// Finds the schedule in effect on the given day, that is the latest schedule starting on or
+/// before it. With `shift_days` a schedule only lasts that many business days, otherwise until
+/// the next schedule starts. Expects the schedules to be sorted by `shift_start_time`.
+pub fn schedule_on(
+    schedules: &[Schedule],
+    day: NaiveDate,
+    shift_days: Option<u32>,
+) -> Option<&Schedule> {
+    schedules
+        .iter()
+        .take_while(|schedule| day_of(schedule.shift_start_time) <= day)
+        .last()
+        .filter(|schedule| match shift_days {
+            Some(shift_days) => {
+                days_diff(schedule.shift_start_time, start_of_day(day)) < shift_days as i32
+            }
+            None => true,
+        })
+}
+
+/// Resolves the assignees for each of the given days. Days nobody is assigned to have no
+/// assignees.
+pub fn assignees_per_day(
+    schedules: &[Schedule],
+    days: &[NaiveDate],
+    shift_days: Option<u32>,
+) -> Vec<(NaiveDate, Vec<String>)> {
+    days.iter()
+        .map(|day| {
+            let assignees = schedule_on(schedules, *day, shift_days)
+                .map(|schedule| schedule.assignees.clone())
+                .unwrap_or_default();
+            (*day, assignees)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rotation::assignees_per_day;
+    use crate::schedule::Schedule;
+    use crate::time::start_of_day;
+    use chrono::NaiveDate;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 12, d).unwrap()
+    }
+
+    fn schedule(d: u32, assignees: Vec<&str>) -> Schedule {
+        Schedule {
+            hero: "hero".to_string(),
+            shift_start_time: start_of_day(day(d)),
+            assignees: assignees.into_iter().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gaps_and_handovers() {
+        let schedules = vec![schedule(20, vec!["a@b.de"]), schedule(22, vec!["c@d.de"])];
+        let days: Vec<NaiveDate> = (19..=23).map(day).collect();
+        assert_eq!(
+            assignees_per_day(&schedules, &days, None),
+            vec![
+                (day(19), vec![]),
+                (day(20), vec!["a@b.de".to_string()]),
+                (day(21), vec!["a@b.de".to_string()]),
+                (day(22), vec!["c@d.de".to_string()]),
+                (day(23), vec!["c@d.de".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn limited_shift_days() {
+        let schedules = vec![schedule(19, vec!["a@b.de"])];
+        let days: Vec<NaiveDate> = (19..=21).map(day).collect();
+        assert_eq!(
+            assignees_per_day(&schedules, &days, Some(2)),
+            vec![
+                (day(19), vec!["a@b.de".to_string()]),
+                (day(20), vec!["a@b.de".to_string()]),
+                (day(21), vec![]),
+            ]
+        );
+    }
+}
@@ -1,17 +1,26 @@
 use chrono::prelude::*;
 
+use crate::time::days_diff;
 use aws_sdk_dynamodb::types::AttributeValue;
+use chrono_tz::Europe::Berlin;
 use serde::ser::{SerializeStruct, Serializer};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Schedule {
     pub hero: String,
     pub shift_start_time: i64,
     pub assignees: Vec<String>,
     pub repeat_every_days: Option<i32>,
+    /// Assignees who acknowledged the handover.
+    pub acknowledged_by: Vec<String>,
+    /// Assignees who asked for a replacement.
+    pub declined_by: Vec<String>,
+    pub escalated_at: Option<i64>,
+    /// Assignees who were reminded of the upcoming shift.
+    pub reminded: Vec<String>,
 }
 
 impl Schedule {
@@ -36,7 +45,89 @@ impl Schedule {
                 i32::from_str(days.as_n().expect("repeat_every_days should be a number"))
                     .expect("repeat_every_days should be a number")
             }),
+            acknowledged_by: string_set(item, "acknowledged_by"),
+            declined_by: string_set(item, "declined_by"),
+            escalated_at: item.get("escalated_at").map(|escalated_at| {
+                i64::from_str(
+                    escalated_at
+                        .as_n()
+                        .expect("escalated_at should be a number"),
+                )
+                .expect("escalated_at should be a number")
+            }),
+            reminded: string_set(item, "reminded"),
+        }
+    }
+
+    /// Whether every assignee acknowledged the handover.
+    pub fn is_acknowledged(&self) -> bool {
+        self.assignees
+            .iter()
+            .all(|assignee| self.acknowledged_by.contains(assignee))
+    }
+
+    /// Whether the shift starts within `reminder_days` business days, but not today.
+    pub fn is_reminder_due(&self, reminder_days: u32, now: i64) -> bool {
+        let days_until_shift = days_diff(now, self.shift_start_time);
+        days_until_shift >= 1 && days_until_shift <= reminder_days as i32
+    }
+
+    /// Assignees who still have to be reminded, given the shift starts within `reminder_days`
+    /// business days.
+    pub fn pending_reminders(&self, reminder_days: u32, now: i64) -> Vec<String> {
+        if !self.is_reminder_due(reminder_days, now) {
+            return Vec::new();
         }
+        self.assignees
+            .iter()
+            .filter(|assignee| !self.reminded.contains(assignee))
+            .cloned()
+            .collect()
+    }
+
+    /// A handover is escalated once, when it is still not acknowledged a business day after the
+    /// shift started or as soon as an assignee asked for a replacement.
+    pub fn needs_escalation(&self, now: i64) -> bool {
+        self.escalated_at.is_none()
+            && (!self.declined_by.is_empty()
+                || (!self.is_acknowledged() && days_diff(self.shift_start_time, now) >= 1))
+    }
+
+    /// Copy of the schedule for another hero, moved by `offset_days` days at the same time of day
+    /// in Berlin. The handover starts over, acknowledgements and reminders are not copied.
+    pub fn copy_for(&self, hero: &str, offset_days: i64) -> Schedule {
+        let start =
+            NaiveDateTime::from_timestamp_opt(self.shift_start_time, 0).expect("Invalid timestamp");
+        let local =
+            Berlin.from_utc_datetime(&start).naive_local() + chrono::Duration::days(offset_days);
+        // A time skipped when the clocks go forward is moved by the skipped hour.
+        let shift_start_time = Berlin
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| {
+                Berlin
+                    .from_local_datetime(&(local + chrono::Duration::hours(1)))
+                    .earliest()
+            })
+            .expect("Invalid local time")
+            .timestamp();
+        Schedule {
+            hero: hero.to_owned(),
+            shift_start_time,
+            assignees: self.assignees.clone(),
+            repeat_every_days: self.repeat_every_days,
+            acknowledged_by: vec![],
+            declined_by: vec![],
+            escalated_at: None,
+            reminded: vec![],
+        }
+    }
+}
+
+fn string_set(item: &HashMap<String, AttributeValue>, name: &str) -> Vec<String> {
+    match item.get(name) {
+        Some(value) => value.as_ss().unwrap_or(&Vec::new()).to_owned(),
+        None => Vec::new(),
     }
 }
 
@@ -49,10 +140,119 @@ impl Serialize for Schedule {
             NaiveDateTime::from_timestamp_opt(self.shift_start_time, 0).expect("Invalid timestamp");
         let datetime: DateTime<Utc> = DateTime::from_naive_utc_and_offset(naive, Utc);
 
-        let mut s = serializer.serialize_struct("Schedule", 2)?;
+        let escalated_at = self.escalated_at.map(|escalated_at| {
+            let naive =
+                NaiveDateTime::from_timestamp_opt(escalated_at, 0).expect("Invalid timestamp");
+            DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc).to_rfc3339()
+        });
+
+        let mut s = serializer.serialize_struct("Schedule", 6)?;
         s.serialize_field("hero", &self.hero)?;
         s.serialize_field("shift_start_time", &datetime.to_rfc3339())?;
         s.serialize_field("assignees", &self.assignees)?;
+        s.serialize_field("acknowledged_by", &self.acknowledged_by)?;
+        s.serialize_field("declined_by", &self.declined_by)?;
+        s.serialize_field("escalated_at", &escalated_at)?;
         s.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::schedule::Schedule;
+
+    // Monday, 19 December 2022 00:00 in Berlin
+    const MONDAY: i64 = 1671404400;
+    const DAY: i64 = 60 * 60 * 24;
+
+    fn schedule(acknowledged_by: Vec<&str>, declined_by: Vec<&str>) -> Schedule {
+        Schedule {
+            hero: "hero".to_string(),
+            shift_start_time: MONDAY,
+            assignees: vec!["a@b.de".to_string(), "c@d.de".to_string()],
+            acknowledged_by: acknowledged_by.into_iter().map(String::from).collect(),
+            declined_by: declined_by.into_iter().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unacknowledged_on_shift_day() {
+        assert!(!schedule(vec!["a@b.de"], vec![]).needs_escalation(MONDAY + 60));
+    }
+
+    #[test]
+    fn unacknowledged_next_business_day() {
+        assert!(schedule(vec!["a@b.de"], vec![]).needs_escalation(MONDAY + DAY));
+        assert!(!schedule(vec!["a@b.de", "c@d.de"], vec![]).needs_escalation(MONDAY + DAY));
+    }
+
+    #[test]
+    fn declined() {
+        assert!(schedule(vec![], vec!["a@b.de"]).needs_escalation(MONDAY));
+    }
+
+    #[test]
+    fn pending_reminders() {
+        let reminded = Schedule {
+            reminded: vec!["a@b.de".to_string()],
+            ..schedule(vec![], vec![])
+        };
+        // Friday before the shift is one business day ahead.
+        assert_eq!(
+            reminded.pending_reminders(2, MONDAY - 3 * DAY),
+            vec!["c@d.de"]
+        );
+        assert!(reminded.pending_reminders(2, MONDAY - 7 * DAY).is_empty());
+        assert!(reminded.pending_reminders(2, MONDAY).is_empty());
+    }
+
+    #[test]
+    fn copy_keeps_time_of_day_across_daylight_saving() {
+        let copy = Schedule {
+            acknowledged_by: vec!["a@b.de".to_string()],
+            repeat_every_days: Some(7),
+            ..schedule(vec![], vec![])
+        }
+        .copy_for("clone", 98);
+
+        // Monday, 27 March 2023 00:00 in Berlin, after the switch to summer time.
+        assert_eq!(copy.shift_start_time, 1679868000);
+        assert_eq!(copy.hero, "clone");
+        assert_eq!(copy.repeat_every_days, Some(7));
+        assert!(copy.acknowledged_by.is_empty());
+    }
+
+    #[test]
+    fn copy_moves_time_skipped_by_daylight_saving() {
+        // Sunday, 19 March 2023 02:30 in Berlin.
+        let copy = Schedule {
+            shift_start_time: 1679189400,
+            ..schedule(vec![], vec![])
+        }
+        .copy_for("clone", 7);
+
+        // 02:30 does not exist on 26 March 2023, the copy starts at 03:30 summer time instead.
+        assert_eq!(copy.shift_start_time, 1679794200);
+    }
+
+    #[test]
+    fn escalated_once() {
+        let escalated = Schedule {
+            escalated_at: Some(MONDAY + DAY),
+            ..schedule(vec![], vec!["a@b.de"])
+        };
+        assert!(!escalated.needs_escalation(MONDAY + 2 * DAY));
+    }
+
+    #[test]
+    fn serializes_escalation() {
+        let escalated = Schedule {
+            escalated_at: Some(MONDAY + DAY),
+            ..schedule(vec![], vec!["a@b.de"])
+        };
+        let json = serde_json::to_value(escalated).unwrap();
+        assert_eq!(json["escalated_at"], "2022-12-19T23:00:00+00:00");
+        assert!(serde_json::to_value(schedule(vec![], vec![])).unwrap()["escalated_at"].is_null());
+    }
+}
@@ -0,0 +1,44 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Cached mapping from an e-mail address to the Slack user id in one workspace.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SlackUser {
+    pub email: String,
+    pub workspace: String,
+    pub slack_user_id: String,
+    pub updated_at: u64,
+    /// Used as the DynamoDB TTL attribute, entries are ignored after it passed.
+    pub expires_at: u64,
+}
+
+impl SlackUser {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+impl From<&HashMap<String, AttributeValue>> for SlackUser {
+    fn from(item: &HashMap<String, AttributeValue>) -> Self {
+        SlackUser {
+            email: item["email"]
+                .as_s()
+                .expect("email attribute is missing in the slack user entry")
+                .to_owned(),
+            workspace: item["workspace"]
+                .as_s()
+                .expect("workspace attribute is missing in the slack user entry")
+                .to_owned(),
+            slack_user_id: item["slack_user_id"]
+                .as_s()
+                .expect("slack_user_id attribute is missing in the slack user entry")
+                .to_owned(),
+            updated_at: u64::from_str(item["updated_at"].as_n().unwrap_or(&"0".to_string()))
+                .expect("updated_at was not a number"),
+            expires_at: u64::from_str(item["expires_at"].as_n().unwrap_or(&"0".to_string()))
+                .expect("expires_at was not a number"),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::schedule::Schedule;
+use crate::time::{days_diff, start_of_day};
+use anyhow::anyhow;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Length of the periods the days served are summed up by.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "month" => Ok(GroupBy::Month),
+            "quarter" => Ok(GroupBy::Quarter),
+            "year" => Ok(GroupBy::Year),
+            _ => Err(anyhow!("unknown grouping: {}", s)),
+        }
+    }
+}
+
+impl GroupBy {
+    fn months(&self) -> u32 {
+        match self {
+            GroupBy::Month => 1,
+            GroupBy::Quarter => 3,
+            GroupBy::Year => 12,
+        }
+    }
+
+    /// First day of the period the given day falls into.
+    fn period_of(&self, day: NaiveDate) -> NaiveDate {
+        let month = (day.month0() / self.months()) * self.months() + 1;
+        NaiveDate::from_ymd_opt(day.year(), month, 1).expect("Invalid date")
+    }
+
+    fn next_period(&self, period: NaiveDate) -> NaiveDate {
+        period
+            .checked_add_months(chrono::Months::new(self.months()))
+            .expect("Invalid date")
+    }
+
+    fn label(&self, period: NaiveDate) -> String {
+        match self {
+            GroupBy::Month => period.format("%Y-%m").to_string(),
+            GroupBy::Quarter => format!("{}-Q{}", period.year(), period.month0() / 3 + 1),
+            GroupBy::Year => period.year().to_string(),
+        }
+    }
+}
+
+/// Business days a member served in one period.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Point {
+    pub period: String,
+    pub days: u64,
+}
+
+/// Days served per period by one member, with a point for every period of the range.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    pub member: String,
+    pub days: u64,
+    pub points: Vec<Point>,
+}
+
+/// Sums up the business days each assignee served between `from` and `to` per period. The
+/// schedules have to be sorted and start with the one in effect at `from`. Each shift lasts until
+/// the next schedule starts, the last one until `now`.
+pub fn series(
+    schedules: &[Schedule],
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: GroupBy,
+    now: i64,
+) -> Vec<Series> {
+    let mut periods = vec![];
+    let mut period = group_by.period_of(from);
+    while period <= to {
+        let next = group_by.next_period(period);
+        let start = start_of_day(period.max(from));
+        let end = start_of_day(next.min(to.succ_opt().expect("Invalid date")));
+        periods.push((group_by.label(period), start, end));
+        period = next;
+    }
+
+    let mut days: BTreeMap<&str, Vec<u64>> = BTreeMap::new();
+    for (index, schedule) in schedules.iter().enumerate() {
+        let shift_end = schedules
+            .get(index + 1)
+            .map(|next| next.shift_start_time)
+            .unwrap_or(now)
+            .min(now);
+        for (position, (_, start, end)) in periods.iter().enumerate() {
+            let served_from = schedule.shift_start_time.max(*start);
+            let served_to = shift_end.min(*end);
+            if served_from >= served_to {
+                continue;
+            }
+            let served = days_diff(served_from, served_to).max(0) as u64;
+            for assignee in schedule.assignees.iter() {
+                days.entry(assignee.as_str())
+                    .or_insert_with(|| vec![0; periods.len()])[position] += served;
+            }
+        }
+    }
+
+    days.into_iter()
+        .map(|(member, days)| Series {
+            member: member.to_owned(),
+            days: days.iter().sum(),
+            points: periods
+                .iter()
+                .zip(days)
+                .map(|((period, _, _), days)| Point {
+                    period: period.clone(),
+                    days,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn schedule(day: NaiveDate, assignees: Vec<&str>) -> Schedule {
+        Schedule {
+            hero: "hero".to_string(),
+            shift_start_time: start_of_day(day),
+            assignees: assignees.into_iter().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_shifts_at_period_boundaries() {
+        // Thursday, 2022-09-29 until Tuesday, 2022-10-04.
+        let schedules = vec![
+            schedule(day(2022, 9, 29), vec!["a"]),
+            schedule(day(2022, 10, 4), vec!["b"]),
+        ];
+
+        let series = series(
+            &schedules,
+            day(2022, 7, 1),
+            day(2022, 12, 31),
+            GroupBy::Quarter,
+            start_of_day(day(2022, 10, 6)),
+        );
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].member, "a");
+        assert_eq!(
+            series[0].points,
+            vec![
+                Point {
+                    period: "2022-Q3".to_string(),
+                    days: 2
+                },
+                Point {
+                    period: "2022-Q4".to_string(),
+                    days: 1
+                },
+            ]
+        );
+        assert_eq!(series[1].days, 2);
+    }
+
+    #[test]
+    fn clips_shifts_to_range() {
+        let schedules = vec![schedule(day(2022, 11, 28), vec!["a"])];
+
+        let series = series(
+            &schedules,
+            day(2022, 12, 1),
+            day(2022, 12, 9),
+            GroupBy::Month,
+            start_of_day(day(2023, 1, 1)),
+        );
+
+        assert_eq!(series[0].points.len(), 1);
+        assert_eq!(series[0].points[0].period, "2022-12");
+        assert_eq!(series[0].days, 7);
+    }
+}
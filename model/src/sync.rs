@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// How a sync treats the current members of the usergroup of a hero.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    /// Replaces the members without looking at them, the historic behavior.
+    #[default]
+    Replace,
+    /// Compares the members with the assignees and only updates usergroups that drifted.
+    SkipUnchanged,
+    /// Compares the members with the assignees without updating anything.
+    DryRun,
+}
+
+impl SyncMode {
+    pub fn compares(&self) -> bool {
+        *self != SyncMode::Replace
+    }
+}
+
+/// Difference between the user ids in a usergroup and the assignees of the hero.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Drift {
+    /// Assignees that are not in the usergroup.
+    pub missing: Vec<String>,
+    /// Members of the usergroup that are not assigned, e.g. added manually in Slack.
+    pub unexpected: Vec<String>,
+}
+
+impl Drift {
+    pub fn between(expected: &[String], actual: &[String]) -> Drift {
+        let expected: BTreeSet<&String> = expected.iter().collect();
+        let actual: BTreeSet<&String> = actual.iter().collect();
+        Drift {
+            missing: expected
+                .difference(&actual)
+                .map(|id| id.to_string())
+                .collect(),
+            unexpected: actual
+                .difference(&expected)
+                .map(|id| id.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Outcome of updating the usergroup of one hero.
+#[derive(Serialize, Debug)]
+pub struct UsergroupUpdate {
+    pub hero: String,
+    pub usergroup_id: Option<String>,
+    pub user_ids: Vec<String>,
+    /// Only compared if the sync mode asks for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drift: Option<Drift>,
+    pub outcome: UsergroupUpdateOutcome,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum UsergroupUpdateOutcome {
+    Updated,
+    /// The members already matched the assignees, see `SyncMode::SkipUnchanged`.
+    Unchanged,
+    /// Nothing was updated, see `SyncMode::DryRun`.
+    DryRun,
+    MissingUsergroup,
+    Failed {
+        error: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn no_drift_regardless_of_order() {
+        let drift = Drift::between(&ids(&["U1", "U2"]), &ids(&["U2", "U1"]));
+        assert!(drift.is_empty());
+    }
+
+    #[test]
+    fn reports_missing_and_unexpected_members() {
+        let drift = Drift::between(&ids(&["U1", "U2"]), &ids(&["U2", "U3"]));
+        assert_eq!(drift.missing, ids(&["U1"]));
+        assert_eq!(drift.unexpected, ids(&["U3"]));
+        assert!(!drift.is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use bdays::HolidayCalendar;
+use chrono::NaiveDate;
 use chrono::NaiveDateTime;
 use chrono::TimeZone;
 use chrono_tz::Europe::Berlin;
@@ -12,6 +13,35 @@ pub fn days_diff(secs0: i64, secs1: i64) -> i32 {
     cal.bdays(Berlin.from_utc_datetime(&d0), Berlin.from_utc_datetime(&d1))
 }
 
+// Returns the start of the given day in Berlin as seconds since the epoch
+pub fn start_of_day(date: NaiveDate) -> i64 {
+    Berlin
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).expect("Invalid time"))
+        .earliest()
+        .expect("Invalid local time")
+        .timestamp()
+}
+
+// Returns the last second of the given day in Berlin as seconds since the epoch
+pub fn end_of_day(date: NaiveDate) -> i64 {
+    start_of_day(date.succ_opt().expect("Invalid date")) - 1
+}
+
+// Returns the day in Berlin the given seconds since the epoch fall on
+pub fn day_of(secs: i64) -> NaiveDate {
+    let d = NaiveDateTime::from_timestamp_opt(secs, 0).expect("Invalid timestamp");
+    Berlin.from_utc_datetime(&d).date_naive()
+}
+
+// Returns the next `count` business days starting with the given day
+pub fn business_days(from: NaiveDate, count: usize) -> Vec<NaiveDate> {
+    let cal = bdays::calendars::WeekendsOnly;
+    from.iter_days()
+        .filter(|day| cal.is_bday(*day))
+        .take(count)
+        .collect()
+}
+
 pub fn secs_now() -> u64 {
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -21,7 +51,8 @@ pub fn secs_now() -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use crate::time::days_diff;
+    use crate::time::{business_days, day_of, days_diff, start_of_day};
+    use chrono::NaiveDate;
 
     #[test]
     fn days_diff_test() {
@@ -33,4 +64,21 @@ mod tests {
         assert_eq!(days_diff(1671404100, 1671404400), 0);
         assert_eq!(days_diff(1671405000, 1671404400), 0);
     }
+
+    #[test]
+    fn start_of_day_round_trip() {
+        let date = NaiveDate::from_ymd_opt(2022, 12, 19).unwrap();
+        assert_eq!(start_of_day(date), 1671404400);
+        assert_eq!(day_of(start_of_day(date)), date);
+        assert_eq!(day_of(1671490799), date);
+    }
+
+    #[test]
+    fn business_days_skip_weekends() {
+        let friday = NaiveDate::from_ymd_opt(2022, 12, 23).unwrap();
+        assert_eq!(
+            business_days(friday, 2),
+            vec![friday, NaiveDate::from_ymd_opt(2022, 12, 26).unwrap()]
+        );
+    }
 }
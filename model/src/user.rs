@@ -1,14 +1,172 @@
 use std::collections::HashMap;
 
 use aws_sdk_dynamodb::types::AttributeValue;
+use chrono::{TimeZone, Timelike, Utc};
+use chrono_tz::Europe::Berlin;
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
+/// Longest reminder lead time in business days, reminders look up to 60 calendar days ahead.
+pub const MAX_REMINDER_DAYS: u32 = 40;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct User {
     pub email: String,
     pub last_login: Option<u64>,
     pub last_seen_release_notes: Option<String>,
+    #[serde(default)]
+    pub preferences: Preferences,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    SlackDm,
+    Email,
+}
+
+impl NotificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::SlackDm => "slack_dm",
+            NotificationChannel::Email => "email",
+        }
+    }
+}
+
+impl FromStr for NotificationChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slack_dm" => Ok(NotificationChannel::SlackDm),
+            "email" => Ok(NotificationChannel::Email),
+            _ => Err(format!("unknown notification channel: {}", s)),
+        }
+    }
+}
+
+/// Hours of the day, in the timezone of the user, without personal notifications. Wraps around
+/// midnight if `end` is before `start`, e.g. 22 to 7.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl QuietHours {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start <= self.end {
+            self.start <= hour && hour < self.end
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+/// How a user wants to be notified personally, i.e. reminders, escalations and assignment mails.
+/// Posts to the channel of a hero are not affected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Preferences {
+    /// No channel at all turns personal notifications off.
+    pub channels: Vec<NotificationChannel>,
+    /// Business days before a shift the user is reminded, overrides the lead time of the hero.
+    pub reminder_days: Option<u32>,
+    /// Hold back Slack messages, mails are sent anyway as they do not interrupt.
+    pub quiet_hours: Option<QuietHours>,
+    /// IANA name like `Europe/Berlin`, which is used if unset.
+    pub timezone: Option<String>,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            channels: vec![NotificationChannel::SlackDm],
+            reminder_days: None,
+            quiet_hours: None,
+            timezone: None,
+        }
+    }
+}
+
+impl Preferences {
+    pub fn allows(&self, channel: NotificationChannel) -> bool {
+        self.channels.contains(&channel)
+    }
+
+    /// Whether the given seconds since the epoch fall into the quiet hours of the user.
+    pub fn is_quiet(&self, secs: i64) -> bool {
+        let Some(quiet_hours) = self.quiet_hours else {
+            return false;
+        };
+        let tz = self
+            .timezone
+            .as_deref()
+            .and_then(|timezone| Tz::from_str(timezone).ok())
+            .unwrap_or(Berlin);
+        match Utc.timestamp_opt(secs, 0).single() {
+            Some(time) => quiet_hours.contains(time.with_timezone(&tz).hour()),
+            None => false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(reminder_days) = self.reminder_days {
+            if !(1..=MAX_REMINDER_DAYS).contains(&reminder_days) {
+                return Err(format!(
+                    "reminder_days has to be between 1 and {}",
+                    MAX_REMINDER_DAYS
+                ));
+            }
+        }
+        if let Some(ref timezone) = self.timezone {
+            Tz::from_str(timezone).map_err(|_| format!("unknown timezone: {}", timezone))?;
+        }
+        if let Some(quiet_hours) = self.quiet_hours {
+            if quiet_hours.start > 23 || quiet_hours.end > 23 {
+                return Err("quiet hours have to be between 0 and 23".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn from_item(item: &HashMap<String, AttributeValue>) -> Preferences {
+        let number = |name: &str| -> Option<u32> {
+            item.get(name)
+                .and_then(|value| value.as_n().ok())
+                .and_then(|value| u32::from_str(value).ok())
+        };
+        // Users who only opted into e-mail before preferences existed keep getting it.
+        let channels = match item
+            .get("notification_channels")
+            .and_then(|value| value.as_l().ok())
+        {
+            Some(channels) => channels
+                .iter()
+                .filter_map(|channel| channel.as_s().ok())
+                .filter_map(|channel| NotificationChannel::from_str(channel).ok())
+                .collect(),
+            None => match item.get("email_notifications").map(|value| value.as_bool()) {
+                Some(Ok(true)) => vec![NotificationChannel::SlackDm, NotificationChannel::Email],
+                _ => Preferences::default().channels,
+            },
+        };
+        let quiet_hours = match (number("quiet_hours_start"), number("quiet_hours_end")) {
+            (Some(start), Some(end)) => Some(QuietHours { start, end }),
+            _ => None,
+        };
+
+        Preferences {
+            channels,
+            reminder_days: number("reminder_days"),
+            quiet_hours,
+            timezone: item
+                .get("timezone")
+                .and_then(|value| value.as_s().ok())
+                .map(|value| value.to_owned()),
+        }
+    }
 }
 
 impl From<&HashMap<String, AttributeValue>> for User {
@@ -18,21 +176,85 @@ impl From<&HashMap<String, AttributeValue>> for User {
                 .as_s()
                 .expect("email attribute is missing in the user entry")
                 .to_owned(),
-            last_login: item["last_login"]
-                .as_n()
+            // Missing for users whose preferences were set before they logged in.
+            last_login: item
+                .get("last_login")
+                .and_then(|value| value.as_n().ok())
                 .map(|timestamp| {
-                    u64::from_str(timestamp)
-                        .expect("last_login attribute was not an N field")
-                        .to_owned()
-                })
-                .ok()
-                .to_owned(),
+                    u64::from_str(timestamp).expect("last_login attribute was not an N field")
+                }),
             last_seen_release_notes: item.get("last_seen_release_notes").map(|value| {
                 value
                     .as_s()
                     .expect("last_seen_release_notes should be a string")
                     .to_owned()
             }),
+            preferences: Preferences::from_item(item),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_hours_wrap_around_midnight() {
+        let night = QuietHours { start: 22, end: 7 };
+        assert!(night.contains(23));
+        assert!(night.contains(0));
+        assert!(!night.contains(7));
+        assert!(!night.contains(12));
+
+        let lunch = QuietHours { start: 12, end: 13 };
+        assert!(lunch.contains(12));
+        assert!(!lunch.contains(13));
+    }
+
+    #[test]
+    fn quiet_in_timezone_of_user() {
+        let preferences = Preferences {
+            quiet_hours: Some(QuietHours { start: 22, end: 7 }),
+            timezone: Some("America/New_York".to_string()),
+            ..Default::default()
+        };
+
+        // 2022-12-19 05:00 UTC is midnight in New York, but 06:00 in Berlin.
+        assert!(preferences.is_quiet(1671426000));
+        // 2022-12-19 15:00 UTC
+        assert!(!preferences.is_quiet(1671462000));
+    }
+
+    #[test]
+    fn legacy_email_opt_in_keeps_email() {
+        let item = HashMap::from([
+            ("email".to_string(), AttributeValue::S("a@b.de".to_string())),
+            ("last_login".to_string(), AttributeValue::N("0".to_string())),
+            (
+                "email_notifications".to_string(),
+                AttributeValue::Bool(true),
+            ),
+        ]);
+
+        let user = User::from(&item);
+
+        assert!(user.preferences.allows(NotificationChannel::Email));
+        assert!(user.preferences.allows(NotificationChannel::SlackDm));
+    }
+
+    #[test]
+    fn user_without_login() {
+        let item = HashMap::from([
+            ("email".to_string(), AttributeValue::S("a@b.de".to_string())),
+            (
+                "notification_channels".to_string(),
+                AttributeValue::L(vec![AttributeValue::S("email".to_string())]),
+            ),
+        ]);
+
+        let user = User::from(&item);
+
+        assert_eq!(user.last_login, None);
+        assert!(user.preferences.allows(NotificationChannel::Email));
+    }
+}
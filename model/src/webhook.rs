@@ -0,0 +1,326 @@
+use anyhow::anyhow;
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Deliveries are given up after this many failed attempts.
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+/// Wait before the first retry, doubled with every further attempt.
+const RETRY_BASE_SECS: u64 = 5 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    #[serde(rename = "shift.started")]
+    ShiftStarted,
+    #[serde(rename = "schedule.changed")]
+    ScheduleChanged,
+    #[serde(rename = "member.added")]
+    MemberAdded,
+    #[serde(rename = "member.removed")]
+    MemberRemoved,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::ShiftStarted => "shift.started",
+            EventType::ScheduleChanged => "schedule.changed",
+            EventType::MemberAdded => "member.added",
+            EventType::MemberRemoved => "member.removed",
+        }
+    }
+}
+
+impl FromStr for EventType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shift.started" => Ok(EventType::ShiftStarted),
+            "schedule.changed" => Ok(EventType::ScheduleChanged),
+            "member.added" => Ok(EventType::MemberAdded),
+            "member.removed" => Ok(EventType::MemberRemoved),
+            _ => Err(anyhow!("unknown event type: {}", s)),
+        }
+    }
+}
+
+/// Webhook of a hero receiving the events it subscribed to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Subscription {
+    pub hero: String,
+    pub id: String,
+    pub url: String,
+    /// Key of the HMAC signature, only ever returned when the subscription is created.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<EventType>,
+    pub created_at: u64,
+}
+
+impl Subscription {
+    pub fn is_subscribed(&self, event_type: EventType) -> bool {
+        self.events.contains(&event_type)
+    }
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for Subscription {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .map(|value| value.to_owned())
+                .ok_or_else(|| anyhow!("{} attribute is missing in the subscription entry", name))
+        };
+
+        let events = item
+            .get("events")
+            .and_then(|attr| attr.as_ss().ok())
+            .map(|events| {
+                events
+                    .iter()
+                    .map(|event| EventType::from_str(event))
+                    .collect::<anyhow::Result<Vec<EventType>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Subscription {
+            hero: string("hero")?,
+            id: string("id")?,
+            url: string("url")?,
+            secret: string("secret")?,
+            events,
+            created_at: u64::from_str(&string_n(item, "created_at").unwrap_or_default())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Payload sent to the subscriptions of a hero.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Event {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub hero: String,
+    pub occurred_at: u64,
+    pub data: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(anyhow!("unknown delivery status: {}", s)),
+        }
+    }
+}
+
+/// Delivery of one event to one subscription, kept as the delivery log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Delivery {
+    pub subscription_id: String,
+    /// Sorts the deliveries of a subscription by creation, `<created_at>#<event id>`.
+    pub delivery_id: String,
+    pub hero: String,
+    pub event_type: EventType,
+    /// The signed JSON body, sent again as is on retries.
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub next_attempt_at: Option<u64>,
+}
+
+impl Delivery {
+    pub fn new(subscription: &Subscription, event: &Event, payload: String) -> Delivery {
+        Delivery {
+            subscription_id: subscription.id.clone(),
+            delivery_id: format!("{:020}#{}", event.occurred_at, event.id),
+            hero: event.hero.clone(),
+            event_type: event.event_type,
+            payload,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            last_status_code: None,
+            last_error: None,
+            created_at: event.occurred_at,
+            next_attempt_at: Some(event.occurred_at),
+        }
+    }
+
+    /// Records an attempt answered with `status_code`, or failed with `error` before getting an
+    /// answer, and schedules the next one with exponential backoff.
+    pub fn record_attempt(&mut self, status_code: Option<u16>, error: Option<String>, now: u64) {
+        self.attempts += 1;
+        self.last_status_code = status_code;
+        self.last_error = error;
+
+        if status_code.is_some_and(|code| (200..300).contains(&code)) {
+            self.status = DeliveryStatus::Delivered;
+            self.next_attempt_at = None;
+        } else if self.attempts >= MAX_DELIVERY_ATTEMPTS {
+            self.status = DeliveryStatus::Failed;
+            self.next_attempt_at = None;
+        } else {
+            self.status = DeliveryStatus::Pending;
+            self.next_attempt_at = Some(now + RETRY_BASE_SECS * 2u64.pow(self.attempts - 1));
+        }
+    }
+
+    /// Gives up on the delivery without another attempt, e.g. once its subscription is deleted.
+    pub fn abandon(&mut self, error: String) {
+        self.status = DeliveryStatus::Failed;
+        self.last_error = Some(error);
+        self.next_attempt_at = None;
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        self.status == DeliveryStatus::Pending
+            && self.next_attempt_at.is_some_and(|next| next <= now)
+    }
+}
+
+impl TryFrom<&HashMap<String, AttributeValue>> for Delivery {
+    type Error = anyhow::Error;
+
+    fn try_from(item: &HashMap<String, AttributeValue>) -> Result<Self, Self::Error> {
+        let string = |name: &str| -> anyhow::Result<String> {
+            item.get(name)
+                .and_then(|attr| attr.as_s().ok())
+                .map(|value| value.to_owned())
+                .ok_or_else(|| anyhow!("{} attribute is missing in the delivery entry", name))
+        };
+        let number = |name: &str| -> Option<u64> {
+            string_n(item, name).and_then(|value| u64::from_str(&value).ok())
+        };
+
+        Ok(Delivery {
+            subscription_id: string("subscription_id")?,
+            delivery_id: string("delivery_id")?,
+            hero: string("hero")?,
+            event_type: EventType::from_str(&string("event_type")?)?,
+            payload: string("payload")?,
+            status: DeliveryStatus::from_str(&string("status")?)?,
+            attempts: number("attempts").unwrap_or_default() as u32,
+            last_status_code: number("last_status_code").map(|code| code as u16),
+            last_error: item
+                .get("last_error")
+                .and_then(|attr| attr.as_s().ok())
+                .map(|value| value.to_owned()),
+            created_at: number("created_at").unwrap_or_default(),
+            next_attempt_at: number("next_attempt_at"),
+        })
+    }
+}
+
+fn string_n(item: &HashMap<String, AttributeValue>, name: &str) -> Option<String> {
+    item.get(name)
+        .and_then(|attr| attr.as_n().ok())
+        .map(|value| value.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn delivery() -> Delivery {
+        let subscription = Subscription {
+            hero: "oncall".to_string(),
+            id: "W1".to_string(),
+            url: "https://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![EventType::ShiftStarted],
+            created_at: 0,
+        };
+        let event = Event {
+            id: "E1".to_string(),
+            event_type: EventType::ShiftStarted,
+            hero: "oncall".to_string(),
+            occurred_at: 1000,
+            data: json!({}),
+        };
+        Delivery::new(&subscription, &event, "{}".to_string())
+    }
+
+    #[test]
+    fn event_type_serializes_dotted() {
+        let event_type = serde_json::to_string(&EventType::MemberRemoved).unwrap();
+        assert_eq!(event_type, "\"member.removed\"");
+        assert_eq!(
+            EventType::from_str("member.removed").unwrap(),
+            EventType::MemberRemoved
+        );
+    }
+
+    #[test]
+    fn retries_with_exponential_backoff() {
+        let mut delivery = delivery();
+        assert!(delivery.is_due(1000));
+
+        delivery.record_attempt(Some(503), None, 1000);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.next_attempt_at, Some(1000 + 300));
+
+        delivery.record_attempt(None, Some("timed out".to_string()), 2000);
+        assert_eq!(delivery.next_attempt_at, Some(2000 + 600));
+        assert!(!delivery.is_due(2599));
+        assert!(delivery.is_due(2600));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut delivery = delivery();
+        for _ in 0..MAX_DELIVERY_ATTEMPTS {
+            delivery.record_attempt(Some(500), None, 1000);
+        }
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert!(!delivery.is_due(u64::MAX));
+    }
+
+    #[test]
+    fn delivered_on_success() {
+        let mut delivery = delivery();
+        delivery.record_attempt(Some(204), None, 1000);
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.next_attempt_at, None);
+    }
+
+    #[test]
+    fn abandoned_is_not_due() {
+        let mut delivery = delivery();
+        delivery.abandon("The webhook was deleted".to_string());
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert!(!delivery.is_due(u64::MAX));
+    }
+}
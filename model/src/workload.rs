@@ -0,0 +1,205 @@
+use crate::punch_clock::PunchClock;
+use crate::schedule::Schedule;
+use crate::time::days_diff;
+use serde::Serialize;
+
+/// Upcoming shift of a member, from its start, or now if it already started, until the next
+/// schedule of the hero starts or the end of the report.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Shift {
+    pub hero: String,
+    pub start: i64,
+    pub end: i64,
+    pub days: u64,
+}
+
+/// Time a member holds two heroes at once.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Overlap {
+    pub heroes: (String, String),
+    pub start: i64,
+    pub end: i64,
+    pub days: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HeroWorkload {
+    pub hero: String,
+    pub days_served: u64,
+    pub upcoming_days: u64,
+}
+
+/// Load of one member across all heroes.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Workload {
+    pub member: String,
+    pub days_served: u64,
+    pub upcoming_days: u64,
+    pub heroes: Vec<HeroWorkload>,
+    pub shifts: Vec<Shift>,
+    pub overlaps: Vec<Overlap>,
+}
+
+/// Upcoming shifts of `member` between `now` and `until`. The schedules of a hero have to be
+/// sorted and start with the one in effect at `now`.
+pub fn upcoming_shifts(member: &str, schedules: &[Schedule], now: i64, until: i64) -> Vec<Shift> {
+    schedules
+        .iter()
+        .enumerate()
+        .filter(|(_, schedule)| schedule.assignees.iter().any(|a| a == member))
+        .filter_map(|(index, schedule)| {
+            let start = schedule.shift_start_time.max(now);
+            let end = schedules
+                .get(index + 1)
+                .map(|next| next.shift_start_time)
+                .unwrap_or(until)
+                .min(until);
+            (start < end).then(|| Shift {
+                hero: schedule.hero.clone(),
+                start,
+                end,
+                days: days_diff(start, end).max(0) as u64,
+            })
+        })
+        .collect()
+}
+
+/// Sums up the days `member` served according to the punch clocks and the upcoming days of its
+/// shifts, and finds the shifts of different heroes which overlap.
+pub fn workload(member: &str, punch_clocks: &[PunchClock], shifts: Vec<Shift>) -> Workload {
+    let mut heroes: Vec<HeroWorkload> = punch_clocks
+        .iter()
+        .filter(|punch_clock| punch_clock.member == member)
+        .map(|punch_clock| HeroWorkload {
+            hero: punch_clock.hero.clone(),
+            days_served: punch_clock.days,
+            upcoming_days: 0,
+        })
+        .collect();
+    for shift in shifts.iter() {
+        match heroes.iter_mut().find(|hero| hero.hero == shift.hero) {
+            Some(hero) => hero.upcoming_days += shift.days,
+            None => heroes.push(HeroWorkload {
+                hero: shift.hero.clone(),
+                days_served: 0,
+                upcoming_days: shift.days,
+            }),
+        }
+    }
+    heroes.sort_by(|a, b| a.hero.cmp(&b.hero));
+
+    let mut overlaps = vec![];
+    for (index, shift) in shifts.iter().enumerate() {
+        for other in shifts.iter().skip(index + 1) {
+            let start = shift.start.max(other.start);
+            let end = shift.end.min(other.end);
+            if shift.hero != other.hero && start < end {
+                overlaps.push(Overlap {
+                    heroes: (shift.hero.clone(), other.hero.clone()),
+                    start,
+                    end,
+                    days: days_diff(start, end).max(0) as u64,
+                });
+            }
+        }
+    }
+    overlaps.sort_by_key(|overlap| overlap.start);
+
+    let mut shifts = shifts;
+    shifts.sort_by_key(|shift| shift.start);
+
+    Workload {
+        member: member.to_owned(),
+        days_served: heroes.iter().map(|hero| hero.days_served).sum(),
+        upcoming_days: heroes.iter().map(|hero| hero.upcoming_days).sum(),
+        heroes,
+        shifts,
+        overlaps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::start_of_day;
+    use chrono::NaiveDate;
+
+    fn at(d: u32) -> i64 {
+        start_of_day(NaiveDate::from_ymd_opt(2022, 12, d).unwrap())
+    }
+
+    fn schedule(hero: &str, d: u32, assignees: Vec<&str>) -> Schedule {
+        Schedule {
+            hero: hero.to_string(),
+            shift_start_time: at(d),
+            assignees: assignees.into_iter().map(String::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn upcoming_shifts_start_now_and_end_with_report() {
+        let schedules = vec![
+            schedule("oncall", 16, vec!["a"]),
+            schedule("oncall", 21, vec!["b"]),
+            schedule("oncall", 26, vec!["a"]),
+        ];
+
+        let shifts = upcoming_shifts("a", &schedules, at(19), at(28));
+
+        assert_eq!(
+            shifts,
+            vec![
+                Shift {
+                    hero: "oncall".to_string(),
+                    start: at(19),
+                    end: at(21),
+                    days: 2
+                },
+                Shift {
+                    hero: "oncall".to_string(),
+                    start: at(26),
+                    end: at(28),
+                    days: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_overlaps_across_heroes() {
+        let punch_clocks = vec![PunchClock {
+            hero: "oncall".to_string(),
+            member: "a".to_string(),
+            days: 10,
+            points: 10.0,
+            first_punch: 0,
+            last_punch: 0,
+        }];
+        let mut shifts = upcoming_shifts("a", &[schedule("oncall", 19, vec!["a"])], at(19), at(24));
+        shifts.extend(upcoming_shifts(
+            "a",
+            &[
+                schedule("release", 19, vec!["b"]),
+                schedule("release", 21, vec!["a"]),
+            ],
+            at(19),
+            at(24),
+        ));
+
+        let workload = workload("a", &punch_clocks, shifts);
+
+        assert_eq!(workload.days_served, 10);
+        assert_eq!(workload.upcoming_days, 8);
+        assert_eq!(workload.heroes.len(), 2);
+        assert_eq!(
+            workload.overlaps,
+            vec![Overlap {
+                heroes: ("oncall".to_string(), "release".to_string()),
+                start: at(21),
+                end: at(24),
+                days: 3
+            }]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! E-mail backend. Assignees who opted in get a mail with a calendar invite for their shift when
+//! they are assigned, when the assignment is cancelled and before the shift starts.
+
+use crate::Error;
+use aws_sdk_ssm::Client as SsmClient;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use model::hero::Hero;
+use model::time::{business_days, day_of};
+use std::env;
+use std::str::FromStr;
+
+/// How the connection to the SMTP server is secured, set by `SMTP_TLS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tls {
+    /// Plain text, only meant for a local SMTP sink.
+    None,
+    #[default]
+    StartTls,
+    /// TLS from the start, usually on port 465.
+    Wrapper,
+}
+
+impl FromStr for Tls {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Tls::None),
+            "starttls" => Ok(Tls::StartTls),
+            "tls" => Ok(Tls::Wrapper),
+            _ => Err(format!("unknown SMTP_TLS mode: {}", s).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    pub tls: Tls,
+    pub credentials: Option<(String, String)>,
+    pub from: String,
+}
+
+impl SmtpConfig {
+    /// Reads `SMTP_HOST`, `SMTP_PORT`, `SMTP_TLS`, `SMTP_FROM` and `SMTP_USERNAME` with the
+    /// password stored in SSM at `SMTP_PASSWORD_PARAMETER`. Without `SMTP_HOST` e-mail is off.
+    pub async fn from_env() -> Result<Option<SmtpConfig>, Error> {
+        let host = match env::var("SMTP_HOST") {
+            Ok(host) if !host.is_empty() => host,
+            _ => return Ok(None),
+        };
+        let port = match env::var("SMTP_PORT") {
+            Ok(port) if !port.is_empty() => Some(u16::from_str(&port)?),
+            _ => None,
+        };
+        let tls = match env::var("SMTP_TLS") {
+            Ok(tls) if !tls.is_empty() => Tls::from_str(&tls)?,
+            _ => Tls::default(),
+        };
+        let credentials = match env::var("SMTP_USERNAME") {
+            Ok(username) if !username.is_empty() => Some((username, get_smtp_password().await?)),
+            _ => None,
+        };
+
+        Ok(Some(SmtpConfig {
+            host,
+            port,
+            tls,
+            credentials,
+            from: env::var("SMTP_FROM")?,
+        }))
+    }
+}
+
+/// Kind of mail sent to an assignee, each comes with a matching calendar invite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notice {
+    Assignment,
+    Cancellation,
+    Reminder,
+}
+
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(config: SmtpConfig) -> Result<Mailer, Error> {
+        let mut builder = match config.tls {
+            Tls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            Tls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+            Tls::Wrapper => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let Some((username, password)) = config.credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Mailer {
+            transport: builder.build(),
+            from: config.from.parse()?,
+        })
+    }
+
+    /// Creates a mailer as configured by the environment, see `SmtpConfig::from_env`.
+    pub async fn from_env() -> Result<Option<Mailer>, Error> {
+        SmtpConfig::from_env().await?.map(Mailer::new).transpose()
+    }
+
+    /// Mails the notice about the shift of `hero` starting at `shift_start_time` to `to`.
+    pub async fn send(
+        &self,
+        notice: Notice,
+        to: &str,
+        hero: &Hero,
+        shift_start_time: i64,
+    ) -> Result<(), Error> {
+        let (first_day, _) = shift_days(hero, shift_start_time);
+        let (subject, text) = match notice {
+            Notice::Assignment => (
+                format!("You are the hero of {} on {}", hero.name, first_day),
+                format!(
+                    "You have been assigned as {} for the shift starting {}.\n\nThe attached invite adds it to your calendar.",
+                    hero.name, first_day
+                ),
+            ),
+            Notice::Cancellation => (
+                format!("Cancelled: {} on {}", hero.name, first_day),
+                format!(
+                    "You are no longer assigned as {} for the shift starting {}.",
+                    hero.name, first_day
+                ),
+            ),
+            Notice::Reminder => (
+                format!("Reminder: you are the hero of {} on {}", hero.name, first_day),
+                format!(
+                    "Your shift as {} starts {}.",
+                    hero.name, first_day
+                ),
+            ),
+        };
+        let invite = invite(
+            notice,
+            to,
+            self.from.email.as_ref(),
+            hero,
+            shift_start_time,
+            Utc::now(),
+        );
+        let method = match notice {
+            Notice::Cancellation => "CANCEL",
+            _ => "REQUEST",
+        };
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to.parse()?)
+            .subject(subject)
+            .multipart(
+                MultiPart::mixed()
+                    .singlepart(SinglePart::plain(text))
+                    .singlepart(Attachment::new("shift.ics".to_string()).body(
+                        invite,
+                        ContentType::parse(&format!(
+                            "text/calendar; charset=utf-8; method={}",
+                            method
+                        ))?,
+                    )),
+            )?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// First day of the shift and the day after its last one. Shifts without `shift_days` last until
+/// the next schedule, which is unknown here, so their invite covers the first day.
+fn shift_days(hero: &Hero, shift_start_time: i64) -> (NaiveDate, NaiveDate) {
+    let days = business_days(
+        day_of(shift_start_time),
+        hero.shift_days.unwrap_or(1).max(1) as usize,
+    );
+    let first_day = days[0];
+    let end_day = days[days.len() - 1] + Days::new(1);
+    (first_day, end_day)
+}
+
+/// Builds the iCalendar invite of a shift. Its UID only depends on hero, shift and attendee, so
+/// a cancellation or a repeated assignment updates the event already in the calendar. Calendars
+/// ignore updates that do not raise the SEQUENCE, so it is the second the invite was stamped.
+pub fn invite(
+    notice: Notice,
+    attendee: &str,
+    organizer: &str,
+    hero: &Hero,
+    shift_start_time: i64,
+    stamped_at: DateTime<Utc>,
+) -> String {
+    let (first_day, end_day) = shift_days(hero, shift_start_time);
+    let (method, status) = match notice {
+        Notice::Cancellation => ("CANCEL", "CANCELLED"),
+        _ => ("REQUEST", "CONFIRMED"),
+    };
+    [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//hero-of-the-day//EN".to_string(),
+        format!("METHOD:{}", method),
+        "BEGIN:VEVENT".to_string(),
+        format!(
+            "UID:{}-{}-{}@hero-of-the-day",
+            hero.name, shift_start_time, attendee
+        ),
+        format!("DTSTAMP:{}", stamped_at.format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART;VALUE=DATE:{}", first_day.format("%Y%m%d")),
+        format!("DTEND;VALUE=DATE:{}", end_day.format("%Y%m%d")),
+        format!("SUMMARY:{}", hero.name),
+        format!("ORGANIZER:mailto:{}", organizer),
+        format!("ATTENDEE;ROLE=REQ-PARTICIPANT:mailto:{}", attendee),
+        format!("STATUS:{}", status),
+        format!("SEQUENCE:{}", stamped_at.timestamp()),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ]
+    .join("\r\n")
+        + "\r\n"
+}
+
+async fn get_smtp_password() -> Result<String, Error> {
+    let shared_config = aws_config::load_from_env().await;
+    let response = SsmClient::new(&shared_config)
+        .get_parameter()
+        .name(env::var("SMTP_PASSWORD_PARAMETER")?)
+        .with_decryption(true)
+        .send()
+        .await?;
+    response
+        .parameter
+        .and_then(|parameter| parameter.value)
+        .ok_or_else(|| "SMTP password not found as an SSM parameter.".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invite_covers_business_days_of_shift() {
+        let hero = Hero {
+            name: "oncall".to_string(),
+            shift_days: Some(2),
+            ..Default::default()
+        };
+
+        // Friday, 2022-12-23.
+        let invite = invite(
+            Notice::Assignment,
+            "a@b.de",
+            "hero@b.de",
+            &hero,
+            1671793200,
+            Utc::now(),
+        );
+
+        assert!(invite.contains("METHOD:REQUEST\r\n"));
+        assert!(invite.contains("DTSTART;VALUE=DATE:20221223\r\n"));
+        assert!(invite.contains("DTEND;VALUE=DATE:20221227\r\n"));
+        assert!(invite.contains("UID:oncall-1671793200-a@b.de@hero-of-the-day\r\n"));
+    }
+
+    #[test]
+    fn later_invites_raise_the_sequence() {
+        let hero = Hero {
+            name: "oncall".to_string(),
+            ..Default::default()
+        };
+        let assigned_at = DateTime::from_timestamp(1671700000, 0).unwrap();
+        let cancelled_at = DateTime::from_timestamp(1671710000, 0).unwrap();
+        let reassigned_at = DateTime::from_timestamp(1671720000, 0).unwrap();
+
+        let assignment = invite(
+            Notice::Assignment,
+            "a@b.de",
+            "hero@b.de",
+            &hero,
+            1671793200,
+            assigned_at,
+        );
+        let cancellation = invite(
+            Notice::Cancellation,
+            "a@b.de",
+            "hero@b.de",
+            &hero,
+            1671793200,
+            cancelled_at,
+        );
+        let reassignment = invite(
+            Notice::Assignment,
+            "a@b.de",
+            "hero@b.de",
+            &hero,
+            1671793200,
+            reassigned_at,
+        );
+
+        assert!(assignment.contains("SEQUENCE:1671700000\r\n"));
+        assert!(cancellation.contains("SEQUENCE:1671710000\r\n"));
+        assert!(reassignment.contains("SEQUENCE:1671720000\r\n"));
+    }
+}
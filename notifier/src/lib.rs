@@ -0,0 +1,108 @@
+use futures::future::BoxFuture;
+use model::hero::{Hero, Platform};
+use model::schedule::Schedule;
+use model::sync::{SyncMode, UsergroupUpdate};
+use slack::workspace::{workspace_name, Workspaces};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+pub mod email;
+pub mod teams;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// What the nightly job and `schedule-update` need from the chat platform of a hero.
+pub trait Notifier: Send + Sync {
+    /// Brings the group of every hero in line with the assignees of its schedule and reports the
+    /// outcome per group.
+    fn sync_members<'a>(
+        &'a self,
+        schedules: &'a [(Hero, Schedule)],
+        mode: SyncMode,
+    ) -> BoxFuture<'a, Result<Vec<UsergroupUpdate>, Error>>;
+
+    /// Announces the assignees of a schedule in the channel of its hero, if it has one.
+    fn post_handover<'a>(
+        &'a self,
+        hero: &'a Hero,
+        schedule: &'a Schedule,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+impl Notifier for slack::Client {
+    fn sync_members<'a>(
+        &'a self,
+        schedules: &'a [(Hero, Schedule)],
+        mode: SyncMode,
+    ) -> BoxFuture<'a, Result<Vec<UsergroupUpdate>, Error>> {
+        Box::pin(async move {
+            let schedules = schedules
+                .iter()
+                .map(|(_, schedule)| schedule.clone())
+                .collect();
+            Ok(self.sync_usergroups(schedules, mode).await?)
+        })
+    }
+
+    fn post_handover<'a>(
+        &'a self,
+        hero: &'a Hero,
+        schedule: &'a Schedule,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if let Some(ref channel) = hero.channel {
+                slack::Client::post_handover(self, channel, schedule).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Heroes with the same key are served by the same notifier.
+pub fn notifier_key(hero: &Hero) -> String {
+    match hero.platform {
+        Platform::Slack => format!(
+            "{}/{}",
+            hero.platform,
+            workspace_name(hero.workspace.as_deref())
+        ),
+        Platform::Teams => hero.platform.to_string(),
+    }
+}
+
+/// Resolves the notifier of a hero, creating clients on first use.
+pub struct Notifiers {
+    slack: Workspaces,
+    teams: OnceCell<Arc<teams::Client>>,
+}
+
+impl Notifiers {
+    pub fn new(slack: Workspaces) -> Notifiers {
+        Notifiers {
+            slack,
+            teams: OnceCell::new(),
+        }
+    }
+
+    /// The Slack clients, for what only Slack supports, e.g. direct messages.
+    pub fn slack(&self) -> &Workspaces {
+        &self.slack
+    }
+
+    pub async fn for_hero(&self, hero: &Hero) -> Result<Arc<dyn Notifier>, Error> {
+        match hero.platform {
+            Platform::Slack => {
+                let client: Arc<dyn Notifier> =
+                    self.slack.client(hero.workspace.as_deref()).await?;
+                Ok(client)
+            }
+            Platform::Teams => {
+                let client = self
+                    .teams
+                    .get_or_try_init(|| async { teams::Client::connect().await.map(Arc::new) })
+                    .await?;
+                Ok(client.clone())
+            }
+        }
+    }
+}
@@ -0,0 +1,345 @@
+//! Microsoft Teams backend. Group membership is managed through the Graph API, handovers are
+//! posted to the incoming webhook of a channel.
+
+use crate::{Error, Notifier};
+use aws_sdk_ssm::Client as SsmClient;
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use model::hero::Hero;
+use model::schedule::Schedule;
+use model::sync::{Drift, SyncMode, UsergroupUpdate, UsergroupUpdateOutcome};
+use model::time::day_of;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::env;
+
+/// Used unless `TEAMS_GRAPH_BASE_URL` is set.
+pub const DEFAULT_GRAPH_BASE_URL: &str = "https://graph.microsoft.com/v1.0/";
+const LOGIN_BASE_URL: &str = "https://login.microsoftonline.com/";
+const GRAPH_SCOPE: &str = "https://graph.microsoft.com/.default";
+/// Upper bound of heroes synced at the same time.
+const MAX_CONCURRENT_SYNCS: usize = 4;
+
+pub struct Client {
+    http: reqwest::Client,
+    graph_base_url: String,
+    token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DirectoryObject {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DirectoryObjects {
+    #[serde(default)]
+    value: Vec<DirectoryObject>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+impl Client {
+    /// Signs in as the app registration `MS_CLIENT_ID` of tenant `MS_TENANT_ID` with the client
+    /// secret stored in SSM at `TEAMS_CLIENT_SECRET_PARAMETER`.
+    pub async fn connect() -> Result<Client, Error> {
+        let tenant_id = env::var("MS_TENANT_ID")?;
+        let client_id = env::var("MS_CLIENT_ID")?;
+        let client_secret = get_client_secret().await?;
+        let graph_base_url =
+            env::var("TEAMS_GRAPH_BASE_URL").unwrap_or_else(|_| DEFAULT_GRAPH_BASE_URL.to_string());
+
+        let http = reqwest::Client::new();
+        let response: TokenResponse = http
+            .post(format!("{}{}/oauth2/v2.0/token", LOGIN_BASE_URL, tenant_id))
+            .form(&[
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+                ("scope", GRAPH_SCOPE),
+                ("grant_type", "client_credentials"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(Client {
+            http,
+            graph_base_url,
+            token: response.access_token,
+        })
+    }
+
+    /// Creates a client for the Graph API at `graph_base_url`, e.g. a mock server in tests.
+    pub fn with_token(token: String, graph_base_url: String) -> Client {
+        Client {
+            http: reqwest::Client::new(),
+            graph_base_url,
+            token,
+        }
+    }
+
+    /// Resolves the Azure AD object id of the user with the given e-mail address.
+    pub async fn user_id_by_email(&self, email: &str) -> Result<String, Error> {
+        let user: DirectoryObject = self
+            .http
+            .get(format!("{}users/{}", self.graph_base_url, email))
+            .query(&[("$select", "id")])
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(user.id)
+    }
+
+    /// Lists the object ids of the members of a group.
+    pub async fn group_members(&self, group_id: &str) -> Result<Vec<String>, Error> {
+        let mut members = Vec::new();
+        let mut url = format!(
+            "{}groups/{}/members?$select=id",
+            self.graph_base_url, group_id
+        );
+        loop {
+            let page: DirectoryObjects = self
+                .http
+                .get(&url)
+                .bearer_auth(&self.token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            members.extend(page.value.into_iter().map(|member| member.id));
+            match page.next_link {
+                Some(next_link) => url = next_link,
+                None => break,
+            }
+        }
+        Ok(members)
+    }
+
+    pub async fn add_group_member(&self, group_id: &str, user_id: &str) -> Result<(), Error> {
+        self.http
+            .post(format!(
+                "{}groups/{}/members/$ref",
+                self.graph_base_url, group_id
+            ))
+            .bearer_auth(&self.token)
+            .json(&json!({
+                "@odata.id": format!("{}directoryObjects/{}", self.graph_base_url, user_id)
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn remove_group_member(&self, group_id: &str, user_id: &str) -> Result<(), Error> {
+        self.http
+            .delete(format!(
+                "{}groups/{}/members/{}/$ref",
+                self.graph_base_url, group_id, user_id
+            ))
+            .bearer_auth(&self.token)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Posts a message to the incoming webhook of a channel.
+    pub async fn post_webhook(&self, webhook_url: &str, message: &Value) -> Result<(), Error> {
+        self.http
+            .post(webhook_url)
+            .json(message)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn sync_group(
+        &self,
+        hero: &Hero,
+        schedule: &Schedule,
+        mode: SyncMode,
+    ) -> UsergroupUpdate {
+        let mut update = UsergroupUpdate {
+            hero: hero.name.clone(),
+            usergroup_id: hero.teams_group_id.clone(),
+            user_ids: Vec::new(),
+            drift: None,
+            outcome: UsergroupUpdateOutcome::Updated,
+        };
+        let Some(ref group_id) = hero.teams_group_id else {
+            tracing::warn!("no Teams group id for {}", hero.name);
+            update.outcome = UsergroupUpdateOutcome::MissingUsergroup;
+            return update;
+        };
+        if let Err(err) = self
+            .sync_group_members(group_id, schedule, mode, &mut update)
+            .await
+        {
+            update.outcome = UsergroupUpdateOutcome::Failed {
+                error: err.to_string(),
+            };
+        }
+        update
+    }
+
+    /// The Graph API cannot replace the members of a group, so the drift is always computed and
+    /// applied member by member. It is only reported if the sync mode asks for it.
+    async fn sync_group_members(
+        &self,
+        group_id: &str,
+        schedule: &Schedule,
+        mode: SyncMode,
+        update: &mut UsergroupUpdate,
+    ) -> Result<(), Error> {
+        update.user_ids = future::try_join_all(
+            schedule
+                .assignees
+                .iter()
+                .map(|assignee| self.user_id_by_email(assignee)),
+        )
+        .await?;
+        let drift = Drift::between(&update.user_ids, &self.group_members(group_id).await?);
+        if mode.compares() {
+            update.drift = Some(drift.clone());
+        }
+        if mode == SyncMode::DryRun {
+            update.outcome = UsergroupUpdateOutcome::DryRun;
+            return Ok(());
+        }
+        if drift.is_empty() && mode == SyncMode::SkipUnchanged {
+            update.outcome = UsergroupUpdateOutcome::Unchanged;
+            return Ok(());
+        }
+
+        tracing::info!(
+            "Updating Teams group {}: user_ids: {:?}",
+            group_id,
+            update.user_ids
+        );
+        for user_id in drift.missing.iter() {
+            self.add_group_member(group_id, user_id).await?;
+        }
+        for user_id in drift.unexpected.iter() {
+            self.remove_group_member(group_id, user_id).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Client {
+    fn sync_members<'a>(
+        &'a self,
+        schedules: &'a [(Hero, Schedule)],
+        mode: SyncMode,
+    ) -> BoxFuture<'a, Result<Vec<UsergroupUpdate>, Error>> {
+        Box::pin(async move {
+            // Boxing the futures up front keeps the stream `Send` for callers.
+            let syncs: Vec<BoxFuture<UsergroupUpdate>> = schedules
+                .iter()
+                .map(|(hero, schedule)| self.sync_group(hero, schedule, mode).boxed())
+                .collect();
+            Ok(stream::iter(syncs)
+                .buffer_unordered(MAX_CONCURRENT_SYNCS)
+                .collect()
+                .await)
+        })
+    }
+
+    fn post_handover<'a>(
+        &'a self,
+        hero: &'a Hero,
+        schedule: &'a Schedule,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if let Some(ref webhook_url) = hero.channel {
+                self.post_webhook(webhook_url, &handover_card(schedule))
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Announces the new heroes of a schedule as an Adaptive Card. Teams has no equivalent of the
+/// acknowledge buttons of the Slack handover, they are acknowledged in Slack or the web app.
+pub fn handover_card(schedule: &Schedule) -> Value {
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "type": "AdaptiveCard",
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "version": "1.4",
+                "body": [
+                    {
+                        "type": "TextBlock",
+                        "text": format!(
+                            "**{}** is handed over to {}.",
+                            schedule.hero,
+                            schedule.assignees.join(", ")
+                        ),
+                        "wrap": true
+                    },
+                    {
+                        "type": "TextBlock",
+                        "text": format!("Shift starting {}", day_of(schedule.shift_start_time)),
+                        "isSubtle": true,
+                        "spacing": "None"
+                    }
+                ]
+            }
+        }]
+    })
+}
+
+async fn get_client_secret() -> Result<String, Error> {
+    let shared_config = aws_config::load_from_env().await;
+    let response = SsmClient::new(&shared_config)
+        .get_parameter()
+        .name(env::var("TEAMS_CLIENT_SECRET_PARAMETER")?)
+        .with_decryption(true)
+        .send()
+        .await?;
+    response
+        .parameter
+        .and_then(|parameter| parameter.value)
+        .ok_or_else(|| "Teams client secret not found as an SSM parameter.".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handover_card_lists_assignees() {
+        let schedule = Schedule {
+            hero: "oncall".to_string(),
+            shift_start_time: 1671404400,
+            assignees: vec!["a@b.de".to_string(), "c@d.de".to_string()],
+            ..Default::default()
+        };
+
+        let card = handover_card(&schedule);
+
+        let body = &card["attachments"][0]["content"]["body"];
+        assert_eq!(
+            body[0]["text"],
+            "**oncall** is handed over to a@b.de, c@d.de."
+        );
+        assert_eq!(body[1]["text"], "Shift starting 2022-12-19");
+    }
+}
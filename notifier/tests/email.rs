@@ -0,0 +1,120 @@
+use model::hero::Hero;
+use notifier::email::{Mailer, Notice, SmtpConfig, Tls};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Envelope recipients and data of every mail received by the sink.
+#[derive(Default, Debug, Clone)]
+struct Mail {
+    recipients: Vec<String>,
+    data: String,
+}
+
+type Mailbox = Arc<Mutex<Vec<Mail>>>;
+
+/// Starts an SMTP sink accepting every mail, just enough of RFC 5321 for lettre.
+async fn start(mailbox: Mailbox) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mailbox = mailbox.clone();
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                let mut mail = Mail::default();
+                writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+                while let Some(line) = lines.next_line().await.unwrap() {
+                    let command = line.to_uppercase();
+                    let reply: &[u8] = if command.starts_with("EHLO") {
+                        b"250 sink\r\n"
+                    } else if command.starts_with("RCPT TO:") {
+                        mail.recipients
+                            .push(line[8..].trim_matches(&['<', '>', ' '][..]).to_string());
+                        b"250 OK\r\n"
+                    } else if command.starts_with("DATA") {
+                        writer.write_all(b"354 Go ahead\r\n").await.unwrap();
+                        while let Some(line) = lines.next_line().await.unwrap() {
+                            if line == "." {
+                                break;
+                            }
+                            mail.data.push_str(&line);
+                            mail.data.push('\n');
+                        }
+                        mailbox.lock().unwrap().push(std::mem::take(&mut mail));
+                        b"250 OK\r\n"
+                    } else if command.starts_with("QUIT") {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    writer.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+    port
+}
+
+async fn mailer() -> (Mailbox, Mailer) {
+    let mailbox = Mailbox::default();
+    let port = start(mailbox.clone()).await;
+    let mailer = Mailer::new(SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port: Some(port),
+        tls: Tls::None,
+        credentials: None,
+        from: "Hero of the Day <hero@example.com>".to_string(),
+    })
+    .unwrap();
+    (mailbox, mailer)
+}
+
+fn hero() -> Hero {
+    Hero {
+        name: "oncall".to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn sends_assignment_with_invite() {
+    let (mailbox, mailer) = mailer().await;
+
+    mailer
+        .send(Notice::Assignment, "a@b.de", &hero(), 1671404400)
+        .await
+        .unwrap();
+
+    let mails = mailbox.lock().unwrap().clone();
+    assert_eq!(mails.len(), 1);
+    assert_eq!(mails[0].recipients, vec!["a@b.de"]);
+    assert!(mails[0]
+        .data
+        .contains("Subject: You are the hero of oncall on 2022-12-19"));
+    assert!(mails[0]
+        .data
+        .contains("text/calendar; charset=utf-8; method=REQUEST"));
+    assert!(mails[0].data.contains("filename=\"shift.ics\""));
+}
+
+#[tokio::test]
+async fn sends_cancellation_with_cancelled_invite() {
+    let (mailbox, mailer) = mailer().await;
+
+    mailer
+        .send(Notice::Cancellation, "a@b.de", &hero(), 1671404400)
+        .await
+        .unwrap();
+
+    let mails = mailbox.lock().unwrap().clone();
+    assert!(mails[0]
+        .data
+        .contains("Subject: Cancelled: oncall on 2022-12-19"));
+    assert!(mails[0]
+        .data
+        .contains("text/calendar; charset=utf-8; method=CANCEL"));
+}
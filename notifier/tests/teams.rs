@@ -0,0 +1,177 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use model::hero::{Hero, Platform};
+use model::schedule::Schedule;
+use model::sync::{SyncMode, UsergroupUpdateOutcome};
+use notifier::{teams, Notifier};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Members per group and the messages posted to the webhook.
+#[derive(Default)]
+struct Graph {
+    groups: BTreeMap<String, Vec<String>>,
+    messages: Vec<Value>,
+}
+
+type SharedGraph = Arc<Mutex<Graph>>;
+
+async fn start(graph: SharedGraph) -> String {
+    let app = Router::new()
+        .route("/users/:email", get(user))
+        .route("/groups/:group/members", get(members))
+        .route("/groups/:group/members/$ref", post(add_member))
+        .route("/groups/:group/members/:user/$ref", delete(remove_member))
+        .route("/webhook", post(webhook))
+        .with_state(graph);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/", address)
+}
+
+/// Users are named after their e-mail address, `a@b.de` is `a`.
+async fn user(Path(email): Path<String>) -> Result<Json<Value>, StatusCode> {
+    match email.split_once('@') {
+        Some((id, _)) => Ok(Json(json!({ "id": id }))),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+async fn members(State(graph): State<SharedGraph>, Path(group): Path<String>) -> Json<Value> {
+    let graph = graph.lock().unwrap();
+    let members: Vec<Value> = graph.groups[&group]
+        .iter()
+        .map(|id| json!({ "id": id }))
+        .collect();
+    Json(json!({ "value": members }))
+}
+
+async fn add_member(
+    State(graph): State<SharedGraph>,
+    Path(group): Path<String>,
+    Json(body): Json<Value>,
+) -> StatusCode {
+    let id = body["@odata.id"]
+        .as_str()
+        .unwrap()
+        .rsplit('/')
+        .next()
+        .unwrap();
+    graph
+        .lock()
+        .unwrap()
+        .groups
+        .get_mut(&group)
+        .unwrap()
+        .push(id.to_string());
+    StatusCode::NO_CONTENT
+}
+
+async fn remove_member(
+    State(graph): State<SharedGraph>,
+    Path((group, user)): Path<(String, String)>,
+) -> StatusCode {
+    graph
+        .lock()
+        .unwrap()
+        .groups
+        .get_mut(&group)
+        .unwrap()
+        .retain(|id| *id != user);
+    StatusCode::NO_CONTENT
+}
+
+async fn webhook(State(graph): State<SharedGraph>, Json(body): Json<Value>) -> StatusCode {
+    graph.lock().unwrap().messages.push(body);
+    StatusCode::OK
+}
+
+fn hero(channel: Option<String>) -> Hero {
+    Hero {
+        name: "oncall".to_string(),
+        channel,
+        platform: Platform::Teams,
+        teams_group_id: Some("G1".to_string()),
+        ..Default::default()
+    }
+}
+
+fn schedule(assignees: &[&str]) -> Schedule {
+    Schedule {
+        hero: "oncall".to_string(),
+        shift_start_time: 1671404400,
+        assignees: assignees.iter().map(|a| a.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+async fn teams_with_group(members: &[&str]) -> (SharedGraph, String, teams::Client) {
+    let graph = SharedGraph::default();
+    graph.lock().unwrap().groups.insert(
+        "G1".to_string(),
+        members.iter().map(|m| m.to_string()).collect(),
+    );
+    let base_url = start(graph.clone()).await;
+    let client = teams::Client::with_token("token".to_string(), base_url.clone());
+    (graph, base_url, client)
+}
+
+#[tokio::test]
+async fn replaces_group_members_with_assignees() {
+    let (graph, _, client) = teams_with_group(&["a", "x"]).await;
+
+    let updates = client
+        .sync_members(
+            &[(hero(None), schedule(&["a@b.de", "c@d.de"]))],
+            SyncMode::SkipUnchanged,
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        updates[0].outcome,
+        UsergroupUpdateOutcome::Updated
+    ));
+    let drift = updates[0].drift.as_ref().unwrap();
+    assert_eq!(drift.missing, vec!["c"]);
+    assert_eq!(drift.unexpected, vec!["x"]);
+    assert_eq!(graph.lock().unwrap().groups["G1"], vec!["a", "c"]);
+}
+
+#[tokio::test]
+async fn dry_run_leaves_group_alone() {
+    let (graph, _, client) = teams_with_group(&["x"]).await;
+
+    let updates = client
+        .sync_members(&[(hero(None), schedule(&["a@b.de"]))], SyncMode::DryRun)
+        .await
+        .unwrap();
+
+    assert!(matches!(updates[0].outcome, UsergroupUpdateOutcome::DryRun));
+    assert_eq!(graph.lock().unwrap().groups["G1"], vec!["x"]);
+}
+
+#[tokio::test]
+async fn posts_handover_to_webhook() {
+    let (graph, base_url, client) = teams_with_group(&[]).await;
+
+    client
+        .post_handover(
+            &hero(Some(format!("{}webhook", base_url))),
+            &schedule(&["a@b.de"]),
+        )
+        .await
+        .unwrap();
+
+    let messages = graph.lock().unwrap().messages.clone();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        messages[0]["attachments"][0]["content"]["body"][0]["text"],
+        "**oncall** is handed over to a@b.de."
+    );
+}
@@ -0,0 +1,47 @@
+use aws_sdk_dynamodb::types::WriteRequest;
+use aws_sdk_dynamodb::Client;
+use std::time::Duration;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// Most write requests DynamoDB accepts in one `BatchWriteItem`.
+const BATCH_SIZE: usize = 25;
+/// Unprocessed items are retried with a growing delay before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Writes the requests in batches, retrying the items DynamoDB did not process.
+pub(crate) async fn write_all(
+    client: &Client,
+    table_name: &str,
+    requests: Vec<WriteRequest>,
+) -> Result<(), Error> {
+    for chunk in requests.chunks(BATCH_SIZE) {
+        let mut pending = chunk.to_vec();
+        let mut attempt = 0;
+        while !pending.is_empty() {
+            if attempt == MAX_ATTEMPTS {
+                return Err(format!(
+                    "{} items were not written to {} after {} attempts",
+                    pending.len(),
+                    table_name,
+                    MAX_ATTEMPTS
+                )
+                .into());
+            }
+            if attempt > 0 {
+                tokio::time::sleep(Duration::from_millis(50 * 2u64.pow(attempt))).await;
+            }
+            let response = client
+                .batch_write_item()
+                .request_items(table_name, pending)
+                .send()
+                .await?;
+            pending = response
+                .unprocessed_items
+                .and_then(|mut unprocessed| unprocessed.remove(table_name))
+                .unwrap_or_default();
+            attempt += 1;
+        }
+    }
+    Ok(())
+}
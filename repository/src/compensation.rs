@@ -0,0 +1,123 @@
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::types::{AttributeValue, Put, TransactWriteItem, Update};
+use aws_sdk_dynamodb::Client;
+use maplit::hashmap;
+use model::compensation::{Line, SignOff};
+use std::env;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// `signed_off_at` of the header of a month, which counts its sign-offs.
+const HEADER: u64 = 0;
+
+pub struct CompensationRepository {
+    client: Client,
+    table_name: String,
+}
+
+impl CompensationRepository {
+    pub fn new(shared_config: &SdkConfig) -> CompensationRepository {
+        CompensationRepository {
+            client: Client::new(shared_config),
+            table_name: env::var("COMPENSATION_TABLE").unwrap(),
+        }
+    }
+
+    pub fn new_with_table_name(
+        shared_config: &SdkConfig,
+        table_name: String,
+    ) -> CompensationRepository {
+        CompensationRepository {
+            client: Client::new(shared_config),
+            table_name: env::var(table_name).unwrap(),
+        }
+    }
+
+    /// Sign-offs of a month, the first one first.
+    pub async fn get(&self, month: String) -> Result<Vec<SignOff>, Error> {
+        let sign_offs = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            // Skips the header of the month.
+            .key_condition_expression("#month = :month AND signed_off_at > :header")
+            .expression_attribute_names("#month", "month")
+            .expression_attribute_values(":month", AttributeValue::S(month))
+            .expression_attribute_values(":header", AttributeValue::N(HEADER.to_string()))
+            .scan_index_forward(true)
+            .send()
+            .await?
+            .items()
+            .iter()
+            .map(SignOff::try_from)
+            .collect::<Result<Vec<SignOff>, _>>()?;
+        Ok(sign_offs)
+    }
+
+    /// Stores a sign-off of a month with `previous` sign-offs. Returns `false` if the month has
+    /// been signed off since, the count in the header of the month guards against that.
+    pub async fn put(&self, sign_off: &SignOff, previous: usize) -> Result<bool, Error> {
+        let month = AttributeValue::S(sign_off.month.to_string());
+        let item = hashmap! {
+            "month".to_string() => month.clone(),
+            "signed_off_at".to_string() => AttributeValue::N(sign_off.signed_off_at.to_string()),
+            "signed_off_by".to_string() => AttributeValue::S(sign_off.signed_off_by.to_string()),
+            "weekday_rate".to_string() => AttributeValue::N(sign_off.rates.weekday.to_string()),
+            "weekend_rate".to_string() => AttributeValue::N(sign_off.rates.weekend.to_string()),
+            "holiday_rate".to_string() => AttributeValue::N(sign_off.rates.holiday.to_string()),
+            "lines".to_string() => AttributeValue::L(sign_off.lines.iter().map(line_item).collect()),
+        };
+        // Months signed off before there were headers have none until their next sign-off.
+        let condition = if previous == 0 {
+            "attribute_not_exists(sign_offs)"
+        } else {
+            "attribute_not_exists(sign_offs) OR sign_offs = :previous"
+        };
+        let mut header = Update::builder()
+            .table_name(&self.table_name)
+            .key("month", month)
+            .key("signed_off_at", AttributeValue::N(HEADER.to_string()))
+            .update_expression("SET sign_offs = :count")
+            .condition_expression(condition)
+            .expression_attribute_values(":count", AttributeValue::N((previous + 1).to_string()));
+        if previous > 0 {
+            header = header
+                .expression_attribute_values(":previous", AttributeValue::N(previous.to_string()));
+        }
+        let put = Put::builder()
+            .table_name(&self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(signed_off_at)");
+
+        let result = self
+            .client
+            .transact_write_items()
+            .transact_items(TransactWriteItem::builder().update(header.build()?).build())
+            .transact_items(TransactWriteItem::builder().put(put.build()?).build())
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .map(|err| err.is_transaction_canceled_exception())
+                    .unwrap_or(false) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+fn line_item(line: &Line) -> AttributeValue {
+    AttributeValue::M(hashmap! {
+        "member".to_string() => AttributeValue::S(line.member.to_string()),
+        "weekday_days".to_string() => AttributeValue::N(line.weekday_days.to_string()),
+        "weekend_days".to_string() => AttributeValue::N(line.weekend_days.to_string()),
+        "holiday_days".to_string() => AttributeValue::N(line.holiday_days.to_string()),
+        "amount".to_string() => AttributeValue::N(line.amount.to_string()),
+    })
+}
@@ -1,10 +1,13 @@
 use aws_config::SdkConfig;
 use aws_sdk_dynamodb::{
+    operation::put_item::builders::PutItemFluentBuilder,
     types::{AttributeValue, ReturnValue},
     Client,
 };
 use email_address::EmailAddress;
-use model::hero::Hero;
+use maplit::hashmap;
+use model::hero::{Hero, Platform};
+use std::collections::BTreeSet;
 use std::env;
 
 type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
@@ -46,6 +49,18 @@ impl HeroRepository {
         Ok(hero)
     }
 
+    /// Like `get`, but a missing hero is not an error.
+    pub async fn find(&self, name: String) -> Result<Option<Hero>, Error> {
+        let response = self
+            .client
+            .get_item()
+            .key("name", AttributeValue::S(name))
+            .table_name(&self.table_name)
+            .send()
+            .await?;
+        Ok(response.item().map(Hero::try_from).transpose()?)
+    }
+
     pub async fn list(&self) -> Result<Vec<Hero>, Error> {
         let response = self
             .client
@@ -68,11 +83,196 @@ impl HeroRepository {
     }
 
     pub async fn put(&self, hero: &Hero) -> Result<(), Error> {
-        self.client
+        self.put_item(hero).send().await?;
+        Ok(())
+    }
+
+    /// Stores a new hero. Returns `false` if a hero with the same name exists.
+    pub async fn create(&self, hero: &Hero) -> Result<bool, Error> {
+        let result = self
+            .put_item(hero)
+            .condition_expression("attribute_not_exists(#name)")
+            .expression_attribute_names("#name", "name")
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .map(|err| err.is_conditional_check_failed_exception())
+                    .unwrap_or(false) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn put_item(&self, hero: &Hero) -> PutItemFluentBuilder {
+        let mut put_item = self
+            .client
             .put_item()
             .table_name(&self.table_name)
             .item("name", AttributeValue::S(hero.name.to_string()))
-            .item("members", AttributeValue::Ss(hero.members.to_owned()))
+            .item("members", AttributeValue::Ss(hero.members.to_owned()));
+
+        if let Some(ref channel) = hero.channel {
+            put_item = put_item.item("channel", AttributeValue::S(channel.to_string()));
+        }
+
+        // DynamoDB does not allow empty sets.
+        if !hero.owners.is_empty() {
+            put_item = put_item.item("owners", AttributeValue::Ss(hero.owners.to_owned()));
+        }
+
+        if let Some(reminder_days) = hero.reminder_days {
+            put_item = put_item.item(
+                "reminder_days",
+                AttributeValue::N(reminder_days.to_string()),
+            );
+        }
+
+        if let Some(ref digest_template) = hero.digest_template {
+            put_item = put_item.item(
+                "digest_template",
+                AttributeValue::S(digest_template.to_string()),
+            );
+        }
+
+        if let Some(shift_days) = hero.shift_days {
+            put_item = put_item.item("shift_days", AttributeValue::N(shift_days.to_string()));
+        }
+
+        if let Some(min_assignees) = hero.min_assignees {
+            put_item = put_item.item(
+                "min_assignees",
+                AttributeValue::N(min_assignees.to_string()),
+            );
+        }
+
+        if let Some(ref workspace) = hero.workspace {
+            put_item = put_item.item("workspace", AttributeValue::S(workspace.to_string()));
+        }
+
+        if hero.platform != Platform::Slack {
+            put_item = put_item.item("platform", AttributeValue::S(hero.platform.to_string()));
+        }
+
+        if let Some(ref teams_group_id) = hero.teams_group_id {
+            put_item = put_item.item(
+                "teams_group_id",
+                AttributeValue::S(teams_group_id.to_string()),
+            );
+        }
+
+        let weights = &hero.weights;
+        put_item = put_item
+            .item(
+                "weekday_weight",
+                AttributeValue::N(weights.weekday.to_string()),
+            )
+            .item(
+                "weekend_weight",
+                AttributeValue::N(weights.weekend.to_string()),
+            )
+            .item(
+                "holiday_weight",
+                AttributeValue::N(weights.holiday.to_string()),
+            );
+        if let Some(night) = weights.night {
+            put_item = put_item.item("night_weight", AttributeValue::N(night.to_string()));
+        }
+        if !weights.holidays.is_empty() {
+            put_item = put_item.item(
+                "holidays",
+                AttributeValue::Ss(
+                    weights
+                        .holidays
+                        .iter()
+                        .map(|day| day.format("%Y-%m-%d").to_string())
+                        .collect::<BTreeSet<String>>()
+                        .into_iter()
+                        .collect(),
+                ),
+            );
+        }
+
+        if let Some(archived_at) = hero.archived_at {
+            put_item = put_item.item("archived_at", AttributeValue::N(archived_at.to_string()));
+        }
+
+        for (name, value) in [
+            ("description", &hero.description),
+            ("team", &hero.team),
+            ("escalation_contact", &hero.escalation_contact),
+        ] {
+            if let Some(value) = value.as_ref().filter(|value| !value.trim().is_empty()) {
+                put_item = put_item.item(name, AttributeValue::S(value.to_string()));
+            }
+        }
+
+        if !hero.tags.is_empty() {
+            put_item = put_item.item(
+                "tags",
+                AttributeValue::Ss(Hero::normalize_tags(hero.tags.to_owned())),
+            );
+        }
+
+        if !hero.runbooks.is_empty() {
+            put_item = put_item.item(
+                "runbooks",
+                AttributeValue::L(
+                    hero.runbooks
+                        .iter()
+                        .map(|link| {
+                            AttributeValue::M(hashmap! {
+                                "title".to_string() => AttributeValue::S(link.title.to_string()),
+                                "url".to_string() => AttributeValue::S(link.url.to_string()),
+                            })
+                        })
+                        .collect(),
+                ),
+            );
+        }
+
+        put_item
+    }
+
+    /// Heroes which are not archived.
+    pub async fn list_active(&self) -> Result<Vec<Hero>, Error> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|hero| !hero.is_archived())
+            .collect())
+    }
+
+    /// Archives an existing hero, keeping the time it was archived first.
+    pub async fn archive(&self, hero: String, now: u64) -> Result<(), Error> {
+        self.client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("name", AttributeValue::S(hero))
+            .update_expression("SET archived_at = if_not_exists(archived_at, :now)")
+            .condition_expression("attribute_exists(#name)")
+            .expression_attribute_names("#name", "name")
+            .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn restore(&self, hero: String) -> Result<(), Error> {
+        self.client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("name", AttributeValue::S(hero))
+            .update_expression("REMOVE archived_at")
+            .condition_expression("attribute_exists(#name)")
+            .expression_attribute_names("#name", "name")
             .send()
             .await?;
         Ok(())
@@ -0,0 +1,130 @@
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use model::job::{Job, JobStatus};
+use std::env;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+pub struct JobRepository {
+    client: Client,
+    table_name: String,
+}
+
+impl JobRepository {
+    pub fn new(shared_config: &SdkConfig) -> JobRepository {
+        JobRepository {
+            client: Client::new(shared_config),
+            table_name: env::var("JOB_TABLE").unwrap(),
+        }
+    }
+
+    pub fn new_with_table_name(shared_config: &SdkConfig, table_name: String) -> JobRepository {
+        JobRepository {
+            client: Client::new(shared_config),
+            table_name: env::var(table_name).unwrap(),
+        }
+    }
+
+    pub async fn get(&self, id: String) -> Result<Option<Job>, Error> {
+        let response = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("id", AttributeValue::S(id))
+            .send()
+            .await?;
+        Ok(response.item().map(Job::try_from).transpose()?)
+    }
+
+    /// Stores a new job, it expires after `retention_secs`.
+    pub async fn put(&self, job: &Job, retention_secs: u64) -> Result<(), Error> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .item("id", AttributeValue::S(job.id.to_string()))
+            .item("status", AttributeValue::S(job.status.as_str().to_string()))
+            .item(
+                "heroes_total",
+                AttributeValue::N(job.heroes_total.to_string()),
+            )
+            .item(
+                "heroes_done",
+                AttributeValue::N(job.heroes_done.to_string()),
+            )
+            .item("created_at", AttributeValue::N(job.created_at.to_string()))
+            .item("updated_at", AttributeValue::N(job.updated_at.to_string()))
+            .item(
+                "expires_at",
+                AttributeValue::N((job.created_at + retention_secs).to_string()),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Marks the job as running on `heroes_total` heroes, resetting the progress of an earlier
+    /// attempt.
+    pub async fn start(&self, id: String, heroes_total: u64, now: u64) -> Result<(), Error> {
+        self.client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("id", AttributeValue::S(id))
+            .update_expression(
+                "SET #status = :running, heroes_total = :total, heroes_done = :zero, updated_at = :now REMOVE heroes_failed",
+            )
+            .expression_attribute_names("#status", "status")
+            .expression_attribute_values(
+                ":running",
+                AttributeValue::S(JobStatus::Running.as_str().to_string()),
+            )
+            .expression_attribute_values(":total", AttributeValue::N(heroes_total.to_string()))
+            .expression_attribute_values(":zero", AttributeValue::N("0".to_string()))
+            .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Counts a hero as done, adding it to the failed heroes if its recalculation failed.
+    pub async fn hero_done(
+        &self,
+        id: String,
+        hero: &str,
+        failed: bool,
+        now: u64,
+    ) -> Result<(), Error> {
+        let mut update_item = self
+            .client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("id", AttributeValue::S(id))
+            .expression_attribute_values(":one", AttributeValue::N("1".to_string()))
+            .expression_attribute_values(":now", AttributeValue::N(now.to_string()));
+        if failed {
+            update_item = update_item
+                .update_expression(
+                    "ADD heroes_done :one, heroes_failed :hero SET updated_at = :now",
+                )
+                .expression_attribute_values(":hero", AttributeValue::Ss(vec![hero.to_string()]));
+        } else {
+            update_item =
+                update_item.update_expression("ADD heroes_done :one SET updated_at = :now");
+        }
+        update_item.send().await?;
+        Ok(())
+    }
+
+    pub async fn finish(&self, id: String, status: JobStatus, now: u64) -> Result<(), Error> {
+        self.client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("id", AttributeValue::S(id))
+            .update_expression("SET #status = :status, updated_at = :now")
+            .expression_attribute_names("#status", "status")
+            .expression_attribute_values(":status", AttributeValue::S(status.as_str().to_string()))
+            .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+            .send()
+            .await?;
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
+mod batch;
+pub mod compensation;
 pub mod hero;
+pub mod job;
 pub mod punch_clock;
 pub mod schedule;
+pub mod slack_user;
 pub mod user;
+pub mod webhook;
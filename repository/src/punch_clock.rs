@@ -1,8 +1,10 @@
+use crate::batch;
 use aws_config::SdkConfig;
-use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::types::{AttributeValue, DeleteRequest, PutRequest, WriteRequest};
 use aws_sdk_dynamodb::Client;
 use maplit::hashmap;
-use model::punch_clock::PunchClock;
+use model::punch_clock::{LedgerEntry, PunchClock};
+use std::collections::{HashMap, HashSet};
 use std::env;
 
 type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
@@ -72,22 +74,204 @@ impl PunchClockRepository {
         self.client
             .put_item()
             .table_name(&self.table_name)
-            .item("hero", AttributeValue::S(punch_clock.hero.to_string()))
-            .item("member", AttributeValue::S(punch_clock.member.to_string()))
-            .item(
-                "days",
-                AttributeValue::N(punch_clock.days.to_string().clone()),
-            )
-            .item(
-                "last_punch",
-                AttributeValue::N(punch_clock.last_punch.to_string().clone()),
-            )
-            .item(
-                "first_punch",
-                AttributeValue::N(punch_clock.first_punch.to_string().clone()),
-            )
+            .set_item(Some(punch_clock_item(punch_clock)))
             .send()
             .await?;
         Ok(())
     }
+
+    /// Stores the punch clocks with batched writes.
+    pub async fn put_all(&self, punch_clocks: &[PunchClock]) -> Result<(), Error> {
+        let requests = punch_clocks
+            .iter()
+            .map(|punch_clock| put_request(punch_clock_item(punch_clock)))
+            .collect::<Result<Vec<WriteRequest>, Error>>()?;
+        batch::write_all(&self.client, &self.table_name, requests).await
+    }
+
+    /// Deletes the punch clocks of all members of a hero.
+    pub async fn delete_all(&self, hero: String) -> Result<(), Error> {
+        let requests = self
+            .get_all(hero.clone())
+            .await?
+            .iter()
+            .map(|punch_clock| -> Result<WriteRequest, Error> {
+                Ok(WriteRequest::builder()
+                    .delete_request(
+                        DeleteRequest::builder()
+                            .key("hero", AttributeValue::S(hero.clone()))
+                            .key("member", AttributeValue::S(punch_clock.member.to_string()))
+                            .build()?,
+                    )
+                    .build())
+            })
+            .collect::<Result<Vec<WriteRequest>, Error>>()?;
+        batch::write_all(&self.client, &self.table_name, requests).await
+    }
+}
+
+fn punch_clock_item(punch_clock: &PunchClock) -> HashMap<String, AttributeValue> {
+    hashmap! {
+        "hero".to_string() => AttributeValue::S(punch_clock.hero.to_string()),
+        "member".to_string() => AttributeValue::S(punch_clock.member.to_string()),
+        "days".to_string() => AttributeValue::N(punch_clock.days.to_string()),
+        "points".to_string() => AttributeValue::N(punch_clock.points.to_string()),
+        "last_punch".to_string() => AttributeValue::N(punch_clock.last_punch.to_string()),
+        "first_punch".to_string() => AttributeValue::N(punch_clock.first_punch.to_string()),
+    }
+}
+
+fn put_request(item: HashMap<String, AttributeValue>) -> Result<WriteRequest, Error> {
+    Ok(WriteRequest::builder()
+        .put_request(PutRequest::builder().set_item(Some(item)).build()?)
+        .build())
+}
+
+/// Sort key of a ledger entry, member first so the shifts of one member can be queried.
+fn entry_key(member: &str, shift_start: i64) -> String {
+    format!("{}#{}", member, shift_start)
+}
+
+pub struct PunchClockLedgerRepository {
+    client: Client,
+    table_name: String,
+}
+
+impl PunchClockLedgerRepository {
+    pub fn new(shared_config: &SdkConfig) -> PunchClockLedgerRepository {
+        PunchClockLedgerRepository {
+            client: Client::new(shared_config),
+            table_name: env::var("PUNCH_CLOCK_LEDGER_TABLE").unwrap(),
+        }
+    }
+
+    pub fn new_with_table_name(
+        shared_config: &SdkConfig,
+        table_name: String,
+    ) -> PunchClockLedgerRepository {
+        PunchClockLedgerRepository {
+            client: Client::new(shared_config),
+            table_name: env::var(table_name).unwrap(),
+        }
+    }
+
+    /// Ledger entries of a hero, optionally of one member and of the shifts starting between
+    /// the given times.
+    pub async fn get(
+        &self,
+        hero: String,
+        member: Option<String>,
+        between: Option<(i64, i64)>,
+    ) -> Result<Vec<LedgerEntry>, Error> {
+        let mut attribute_values = hashmap! {
+            ":hero".to_string() => AttributeValue::S(hero)
+        };
+        let mut key_condition_expression = "hero = :hero".to_string();
+        let mut filter_expression = None;
+
+        if let Some(member) = member {
+            attribute_values.insert(
+                ":member".to_string(),
+                AttributeValue::S(format!("{}#", member)),
+            );
+            key_condition_expression = format!(
+                "{} AND begins_with(entry, :member)",
+                key_condition_expression
+            );
+        }
+        if let Some((start_time, end_time)) = between {
+            attribute_values.insert(":s".to_string(), AttributeValue::N(start_time.to_string()));
+            attribute_values.insert(":e".to_string(), AttributeValue::N(end_time.to_string()));
+            filter_expression = Some("shift_start BETWEEN :s AND :e".to_string());
+        }
+
+        let mut entries = vec![];
+        let mut exclusive_start_key = None;
+
+        loop {
+            let response = self
+                .client
+                .query()
+                .key_condition_expression(key_condition_expression.clone())
+                .set_filter_expression(filter_expression.clone())
+                .set_expression_attribute_values(Some(attribute_values.clone()))
+                .table_name(&self.table_name)
+                .set_exclusive_start_key(exclusive_start_key)
+                .send()
+                .await?;
+
+            for item in response.items() {
+                entries.push(LedgerEntry::try_from(item)?);
+            }
+            match response.last_evaluated_key {
+                Some(last_evaluated_key) => exclusive_start_key = Some(last_evaluated_key),
+                None => break,
+            }
+        }
+
+        entries.sort_by_key(|entry| entry.shift_start);
+        Ok(entries)
+    }
+
+    /// Records a shift. An entry is keyed by member and shift start, so recording a shift again
+    /// overwrites it instead of counting it twice.
+    pub async fn put(&self, entry: &LedgerEntry) -> Result<(), Error> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(ledger_item(entry)))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Replaces the ledger of a hero with the given entries, removing the shifts not among them.
+    pub async fn replace(&self, hero: String, entries: &[LedgerEntry]) -> Result<(), Error> {
+        let keys: HashSet<String> = entries
+            .iter()
+            .map(|entry| entry_key(&entry.member, entry.shift_start))
+            .collect();
+        let mut requests = vec![];
+        for stale in self
+            .get(hero.clone(), None, None)
+            .await?
+            .into_iter()
+            .filter(|entry| !keys.contains(&entry_key(&entry.member, entry.shift_start)))
+        {
+            requests.push(
+                WriteRequest::builder()
+                    .delete_request(
+                        DeleteRequest::builder()
+                            .key("hero", AttributeValue::S(hero.clone()))
+                            .key(
+                                "entry",
+                                AttributeValue::S(entry_key(&stale.member, stale.shift_start)),
+                            )
+                            .build()?,
+                    )
+                    .build(),
+            );
+        }
+        for entry in entries {
+            requests.push(put_request(ledger_item(entry))?);
+        }
+        batch::write_all(&self.client, &self.table_name, requests).await
+    }
+
+    /// Deletes the whole ledger of a hero.
+    pub async fn delete_all(&self, hero: String) -> Result<(), Error> {
+        self.replace(hero, &[]).await
+    }
+}
+
+fn ledger_item(entry: &LedgerEntry) -> HashMap<String, AttributeValue> {
+    hashmap! {
+        "hero".to_string() => AttributeValue::S(entry.hero.to_string()),
+        "entry".to_string() => AttributeValue::S(entry_key(&entry.member, entry.shift_start)),
+        "member".to_string() => AttributeValue::S(entry.member.to_string()),
+        "shift_start".to_string() => AttributeValue::N(entry.shift_start.to_string()),
+        "shift_end".to_string() => AttributeValue::N(entry.shift_end.to_string()),
+        "business_days".to_string() => AttributeValue::N(entry.business_days.to_string()),
+        "points".to_string() => AttributeValue::N(entry.points.to_string()),
+    }
 }
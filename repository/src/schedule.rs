@@ -1,10 +1,10 @@
+use crate::batch;
 use aws_config::SdkConfig;
 use aws_sdk_dynamodb::{
-    types::{AttributeValue, ReturnValue},
+    types::{AttributeValue, DeleteRequest, ReturnValue, WriteRequest},
     Client,
 };
 use email_address::EmailAddress;
-use futures::future;
 use maplit::hashmap;
 use model::schedule::Schedule;
 use std::env;
@@ -111,6 +111,24 @@ impl ScheduleRepository {
         Ok(schedules)
     }
 
+    /// Loads the schedule in effect at `start_time` followed by all schedules starting until
+    /// `end_time`, sorted by `shift_start_time`.
+    pub async fn get_in_effect(
+        &self,
+        hero: String,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<Vec<Schedule>, Error> {
+        let mut schedules: Vec<Schedule> = self
+            .get_first_before(hero.clone(), (start_time - 1) as u64)
+            .await?
+            .into_iter()
+            .collect();
+        schedules.extend(self.get(hero, Some((start_time, end_time))).await?);
+        schedules.sort_by_key(|schedule| schedule.shift_start_time);
+        Ok(schedules)
+    }
+
     pub async fn update_assignees(
         &self,
         operation: &Operation,
@@ -148,6 +166,10 @@ impl ScheduleRepository {
         );
 
         if schedule.assignees.is_empty() {
+            println!(
+                "Deleting the schedule of {} at {} since nobody is assigned anymore",
+                hero, shift_start_time
+            );
             self.client
                 .delete_item()
                 .table_name(&self.table_name)
@@ -164,6 +186,165 @@ impl ScheduleRepository {
         }
     }
 
+    /// Records that an assignee acknowledged the handover. Returns `None` if the e-mail address is
+    /// not assigned to the schedule.
+    pub async fn acknowledge(
+        &self,
+        hero: &str,
+        shift_start_time: i64,
+        assignee: &EmailAddress,
+    ) -> Result<Option<Schedule>, Error> {
+        self.add_assignee_response("acknowledged_by", hero, shift_start_time, assignee)
+            .await
+    }
+
+    /// Records that an assignee cannot take the shift and asks for a replacement. Returns `None`
+    /// if the e-mail address is not assigned to the schedule.
+    pub async fn decline(
+        &self,
+        hero: &str,
+        shift_start_time: i64,
+        assignee: &EmailAddress,
+    ) -> Result<Option<Schedule>, Error> {
+        self.add_assignee_response("declined_by", hero, shift_start_time, assignee)
+            .await
+    }
+
+    async fn add_assignee_response(
+        &self,
+        attribute: &str,
+        hero: &str,
+        shift_start_time: i64,
+        assignee: &EmailAddress,
+    ) -> Result<Option<Schedule>, Error> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("hero", AttributeValue::S(hero.to_owned()))
+            .key(
+                "shift_start_time",
+                AttributeValue::N(shift_start_time.to_string()),
+            )
+            .update_expression(format!("ADD {} :a", attribute))
+            .condition_expression("contains(assignees, :e)")
+            .expression_attribute_values(":a", AttributeValue::Ss(vec![assignee.to_string()]))
+            .expression_attribute_values(":e", AttributeValue::S(assignee.to_string()))
+            .return_values(ReturnValue::AllNew)
+            .send()
+            .await;
+
+        match result {
+            Ok(output) => Ok(output.attributes().map(Schedule::from_dynamo_item)),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .map(|err| err.is_conditional_check_failed_exception())
+                    .unwrap_or(false) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Records that an assignee was reminded of the shift. Returns `false` if the reminder was
+    /// already recorded, so reminders are never sent twice.
+    pub async fn mark_reminded(
+        &self,
+        hero: &str,
+        shift_start_time: i64,
+        assignee: &str,
+    ) -> Result<bool, Error> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("hero", AttributeValue::S(hero.to_owned()))
+            .key(
+                "shift_start_time",
+                AttributeValue::N(shift_start_time.to_string()),
+            )
+            .update_expression("ADD reminded :a")
+            .condition_expression("attribute_exists(assignees) AND NOT contains(reminded, :e)")
+            .expression_attribute_values(":a", AttributeValue::Ss(vec![assignee.to_owned()]))
+            .expression_attribute_values(":e", AttributeValue::S(assignee.to_owned()))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .map(|err| err.is_conditional_check_failed_exception())
+                    .unwrap_or(false) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Reverts [`ScheduleRepository::mark_reminded`] so the reminder is retried on the next run.
+    pub async fn unmark_reminded(
+        &self,
+        hero: &str,
+        shift_start_time: i64,
+        assignee: &str,
+    ) -> Result<(), Error> {
+        self.client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("hero", AttributeValue::S(hero.to_owned()))
+            .key(
+                "shift_start_time",
+                AttributeValue::N(shift_start_time.to_string()),
+            )
+            .update_expression("DELETE reminded :a")
+            .expression_attribute_values(":a", AttributeValue::Ss(vec![assignee.to_owned()]))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Records that the handover was escalated. Returns `false` if it already was, so the owners
+    /// are only asked once.
+    pub async fn mark_escalated(
+        &self,
+        hero: &str,
+        shift_start_time: i64,
+        escalated_at: i64,
+    ) -> Result<bool, Error> {
+        let result = self
+            .client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("hero", AttributeValue::S(hero.to_owned()))
+            .key(
+                "shift_start_time",
+                AttributeValue::N(shift_start_time.to_string()),
+            )
+            .update_expression("SET escalated_at = :t")
+            .condition_expression("attribute_not_exists(escalated_at)")
+            .expression_attribute_values(":t", AttributeValue::N(escalated_at.to_string()))
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .map(|err| err.is_conditional_check_failed_exception())
+                    .unwrap_or(false) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
     pub async fn get_last_n_before(
         &self,
         hero: String,
@@ -269,7 +450,8 @@ impl ScheduleRepository {
     }
 
     pub async fn put(&self, schedule: &Schedule) -> Result<(), Error> {
-        self.client
+        let mut put_item = self
+            .client
             .put_item()
             .table_name(&self.table_name)
             .item("hero", AttributeValue::S(schedule.hero.to_string()))
@@ -277,27 +459,37 @@ impl ScheduleRepository {
                 "shift_start_time",
                 AttributeValue::N(schedule.shift_start_time.to_string()),
             )
-            .item("assignees", AttributeValue::Ss(schedule.assignees.clone()))
-            .send()
-            .await?;
+            .item("assignees", AttributeValue::Ss(schedule.assignees.clone()));
+        if let Some(repeat_every_days) = schedule.repeat_every_days {
+            put_item = put_item.item(
+                "repeat_every_days",
+                AttributeValue::N(repeat_every_days.to_string()),
+            );
+        }
+        put_item.send().await?;
         Ok(())
     }
 
+    /// Deletes all schedules of a hero with batched writes.
     pub async fn delete(&self, hero_name: String) -> Result<(), Error> {
-        let schedules = self.get(hero_name, None).await?;
-
-        let _ = future::try_join_all(schedules.iter().map(|schedule| {
-            self.client
-                .delete_item()
-                .table_name(&self.table_name)
-                .key("hero", AttributeValue::S(schedule.hero.to_string()))
-                .key(
-                    "shift_start_time",
-                    AttributeValue::N(schedule.shift_start_time.to_string()),
-                )
-                .send()
-        }))
-        .await?;
-        Ok(())
+        let requests = self
+            .get(hero_name, None)
+            .await?
+            .iter()
+            .map(|schedule| -> Result<WriteRequest, Error> {
+                Ok(WriteRequest::builder()
+                    .delete_request(
+                        DeleteRequest::builder()
+                            .key("hero", AttributeValue::S(schedule.hero.to_string()))
+                            .key(
+                                "shift_start_time",
+                                AttributeValue::N(schedule.shift_start_time.to_string()),
+                            )
+                            .build()?,
+                    )
+                    .build())
+            })
+            .collect::<Result<Vec<WriteRequest>, Error>>()?;
+        batch::write_all(&self.client, &self.table_name, requests).await
     }
 }
@@ -0,0 +1,154 @@
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use futures::future::BoxFuture;
+use model::slack_user::SlackUser;
+use model::time::secs_now;
+use slack::cache::{UserIdCache, USER_ID_TTL_SECS};
+use std::env;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+pub struct SlackUserRepository {
+    client: Client,
+    table_name: String,
+}
+
+impl SlackUserRepository {
+    pub fn new(shared_config: &SdkConfig) -> SlackUserRepository {
+        SlackUserRepository {
+            client: Client::new(shared_config),
+            table_name: env::var("SLACK_USER_TABLE").unwrap(),
+        }
+    }
+
+    pub fn new_with_table_name(
+        shared_config: &SdkConfig,
+        table_name: String,
+    ) -> SlackUserRepository {
+        SlackUserRepository {
+            client: Client::new(shared_config),
+            table_name: env::var(table_name).unwrap(),
+        }
+    }
+
+    pub async fn get(&self, email: String, workspace: String) -> Result<Option<SlackUser>, Error> {
+        let response = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("email", AttributeValue::S(email))
+            .key("workspace", AttributeValue::S(workspace))
+            .send()
+            .await?;
+        Ok(response.item().map(SlackUser::from))
+    }
+
+    pub async fn list(&self) -> Result<Vec<SlackUser>, Error> {
+        let mut slack_users = vec![];
+        let mut exclusive_start_key = None;
+
+        loop {
+            let response = self
+                .client
+                .scan()
+                .table_name(&self.table_name)
+                .set_exclusive_start_key(exclusive_start_key)
+                .send()
+                .await?;
+            slack_users.extend(response.items().iter().map(SlackUser::from));
+            match response.last_evaluated_key {
+                Some(last_evaluated_key) => exclusive_start_key = Some(last_evaluated_key),
+                None => break,
+            }
+        }
+
+        Ok(slack_users)
+    }
+
+    pub async fn put(&self, slack_user: &SlackUser) -> Result<(), Error> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .item("email", AttributeValue::S(slack_user.email.to_string()))
+            .item(
+                "workspace",
+                AttributeValue::S(slack_user.workspace.to_string()),
+            )
+            .item(
+                "slack_user_id",
+                AttributeValue::S(slack_user.slack_user_id.to_string()),
+            )
+            .item(
+                "updated_at",
+                AttributeValue::N(slack_user.updated_at.to_string()),
+            )
+            .item(
+                "expires_at",
+                AttributeValue::N(slack_user.expires_at.to_string()),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, email: String, workspace: String) -> Result<(), Error> {
+        self.client
+            .delete_item()
+            .table_name(&self.table_name)
+            .key("email", AttributeValue::S(email))
+            .key("workspace", AttributeValue::S(workspace))
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+impl UserIdCache for SlackUserRepository {
+    fn get<'a>(&'a self, workspace: &'a str, email: &'a str) -> BoxFuture<'a, Option<String>> {
+        Box::pin(async move {
+            match SlackUserRepository::get(self, email.to_string(), workspace.to_string()).await {
+                Ok(slack_user) => slack_user
+                    .filter(|slack_user| !slack_user.is_expired(secs_now()))
+                    .map(|slack_user| slack_user.slack_user_id),
+                Err(err) => {
+                    eprintln!("Could not read cached Slack user id of {}: {}", email, err);
+                    None
+                }
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        workspace: &'a str,
+        email: &'a str,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let now = secs_now();
+            let slack_user = SlackUser {
+                email: email.to_string(),
+                workspace: workspace.to_string(),
+                slack_user_id: user_id.to_string(),
+                updated_at: now,
+                expires_at: now + USER_ID_TTL_SECS,
+            };
+            if let Err(err) = SlackUserRepository::put(self, &slack_user).await {
+                eprintln!("Could not cache Slack user id of {}: {}", email, err);
+            }
+        })
+    }
+
+    fn remove<'a>(&'a self, workspace: &'a str, email: &'a str) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            if let Err(err) =
+                SlackUserRepository::delete(self, email.to_string(), workspace.to_string()).await
+            {
+                eprintln!(
+                    "Could not remove cached Slack user id of {}: {}",
+                    email, err
+                );
+            }
+        })
+    }
+}
@@ -3,7 +3,7 @@ use aws_sdk_dynamodb::{
     types::{AttributeValue, ReturnValue},
     Client,
 };
-use model::user::User;
+use model::user::{Preferences, User};
 use std::env;
 
 type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
@@ -97,6 +97,92 @@ impl UserRepository {
         Ok(())
     }
 
+    /// Replaces the notification preferences, unset fields are removed.
+    pub async fn update_preferences(
+        &self,
+        email: String,
+        preferences: &Preferences,
+    ) -> Result<(), Error> {
+        let mut set = vec!["notification_channels = :notification_channels"];
+        let mut remove = vec![];
+        let mut update_item = self
+            .client
+            .update_item()
+            .table_name(&self.table_name)
+            .key("email", AttributeValue::S(email))
+            // `timezone` is a reserved word.
+            .expression_attribute_names("#timezone", "timezone")
+            .expression_attribute_values(
+                ":notification_channels",
+                AttributeValue::L(
+                    preferences
+                        .channels
+                        .iter()
+                        .map(|channel| AttributeValue::S(channel.as_str().to_string()))
+                        .collect(),
+                ),
+            );
+
+        match preferences.reminder_days {
+            Some(reminder_days) => {
+                set.push("reminder_days = :reminder_days");
+                update_item = update_item.expression_attribute_values(
+                    ":reminder_days",
+                    AttributeValue::N(reminder_days.to_string()),
+                );
+            }
+            None => remove.push("reminder_days"),
+        }
+        match preferences.quiet_hours {
+            Some(quiet_hours) => {
+                set.push("quiet_hours_start = :quiet_hours_start");
+                set.push("quiet_hours_end = :quiet_hours_end");
+                update_item = update_item
+                    .expression_attribute_values(
+                        ":quiet_hours_start",
+                        AttributeValue::N(quiet_hours.start.to_string()),
+                    )
+                    .expression_attribute_values(
+                        ":quiet_hours_end",
+                        AttributeValue::N(quiet_hours.end.to_string()),
+                    );
+            }
+            None => {
+                remove.push("quiet_hours_start");
+                remove.push("quiet_hours_end");
+            }
+        }
+        match preferences.timezone {
+            Some(ref timezone) => {
+                set.push("#timezone = :timezone");
+                update_item = update_item
+                    .expression_attribute_values(":timezone", AttributeValue::S(timezone.clone()));
+            }
+            None => remove.push("#timezone"),
+        }
+        // Superseded by the channels.
+        remove.push("email_notifications");
+
+        update_item
+            .update_expression(format!(
+                "SET {} REMOVE {}",
+                set.join(", "),
+                remove.join(", ")
+            ))
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// The notification preferences of a user, the defaults for unknown users.
+    pub async fn preferences(&self, email: String) -> Result<Preferences, Error> {
+        Ok(self
+            .get(email)
+            .await?
+            .map(|user| user.preferences)
+            .unwrap_or_default())
+    }
+
     pub async fn list(&self) -> Result<Vec<User>, Error> {
         let response = self
             .client
@@ -0,0 +1,226 @@
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use model::webhook::{Delivery, DeliveryStatus, Subscription};
+use std::env;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+pub struct WebhookRepository {
+    client: Client,
+    table_name: String,
+}
+
+impl WebhookRepository {
+    pub fn new(shared_config: &SdkConfig) -> WebhookRepository {
+        WebhookRepository {
+            client: Client::new(shared_config),
+            table_name: env::var("WEBHOOK_TABLE").unwrap(),
+        }
+    }
+
+    pub fn new_with_table_name(shared_config: &SdkConfig, table_name: String) -> WebhookRepository {
+        WebhookRepository {
+            client: Client::new(shared_config),
+            table_name: env::var(table_name).unwrap(),
+        }
+    }
+
+    pub async fn list(&self, hero: String) -> Result<Vec<Subscription>, Error> {
+        let response = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("hero = :hero")
+            .expression_attribute_values(":hero", AttributeValue::S(hero))
+            .send()
+            .await?;
+        let subscriptions = response
+            .items()
+            .iter()
+            .map(Subscription::try_from)
+            .collect::<Result<Vec<Subscription>, _>>()?;
+        Ok(subscriptions)
+    }
+
+    pub async fn get(&self, hero: String, id: String) -> Result<Option<Subscription>, Error> {
+        let response = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("hero", AttributeValue::S(hero))
+            .key("id", AttributeValue::S(id))
+            .send()
+            .await?;
+        Ok(response.item().map(Subscription::try_from).transpose()?)
+    }
+
+    pub async fn put(&self, subscription: &Subscription) -> Result<(), Error> {
+        let events = subscription
+            .events
+            .iter()
+            .map(|event| event.as_str().to_string())
+            .collect();
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .item("hero", AttributeValue::S(subscription.hero.to_string()))
+            .item("id", AttributeValue::S(subscription.id.to_string()))
+            .item("url", AttributeValue::S(subscription.url.to_string()))
+            .item("secret", AttributeValue::S(subscription.secret.to_string()))
+            .item("events", AttributeValue::Ss(events))
+            .item(
+                "created_at",
+                AttributeValue::N(subscription.created_at.to_string()),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, hero: String, id: String) -> Result<(), Error> {
+        self.client
+            .delete_item()
+            .table_name(&self.table_name)
+            .key("hero", AttributeValue::S(hero))
+            .key("id", AttributeValue::S(id))
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Index of the deliveries by status and `next_attempt_at`, which only pending deliveries have.
+const DUE_INDEX: &str = "status-next_attempt_at";
+
+pub struct WebhookDeliveryRepository {
+    client: Client,
+    table_name: String,
+}
+
+impl WebhookDeliveryRepository {
+    pub fn new(shared_config: &SdkConfig) -> WebhookDeliveryRepository {
+        WebhookDeliveryRepository {
+            client: Client::new(shared_config),
+            table_name: env::var("WEBHOOK_DELIVERY_TABLE").unwrap(),
+        }
+    }
+
+    pub fn new_with_table_name(
+        shared_config: &SdkConfig,
+        table_name: String,
+    ) -> WebhookDeliveryRepository {
+        WebhookDeliveryRepository {
+            client: Client::new(shared_config),
+            table_name: env::var(table_name).unwrap(),
+        }
+    }
+
+    /// The latest deliveries of a subscription, newest first.
+    pub async fn list(&self, subscription_id: String, limit: i32) -> Result<Vec<Delivery>, Error> {
+        let response = self
+            .client
+            .query()
+            .table_name(&self.table_name)
+            .key_condition_expression("subscription_id = :subscription_id")
+            .expression_attribute_values(":subscription_id", AttributeValue::S(subscription_id))
+            .scan_index_forward(false)
+            .limit(limit)
+            .send()
+            .await?;
+        let deliveries = response
+            .items()
+            .iter()
+            .map(Delivery::try_from)
+            .collect::<Result<Vec<Delivery>, _>>()?;
+        Ok(deliveries)
+    }
+
+    /// Pending deliveries whose next attempt is due.
+    pub async fn list_due(&self, now: u64) -> Result<Vec<Delivery>, Error> {
+        let mut deliveries = vec![];
+        let mut exclusive_start_key = None;
+
+        loop {
+            let response = self
+                .client
+                .query()
+                .table_name(&self.table_name)
+                .index_name(DUE_INDEX)
+                .key_condition_expression("#status = :pending AND next_attempt_at <= :now")
+                .expression_attribute_names("#status", "status")
+                .expression_attribute_values(
+                    ":pending",
+                    AttributeValue::S(DeliveryStatus::Pending.as_str().to_string()),
+                )
+                .expression_attribute_values(":now", AttributeValue::N(now.to_string()))
+                .set_exclusive_start_key(exclusive_start_key)
+                .send()
+                .await?;
+            for item in response.items() {
+                deliveries.push(Delivery::try_from(item)?);
+            }
+            match response.last_evaluated_key {
+                Some(last_evaluated_key) => exclusive_start_key = Some(last_evaluated_key),
+                None => break,
+            }
+        }
+
+        Ok(deliveries)
+    }
+
+    /// Stores a delivery, it expires from the log after `retention_secs`.
+    pub async fn put(&self, delivery: &Delivery, retention_secs: u64) -> Result<(), Error> {
+        let mut put_item = self
+            .client
+            .put_item()
+            .table_name(&self.table_name)
+            .item(
+                "subscription_id",
+                AttributeValue::S(delivery.subscription_id.to_string()),
+            )
+            .item(
+                "delivery_id",
+                AttributeValue::S(delivery.delivery_id.to_string()),
+            )
+            .item("hero", AttributeValue::S(delivery.hero.to_string()))
+            .item(
+                "event_type",
+                AttributeValue::S(delivery.event_type.as_str().to_string()),
+            )
+            .item("payload", AttributeValue::S(delivery.payload.to_string()))
+            .item(
+                "status",
+                AttributeValue::S(delivery.status.as_str().to_string()),
+            )
+            .item("attempts", AttributeValue::N(delivery.attempts.to_string()))
+            .item(
+                "created_at",
+                AttributeValue::N(delivery.created_at.to_string()),
+            )
+            .item(
+                "expires_at",
+                AttributeValue::N((delivery.created_at + retention_secs).to_string()),
+            );
+
+        if let Some(last_status_code) = delivery.last_status_code {
+            put_item = put_item.item(
+                "last_status_code",
+                AttributeValue::N(last_status_code.to_string()),
+            );
+        }
+
+        if let Some(ref last_error) = delivery.last_error {
+            put_item = put_item.item("last_error", AttributeValue::S(last_error.to_string()));
+        }
+
+        if let Some(next_attempt_at) = delivery.next_attempt_at {
+            put_item = put_item.item(
+                "next_attempt_at",
+                AttributeValue::N(next_attempt_at.to_string()),
+            );
+        }
+
+        put_item.send().await?;
+        Ok(())
+    }
+}
@@ -1,9 +1,13 @@
 use lambda_http::{
-    http::header::{ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE},
-    Body, Error, Response,
+    http::header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_DISPOSITION,
+        CONTENT_TYPE,
+    },
+    Body, Error, Request, RequestExt, Response,
 };
 use serde::Serialize;
 use serde_json::json;
+use std::env;
 
 pub fn ok<T>(body: T) -> Result<Response<Body>, Error>
 where
@@ -20,6 +24,23 @@ where
     )
 }
 
+/// Responds with a CSV document, offered for download as `filename`.
+pub fn csv(body: String, filename: &str) -> Result<Response<Body>, Error> {
+    Ok::<Response<Body>, Error>(
+        Response::builder()
+            .status(200)
+            .header(CONTENT_TYPE, "text/csv; charset=utf-8")
+            .header(
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            )
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .header(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true")
+            .body(Body::Text(body))
+            .expect("failed to render response"),
+    )
+}
+
 pub fn bad_request(body: String) -> Result<Response<Body>, Error> {
     Ok::<Response<Body>, Error>(
         Response::builder()
@@ -43,3 +64,47 @@ pub fn server_error(body: String) -> Result<Response<Body>, Error> {
             .expect("failed to render response"),
     )
 }
+
+pub fn unauthorized(body: String) -> Result<Response<Body>, Error> {
+    Ok::<Response<Body>, Error>(
+        Response::builder()
+            .status(401)
+            .header(CONTENT_TYPE, "application/json")
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .header(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true")
+            .body(Body::Text(body))
+            .expect("failed to render response"),
+    )
+}
+
+pub fn forbidden(body: String) -> Result<Response<Body>, Error> {
+    Ok::<Response<Body>, Error>(
+        Response::builder()
+            .status(403)
+            .header(CONTENT_TYPE, "application/json")
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .header(ACCESS_CONTROL_ALLOW_CREDENTIALS, "true")
+            .body(Body::Text(body))
+            .expect("failed to render response"),
+    )
+}
+
+/// E-mail address of the signed-in user, passed on by the authorizer.
+pub fn caller_email(event: &Request) -> Option<String> {
+    event
+        .request_context_ref()?
+        .authorizer()?
+        .fields
+        .get("email")?
+        .as_str()
+        .map(String::from)
+}
+
+/// Admins are configured as comma separated e-mail addresses in `ADMIN_EMAILS`.
+pub fn is_admin(email: &str) -> bool {
+    env::var("ADMIN_EMAILS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .any(|admin| !admin.is_empty() && admin.eq_ignore_ascii_case(email))
+}
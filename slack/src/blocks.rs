@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+/// Slack message composed of Block Kit blocks, see https://api.slack.com/block-kit.
+#[derive(Serialize, Debug, Clone)]
+pub struct Message {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_type: Option<ResponseType>,
+    /// Fallback text shown in notifications.
+    pub text: String,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseType {
+    Ephemeral,
+    InChannel,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Section { text: Text },
+    Context { elements: Vec<Text> },
+    Actions { elements: Vec<Element> },
+    Divider,
+}
+
+/// Interactive element, see https://api.slack.com/reference/block-kit/block-elements.
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Element {
+    Button {
+        text: Text,
+        action_id: String,
+        value: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        style: Option<ButtonStyle>,
+    },
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonStyle {
+    Primary,
+    Danger,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Text {
+    Mrkdwn { text: String },
+    PlainText { text: String },
+}
+
+impl Message {
+    /// Message only visible to the user who issued a slash command.
+    pub fn ephemeral(text: String, blocks: Vec<Block>) -> Message {
+        Message {
+            response_type: Some(ResponseType::Ephemeral),
+            text,
+            blocks,
+        }
+    }
+}
+
+impl Message {
+    /// Message posted to a channel.
+    pub fn new(text: String, blocks: Vec<Block>) -> Message {
+        Message {
+            response_type: None,
+            text,
+            blocks,
+        }
+    }
+}
+
+impl Block {
+    pub fn section(text: impl Into<String>) -> Block {
+        Block::Section {
+            text: Text::mrkdwn(text),
+        }
+    }
+
+    pub fn context(text: impl Into<String>) -> Block {
+        Block::Context {
+            elements: vec![Text::mrkdwn(text)],
+        }
+    }
+}
+
+impl Element {
+    pub fn button(
+        text: impl Into<String>,
+        action_id: impl Into<String>,
+        value: impl Into<String>,
+        style: Option<ButtonStyle>,
+    ) -> Element {
+        Element::Button {
+            text: Text::plain(text),
+            action_id: action_id.into(),
+            value: value.into(),
+            style,
+        }
+    }
+}
+
+impl Text {
+    pub fn mrkdwn(text: impl Into<String>) -> Text {
+        Text::Mrkdwn { text: text.into() }
+    }
+
+    pub fn plain(text: impl Into<String>) -> Text {
+        Text::PlainText { text: text.into() }
+    }
+}
+
+/// Formats a Slack user id as a mention.
+pub fn mention(user_id: &str) -> String {
+    format!("<@{}>", user_id)
+}
@@ -0,0 +1,16 @@
+use futures::future::BoxFuture;
+
+/// How long a cached Slack user id is trusted before it is looked up again. Bounds how long a
+/// changed e-mail address in Slack can resolve to the previous user.
+pub const USER_ID_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Mapping from e-mail address to Slack user id consulted before calling `users.lookupByEmail`.
+/// User ids differ between workspaces, so every entry belongs to one.
+///
+/// Failures of the cache are never fatal, implementations log them and fall back to Slack.
+pub trait UserIdCache: Send + Sync {
+    fn get<'a>(&'a self, workspace: &'a str, email: &'a str) -> BoxFuture<'a, Option<String>>;
+    fn put<'a>(&'a self, workspace: &'a str, email: &'a str, user_id: &'a str)
+        -> BoxFuture<'a, ()>;
+    fn remove<'a>(&'a self, workspace: &'a str, email: &'a str) -> BoxFuture<'a, ()>;
+}
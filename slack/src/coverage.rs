@@ -0,0 +1,38 @@
+use crate::blocks::{Block, Message};
+use model::coverage::CoverageReport;
+
+/// Warns a hero's channel about days nobody or not enough people are assigned to.
+pub fn coverage_alert_message(report: &CoverageReport) -> Message {
+    let text = format!("*{}* is not fully covered.", report.hero);
+    let mut blocks = vec![Block::section(format!(":warning: {}", text))];
+    if !report.gaps.is_empty() {
+        let gaps: Vec<String> = report
+            .gaps
+            .iter()
+            .map(|day| day.format("%a %d.%m.").to_string())
+            .collect();
+        blocks.push(Block::section(format!(
+            "Nobody is assigned on {}.",
+            gaps.join(", ")
+        )));
+    }
+    if !report.understaffed.is_empty() {
+        let understaffed: Vec<String> = report
+            .understaffed
+            .iter()
+            .map(|day| {
+                format!(
+                    "{} ({} of {})",
+                    day.day.format("%a %d.%m."),
+                    day.assignees.len(),
+                    report.min_assignees
+                )
+            })
+            .collect();
+        blocks.push(Block::section(format!(
+            "Not enough people are assigned on {}.",
+            understaffed.join(", ")
+        )));
+    }
+    Message::new(text, blocks)
+}
@@ -0,0 +1,48 @@
+use crate::blocks::{Block, Message};
+use chrono::NaiveDate;
+
+/// Used for heroes without a `digest_template`.
+pub const DEFAULT_TEMPLATE: &str = "*{hero}*\n{schedule}";
+
+/// One hero's week in the digest. Assignees are already rendered, e.g. as mentions.
+pub struct DigestEntry {
+    pub hero: String,
+    pub template: Option<String>,
+    pub days: Vec<(NaiveDate, Vec<String>)>,
+}
+
+/// Renders the weekly digest of a channel. Templates may use the `{hero}`, `{week}` and
+/// `{schedule}` placeholders.
+pub fn digest_message(week_start: NaiveDate, entries: &[DigestEntry]) -> Message {
+    let title = format!("Heroes for the week of {}", week_start);
+    let mut blocks = vec![Block::section(format!("*{}*", title))];
+    for entry in entries.iter() {
+        blocks.push(Block::Divider);
+        blocks.push(Block::section(render(week_start, entry)));
+    }
+    Message::new(title, blocks)
+}
+
+fn render(week_start: NaiveDate, entry: &DigestEntry) -> String {
+    let schedule = entry
+        .days
+        .iter()
+        .map(|(day, assignees)| {
+            let assigned = if assignees.is_empty() {
+                ":warning: _nobody assigned_".to_string()
+            } else {
+                assignees.join(", ")
+            };
+            format!("{}: {}", day.format("%a %d.%m."), assigned)
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    entry
+        .template
+        .as_deref()
+        .unwrap_or(DEFAULT_TEMPLATE)
+        .replace("{hero}", &entry.hero)
+        .replace("{week}", &week_start.to_string())
+        .replace("{schedule}", &schedule)
+}
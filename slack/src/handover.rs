@@ -0,0 +1,110 @@
+use crate::blocks::{mention, Block, ButtonStyle, Element, Message};
+use model::schedule::Schedule;
+use model::time::day_of;
+use std::str::FromStr;
+
+pub const ACKNOWLEDGE_ACTION_ID: &str = "handover_acknowledge";
+pub const DECLINE_ACTION_ID: &str = "handover_decline";
+
+/// Identifies the schedule a handover button belongs to. Encoded as `<shift_start_time>:<hero>`
+/// in the value of the button.
+#[derive(Debug, PartialEq)]
+pub struct HandoverRef {
+    pub hero: String,
+    pub shift_start_time: i64,
+}
+
+impl FromStr for HandoverRef {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<HandoverRef, ()> {
+        let (shift_start_time, hero) = s.split_once(':').ok_or(())?;
+        Ok(HandoverRef {
+            hero: hero.to_string(),
+            shift_start_time: shift_start_time.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+impl std::fmt::Display for HandoverRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.shift_start_time, self.hero)
+    }
+}
+
+/// Announces the new heroes of a schedule and asks them to acknowledge the handover.
+pub fn handover_message(schedule: &Schedule, user_ids: &[String]) -> Message {
+    let mentions: Vec<String> = user_ids.iter().map(|user_id| mention(user_id)).collect();
+    let text = format!("{}: {}", schedule.hero, mentions.join(", "));
+    let value = HandoverRef {
+        hero: schedule.hero.clone(),
+        shift_start_time: schedule.shift_start_time,
+    }
+    .to_string();
+
+    Message::new(
+        text.clone(),
+        vec![
+            Block::section(format!(
+                "*{}* is handed over to {}.",
+                schedule.hero,
+                mentions.join(", ")
+            )),
+            Block::Actions {
+                elements: vec![
+                    Element::button(
+                        "Acknowledge",
+                        ACKNOWLEDGE_ACTION_ID,
+                        value.clone(),
+                        Some(ButtonStyle::Primary),
+                    ),
+                    Element::button(
+                        "I can't, find a replacement",
+                        DECLINE_ACTION_ID,
+                        value,
+                        Some(ButtonStyle::Danger),
+                    ),
+                ],
+            },
+        ],
+    )
+}
+
+/// Tells the owners of a hero that a handover needs their attention.
+pub fn escalation_message(schedule: &Schedule) -> Message {
+    let pending: Vec<&String> = schedule
+        .assignees
+        .iter()
+        .filter(|assignee| !schedule.acknowledged_by.contains(assignee))
+        .collect();
+    let text = if schedule.declined_by.is_empty() {
+        format!(
+            "The handover of *{}* on {} was not acknowledged by {}.",
+            schedule.hero,
+            day_of(schedule.shift_start_time),
+            pending
+                .iter()
+                .map(|assignee| assignee.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    } else {
+        format!(
+            "{} can't take *{}* on {}. Please find a replacement.",
+            schedule.declined_by.join(", "),
+            schedule.hero,
+            day_of(schedule.shift_start_time)
+        )
+    };
+    Message::new(text.clone(), vec![Block::section(text)])
+}
+
+/// Reminds an assignee of an upcoming shift.
+pub fn reminder_message(schedule: &Schedule) -> Message {
+    let text = format!(
+        "Reminder: you are the hero *{}* on {}.",
+        schedule.hero,
+        day_of(schedule.shift_start_time)
+    );
+    Message::new(text.clone(), vec![Block::section(text)])
+}
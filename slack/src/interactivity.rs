@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+/// Form encoded request Slack sends to the interactivity endpoint, see
+/// https://api.slack.com/interactivity/handling#payloads.
+#[derive(Deserialize, Debug)]
+pub struct InteractionRequest {
+    /// JSON encoded [`BlockActions`].
+    pub payload: String,
+}
+
+/// Payload sent when a user clicks a button of a Block Kit message.
+#[derive(Deserialize, Debug)]
+pub struct BlockActions {
+    pub team: InteractionTeam,
+    pub user: InteractionUser,
+    pub actions: Vec<Action>,
+    pub response_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InteractionTeam {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InteractionUser {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Action {
+    pub action_id: String,
+    pub value: Option<String>,
+}
@@ -1,30 +1,80 @@
 use aws_sdk_ssm::error::SdkError;
 use aws_sdk_ssm::operation::get_parameter::{GetParameterError, GetParameterOutput};
 use aws_sdk_ssm::Client as SsmClient;
+use blocks::Message;
+use cache::UserIdCache;
+use futures::future::BoxFuture;
 use futures::prelude::*;
 use model::schedule::Schedule;
-use serde::Deserialize;
+use model::sync::{Drift, SyncMode, UsergroupUpdate, UsergroupUpdateOutcome};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::env::VarError;
+use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::OnceCell;
+use transport::{ResponseMetadata, Tier, Transport, DEFAULT_BASE_URL, MAX_CONCURRENT_REQUESTS};
+use workspace::DEFAULT_WORKSPACE;
+
+pub mod blocks;
+pub mod cache;
+pub mod coverage;
+pub mod digest;
+pub mod handover;
+pub mod interactivity;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod signature;
+pub mod transport;
+pub mod workspace;
 
 type Result<T> = std::result::Result<T, SlackError>;
 
 #[derive(Error, Debug)]
 pub enum SlackError {
-    #[error("Could not update user group users.")]
-    UserGroupUsersUpdateError,
+    #[error("Could not update user group users: {0}")]
+    UserGroupUsersUpdateError(String),
     #[error("Could not lookup by email. {0}")]
     UsersLookupByEmailError(String),
-    #[error("Could not list user groups")]
-    UserGroupsList,
-    #[error("Could not create user group.")]
-    CreateUserGroupError,
-    #[error("Could not post message.")]
-    PostMessageError,
+    #[error("Could not lookup {email} by email: {source}")]
+    UsersLookupByEmailRequestError {
+        email: String,
+        #[source]
+        source: Box<SlackError>,
+    },
+    #[error("No Slack user with email {0}.")]
+    UserNotFoundError(String),
+    #[error("Could not list user group users: {0}")]
+    UserGroupUsersListError(String),
+    #[error("Could not list user groups: {0}")]
+    UserGroupsList(String),
+    #[error("Could not list users: {0}")]
+    UsersListError(String),
+    #[error("Could not create user group: {0}")]
+    CreateUserGroupError(String),
+    #[error("Could not post message: {0}")]
+    PostMessageError(String),
+    #[error("Giving up after {0} retries.")]
+    RetriesExhaustedError(u32),
+    #[error("Could not respond to interaction.")]
+    RespondError,
+    #[error("Could not get user info. {0}")]
+    UsersInfoError(String),
+    #[error("Could not get user info of {user_id}: {source}")]
+    UsersInfoRequestError {
+        user_id: String,
+        #[source]
+        source: Box<SlackError>,
+    },
+    #[error("Could not identify the workspace: {0}")]
+    AuthTestError(String),
+    #[error("No workspace with team id {0}.")]
+    UnknownTeamError(String),
     #[error("Could not get Slack token: {0}")]
     GetSlackTokenError(&'static str),
+    #[error("Invalid request signature: {0}")]
+    InvalidSignatureError(&'static str),
     #[error("Reqwest error: {0}")]
     ReqwestError(#[from] reqwest::Error),
     #[error("Get parameter error: {0}")]
@@ -34,8 +84,10 @@ pub enum SlackError {
 }
 
 pub struct Client {
-    client: reqwest::Client,
-    token: String,
+    transport: Transport,
+    workspace: String,
+    user_id_cache: Option<Arc<dyn UserIdCache>>,
+    team_id: OnceCell<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -47,205 +99,512 @@ pub struct UserGroup {
 #[derive(Deserialize, Debug)]
 struct UserGroupsListResponse {
     ok: bool,
+    error: Option<String>,
+    #[serde(default)]
     usergroups: Vec<UserGroup>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct User {
     pub id: String,
+    pub profile: Option<Profile>,
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Profile {
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserResponse {
+    ok: bool,
+    error: Option<String>,
+    user: Option<User>,
 }
 
 #[derive(Deserialize, Debug)]
-struct UsersLookupByEmailResponse {
+struct UsergroupUsersListResponse {
     ok: bool,
-    user: User,
+    error: Option<String>,
+    #[serde(default)]
+    users: Vec<String>,
 }
 
 #[derive(Deserialize, Debug)]
-struct UsergroupsUsersUpdateResponse {
+struct UsersListResponse {
     ok: bool,
+    error: Option<String>,
+    #[serde(default)]
+    members: Vec<User>,
+    #[serde(default)]
+    response_metadata: ResponseMetadata,
+}
+
+#[derive(Serialize, Debug)]
+struct PostMessageRequest<'a> {
+    channel: &'a str,
+    #[serde(flatten)]
+    message: &'a Message,
 }
 
 #[derive(Deserialize, Debug)]
-struct PostMessageResponse {
+struct AuthTestResponse {
     ok: bool,
+    error: Option<String>,
+    team_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OkResponse {
+    ok: bool,
+    error: Option<String>,
+}
+
+fn error_code(error: Option<String>) -> String {
+    error.unwrap_or_else(|| "unknown_error".to_string())
 }
 
 impl Client {
+    /// Creates a client for the Web API at `SLACK_API_BASE_URL`, falling back to Slack itself.
     pub fn new(token: String) -> Client {
+        let base_url =
+            env::var("SLACK_API_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Client::with_base_url(token, base_url)
+    }
+
+    /// Creates a client for the Web API at `base_url`, e.g. a mock Slack server in tests.
+    pub fn with_base_url(token: String, base_url: String) -> Client {
         Client {
-            client: reqwest::Client::new(),
-            token,
+            transport: Transport::new(token, base_url),
+            workspace: DEFAULT_WORKSPACE.to_string(),
+            user_id_cache: None,
+            team_id: OnceCell::new(),
         }
     }
 
+    /// Names the workspace the token belongs to, which scopes the user id cache.
+    pub fn in_workspace(mut self, workspace: &str) -> Client {
+        self.workspace = workspace.to_string();
+        self
+    }
+
+    pub fn workspace(&self) -> &str {
+        &self.workspace
+    }
+
+    /// Consults `cache` before looking up Slack user ids by e-mail address.
+    pub fn with_user_id_cache(mut self, cache: Arc<dyn UserIdCache>) -> Client {
+        self.user_id_cache = Some(cache);
+        self
+    }
+
+    /// The id of the Slack team the token belongs to, asked for once per client.
+    pub async fn team_id(&self) -> Result<String> {
+        self.team_id
+            .get_or_try_init(|| async {
+                let result: AuthTestResponse =
+                    self.transport.get(Tier::Tier4, "auth.test", &[]).await?;
+                match result.team_id {
+                    Some(team_id) if result.ok => Ok(team_id),
+                    _ => Err(SlackError::AuthTestError(error_code(result.error))),
+                }
+            })
+            .await
+            .cloned()
+    }
+
     /// Lists all Slack groups in order to have an id to handle relation.
     pub async fn usergroups_list(&self) -> Result<Vec<UserGroup>> {
         let result: UserGroupsListResponse = self
-            .client
-            .get(format!(
-                "https://slack.com/api/usergroups.list?token={}&pretty=1",
-                self.token
-            ))
-            .send()
-            .await?
-            .json()
+            .transport
+            .get(Tier::Tier2, "usergroups.list", &[])
             .await?;
         if result.ok {
             Ok(result.usergroups)
         } else {
-            Err(SlackError::UserGroupsList)
+            Err(SlackError::UserGroupsList(error_code(result.error)))
+        }
+    }
+
+    /// Lists all users of the workspace, following the pagination cursor.
+    pub async fn users_list(&self) -> Result<Vec<User>> {
+        let mut users = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let result: UsersListResponse = self
+                .transport
+                .get(
+                    Tier::Tier2,
+                    "users.list",
+                    &[("limit", "200"), ("cursor", cursor.as_str())],
+                )
+                .await?;
+            if !result.ok {
+                return Err(SlackError::UsersListError(error_code(result.error)));
+            }
+            users.extend(result.members);
+            match result.response_metadata.next_cursor() {
+                Some(next_cursor) => cursor = next_cursor,
+                None => break,
+            }
         }
+        Ok(users)
     }
 
     /// Resolves Slack user id by using user's e-mail address.
     pub async fn lookup_by_email(&self, email: String) -> Result<User> {
-        let result: UsersLookupByEmailResponse = self
-            .client
-            .get(format!(
-                "https://slack.com/api/users.lookupByEmail?token={}&pretty=1&email={}",
-                self.token, email
-            ))
-            .send()
-            .map_err(|_| SlackError::UsersLookupByEmailError(email.clone()))
-            .await?
-            .json()
-            .map_err(|_| SlackError::UsersLookupByEmailError(email.clone()))
-            .await?;
-        if result.ok {
-            Ok(result.user)
-        } else {
-            Err(SlackError::UsersLookupByEmailError(email))
+        let result: UserResponse = self
+            .transport
+            .get(
+                Tier::Tier3,
+                "users.lookupByEmail",
+                &[("email", email.as_str())],
+            )
+            .await
+            .map_err(|err| SlackError::UsersLookupByEmailRequestError {
+                email: email.clone(),
+                source: Box::new(err),
+            })?;
+        match result.user {
+            Some(user) if result.ok => Ok(user),
+            _ if result.error.as_deref() == Some("users_not_found") => {
+                Err(SlackError::UserNotFoundError(email))
+            }
+            _ => Err(SlackError::UsersLookupByEmailError(format!(
+                "{} ({})",
+                email,
+                error_code(result.error)
+            ))),
+        }
+    }
+
+    /// Resolves the Slack user id of an e-mail address, preferring the user id cache if there is
+    /// one. Users that are no longer found in Slack, e.g. because their e-mail address changed,
+    /// are dropped from the cache.
+    pub async fn user_id_by_email(&self, email: String) -> Result<String> {
+        let Some(cache) = &self.user_id_cache else {
+            return self.lookup_by_email(email).await.map(|user| user.id);
+        };
+        if let Some(user_id) = cache.get(&self.workspace, &email).await {
+            return Ok(user_id);
+        }
+        match self.lookup_by_email(email.clone()).await {
+            Ok(user) => {
+                cache.put(&self.workspace, &email, &user.id).await;
+                Ok(user.id)
+            }
+            Err(err @ SlackError::UserNotFoundError(_)) => {
+                cache.remove(&self.workspace, &email).await;
+                Err(err)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Resolves user's e-mail address by using the Slack user id, the reverse of `lookup_by_email`.
+    pub async fn lookup_email(&self, user_id: String) -> Result<String> {
+        let result: UserResponse = self
+            .transport
+            .get(Tier::Tier4, "users.info", &[("user", user_id.as_str())])
+            .await
+            .map_err(|err| SlackError::UsersInfoRequestError {
+                user_id: user_id.clone(),
+                source: Box::new(err),
+            })?;
+        match result.user.and_then(|user| user.profile?.email) {
+            Some(email) if result.ok => Ok(email),
+            _ => Err(SlackError::UsersInfoError(format!(
+                "{} ({})",
+                user_id,
+                error_code(result.error)
+            ))),
         }
     }
 
     pub async fn usergroups_users_update(
         &self,
-        usergroup_id: &String,
+        usergroup_id: &str,
         user_ids: &[String],
     ) -> Result<()> {
-        let url =
-            format!(
-            "https://slack.com/api/usergroups.users.update?token={}&pretty=1&usergroup={}&users={}",
-            self.token, usergroup_id, user_ids.join(",")
-        );
-        println!("url: {}", url);
-        let result: UsergroupsUsersUpdateResponse =
-            self.client.post(url).send().await?.json().await?;
+        let users = user_ids.join(",");
+        let result: OkResponse = self
+            .transport
+            .post_form(
+                Tier::Tier2,
+                "usergroups.users.update",
+                &[("usergroup", usergroup_id), ("users", users.as_str())],
+            )
+            .await?;
         if result.ok {
             Ok(())
         } else {
-            Err(SlackError::UserGroupUsersUpdateError)
+            Err(SlackError::UserGroupUsersUpdateError(error_code(
+                result.error,
+            )))
         }
     }
 
-    pub async fn create_usergroup(&self, usergroup_name: &String) -> Result<()> {
-        let url = format!(
-            "https://slack.com/api/usergroups.create?token={}&pretty=1&name={}",
-            self.token, usergroup_name
-        );
-        println!("url: {}", url);
-        let result: UsergroupsUsersUpdateResponse =
-            self.client.post(url).send().await?.json().await?;
+    /// Lists the Slack user ids in a usergroup.
+    pub async fn usergroups_users_list(&self, usergroup_id: &str) -> Result<Vec<String>> {
+        let result: UsergroupUsersListResponse = self
+            .transport
+            .get(
+                Tier::Tier4,
+                "usergroups.users.list",
+                &[("usergroup", usergroup_id)],
+            )
+            .await?;
+        if result.ok {
+            Ok(result.users)
+        } else {
+            Err(SlackError::UserGroupUsersListError(error_code(
+                result.error,
+            )))
+        }
+    }
+
+    pub async fn create_usergroup(&self, usergroup_name: &str) -> Result<()> {
+        let result: OkResponse = self
+            .transport
+            .post_form(
+                Tier::Tier2,
+                "usergroups.create",
+                &[("name", usergroup_name)],
+            )
+            .await?;
         if result.ok {
             Ok(())
         } else {
-            Err(SlackError::CreateUserGroupError)
+            Err(SlackError::CreateUserGroupError(error_code(result.error)))
         }
     }
 
     async fn look_up_user_ids_by_email(&self, schedule: &Schedule) -> Result<Vec<String>> {
-        future::try_join_all(schedule.assignees.iter().map(|assignee| {
-            self.lookup_by_email(assignee.clone())
-                .map_ok(|user| user.id)
-        }))
-        .await
-        .map_err(|err| {
-            println!("{}", err);
-            err
-        })
+        // Boxing the futures up front keeps the stream `Send` for callers.
+        let lookups: Vec<BoxFuture<Result<String>>> = schedule
+            .assignees
+            .iter()
+            .map(|assignee| self.user_id_by_email(assignee.clone()).boxed())
+            .collect();
+        stream::iter(lookups)
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await
+            .map_err(|err| {
+                tracing::warn!("{}", err);
+                err
+            })
     }
 
+    /// Replaces the members of every hero's usergroup with the assignees of its schedule and
+    /// reports the outcome per usergroup.
     pub async fn usergroups_users_update_with_schedules(
         &self,
         schedules: Vec<Schedule>,
-    ) -> Result<()> {
+    ) -> Result<Vec<UsergroupUpdate>> {
+        self.sync_usergroups(schedules, SyncMode::Replace).await
+    }
+
+    /// Brings the members of every hero's usergroup in line with the assignees of its schedule,
+    /// reporting the outcome and, unless replacing blindly, the drift per usergroup.
+    pub async fn sync_usergroups(
+        &self,
+        schedules: Vec<Schedule>,
+        mode: SyncMode,
+    ) -> Result<Vec<UsergroupUpdate>> {
         let usergroup_id_map: HashMap<String, String> = self
             .usergroups_list()
             .map_ok(|usergroups| usergroups.into_iter().map(|a| (a.handle, a.id)).collect())
             .await?;
 
-        let updates: Vec<Option<(&String, Vec<String>)>> =
-            future::join_all(schedules.iter().map(|schedule| {
-                self.look_up_user_ids_by_email(schedule).map_ok(|users| {
-                    match usergroup_id_map.get(&schedule.hero) {
-                        Some(usergroup_id) => Some((usergroup_id, users)),
-                        None => {
-                            println!("no usergroup id for {}", schedule.hero);
-                            None
-                        }
-                    }
-                })
-            }))
-            .await
-            .into_iter()
-            .flatten()
+        let updates: Vec<BoxFuture<UsergroupUpdate>> = schedules
+            .iter()
+            .map(|schedule| {
+                self.sync_usergroup(&usergroup_id_map, schedule, mode)
+                    .boxed()
+            })
             .collect();
+        let updates = stream::iter(updates)
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
 
-        let filtered: Vec<(&String, Vec<String>)> = updates.into_iter().flatten().collect();
+        Ok(updates)
+    }
 
-        future::join_all(filtered.iter().map(|(usergroup_id, user_ids)| {
-            println!(
-                "Updating usergroup_id {}: user_ids: {:?}",
-                usergroup_id, user_ids
-            );
-            self.usergroups_users_update(usergroup_id, user_ids.as_ref())
-        }))
-        .await;
+    async fn sync_usergroup(
+        &self,
+        usergroup_id_map: &HashMap<String, String>,
+        schedule: &Schedule,
+        mode: SyncMode,
+    ) -> UsergroupUpdate {
+        let usergroup_id = match usergroup_id_map.get(&schedule.hero) {
+            Some(usergroup_id) => usergroup_id.clone(),
+            None => {
+                tracing::warn!("no usergroup id for {}", schedule.hero);
+                return UsergroupUpdate {
+                    hero: schedule.hero.clone(),
+                    usergroup_id: None,
+                    user_ids: Vec::new(),
+                    drift: None,
+                    outcome: UsergroupUpdateOutcome::MissingUsergroup,
+                };
+            }
+        };
 
-        Ok(())
+        let mut update = UsergroupUpdate {
+            hero: schedule.hero.clone(),
+            usergroup_id: Some(usergroup_id.clone()),
+            user_ids: Vec::new(),
+            drift: None,
+            outcome: UsergroupUpdateOutcome::Updated,
+        };
+        if let Err(err) = self
+            .sync_usergroup_users(&usergroup_id, schedule, mode, &mut update)
+            .await
+        {
+            update.outcome = UsergroupUpdateOutcome::Failed {
+                error: err.to_string(),
+            };
+        }
+        update
     }
 
-    pub async fn post_message(
+    async fn sync_usergroup_users(
         &self,
-        channel_id: &String,
-        hero: &String,
-        assignees: Vec<String>,
+        usergroup_id: &str,
+        schedule: &Schedule,
+        mode: SyncMode,
+        update: &mut UsergroupUpdate,
     ) -> Result<()> {
-        let url = format!(
-            "https://slack.com/api/chat.postMessage?token={}&channel={}&text={}:%20{}&pretty=1",
-            self.token,
-            channel_id,
-            hero,
-            assignees.join(",%20")
+        update.user_ids = self.look_up_user_ids_by_email(schedule).await?;
+
+        if mode.compares() {
+            let members = self.usergroups_users_list(usergroup_id).await?;
+            let drift = Drift::between(&update.user_ids, &members);
+            let unchanged = drift.is_empty();
+            update.drift = Some(drift);
+            if mode == SyncMode::DryRun {
+                update.outcome = UsergroupUpdateOutcome::DryRun;
+                return Ok(());
+            }
+            if unchanged {
+                update.outcome = UsergroupUpdateOutcome::Unchanged;
+                return Ok(());
+            }
+        }
+
+        tracing::info!(
+            "Updating usergroup_id {}: user_ids: {:?}",
+            usergroup_id,
+            update.user_ids
         );
-        let result: PostMessageResponse = self.client.post(url).send().await?.json().await?;
+        self.usergroups_users_update(usergroup_id, &update.user_ids)
+            .await
+    }
+
+    /// Posts a Block Kit message to a channel. Passing a user id as the channel sends a direct
+    /// message.
+    pub async fn post_message(&self, channel_id: &str, message: &Message) -> Result<()> {
+        let result: OkResponse = self
+            .transport
+            .post_json(
+                Tier::PostMessage,
+                "chat.postMessage",
+                &PostMessageRequest {
+                    channel: channel_id,
+                    message,
+                },
+            )
+            .await?;
         if result.ok {
             Ok(())
         } else {
-            Err(SlackError::PostMessageError)
+            Err(SlackError::PostMessageError(error_code(result.error)))
+        }
+    }
+
+    /// Announces the assignees of a schedule in a channel and asks them to acknowledge it.
+    pub async fn post_handover(&self, channel_id: &str, schedule: &Schedule) -> Result<()> {
+        let user_ids = self.look_up_user_ids_by_email(schedule).await?;
+        self.post_message(channel_id, &handover::handover_message(schedule, &user_ids))
+            .await
+    }
+
+    /// Sends a direct message to the Slack user with the given e-mail address.
+    pub async fn post_direct_message(&self, email: String, message: &Message) -> Result<()> {
+        let user_id = self.user_id_by_email(email).await?;
+        self.post_message(&user_id, message).await
+    }
+
+    /// Replies to an interaction by using its `response_url`.
+    pub async fn respond(&self, response_url: &str, message: &Message) -> Result<()> {
+        let response = self
+            .transport
+            .client()
+            .post(response_url)
+            .json(message)
+            .send()
+            .await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(SlackError::RespondError)
         }
     }
 }
 
-/// Retrieves Slack application token from SSM.
+/// Retrieves the Slack application token of the default workspace from SSM.
 pub async fn get_slack_token() -> Result<String> {
+    get_workspace_slack_token(None).await
+}
+
+/// Retrieves the token of a workspace, stored at `{SLACK_TOKEN_PARAMETER}/{workspace}`. The
+/// token of the default workspace is stored at `SLACK_TOKEN_PARAMETER` itself.
+pub async fn get_workspace_slack_token(workspace: Option<&str>) -> Result<String> {
+    let parameter = env::var("SLACK_TOKEN_PARAMETER")?;
+    let name = match workspace.filter(|workspace| *workspace != DEFAULT_WORKSPACE) {
+        Some(workspace) => format!("{}/{}", parameter, workspace),
+        None => parameter,
+    };
+    get_parameter(
+        name,
+        "Slack token not found as an SSM parameter.",
+        "Slack token parameter value is empty.",
+    )
+    .await
+}
+
+/// Retrieves the signing secret used to verify requests sent by Slack from SSM.
+pub async fn get_slack_signing_secret() -> Result<String> {
+    get_parameter(
+        env::var("SLACK_SIGNING_SECRET_PARAMETER")?,
+        "Slack signing secret not found as an SSM parameter.",
+        "Slack signing secret parameter value is empty.",
+    )
+    .await
+}
+
+async fn get_parameter(
+    name: String,
+    not_found: &'static str,
+    empty: &'static str,
+) -> Result<String> {
     let shared_config = aws_config::load_from_env().await;
     let client = SsmClient::new(&shared_config);
     let response: GetParameterOutput = client
         .get_parameter()
-        .name(env::var("SLACK_TOKEN_PARAMETER")?)
+        .name(name)
+        .with_decryption(true)
         .send()
         .await?;
-    let token = response
+    let value = response
         .parameter
-        .ok_or(SlackError::GetSlackTokenError(
-            "Slack token not found as an SSM parameter.",
-        ))?
+        .ok_or(SlackError::GetSlackTokenError(not_found))?
         .value
-        .ok_or(SlackError::GetSlackTokenError(
-            "Slack token parameter value is empty.",
-        ))?;
+        .ok_or(SlackError::GetSlackTokenError(empty))?;
 
-    Ok(token)
+    Ok(value)
 }
@@ -0,0 +1,285 @@
+//! In-process Slack Web API for tests, enabled by the `mock` feature.
+//!
+//! Implements `auth.test`, `usergroups.list`, `users.lookupByEmail`, `users.info`, `usergroups.users.list`,
+//! `usergroups.users.update`, `usergroups.create` and `chat.postMessage` on top of an in-memory workspace and records every
+//! call it receives.
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::any;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub method: String,
+    /// Query parameters merged with the form or JSON body.
+    pub params: BTreeMap<String, Value>,
+    pub authorization: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockUser {
+    pub id: String,
+    pub email: String,
+}
+
+#[derive(Default)]
+struct Workspace {
+    team_id: String,
+    users: Vec<MockUser>,
+    /// Usergroup handle to id.
+    usergroups: BTreeMap<String, String>,
+    /// Usergroup id to user ids.
+    members: HashMap<String, Vec<String>>,
+    calls: Vec<Call>,
+    /// Statuses returned instead of handling the next calls of a method.
+    failures: HashMap<String, VecDeque<StatusCode>>,
+}
+
+type SharedWorkspace = Arc<Mutex<Workspace>>;
+
+pub struct MockSlack {
+    base_url: String,
+    workspace: SharedWorkspace,
+    server: JoinHandle<()>,
+}
+
+impl MockSlack {
+    /// Starts the server on a random local port.
+    pub async fn start() -> MockSlack {
+        let workspace = SharedWorkspace::default();
+        let app = Router::new()
+            .route("/api/:method", any(handle))
+            .with_state(workspace.clone());
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("could not bind mock Slack server");
+        let address = listener.local_addr().expect("no local address");
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .await
+                .expect("mock Slack server failed");
+        });
+        MockSlack {
+            base_url: format!("http://{}/api/", address),
+            workspace,
+            server,
+        }
+    }
+
+    /// Base URL to pass to [`crate::Client::with_base_url`].
+    pub fn base_url(&self) -> String {
+        self.base_url.clone()
+    }
+
+    pub fn set_team_id(&self, team_id: &str) {
+        self.workspace().team_id = team_id.to_string();
+    }
+
+    pub fn add_user(&self, id: &str, email: &str) {
+        self.workspace().users.push(MockUser {
+            id: id.to_string(),
+            email: email.to_string(),
+        });
+    }
+
+    pub fn add_usergroup(&self, id: &str, handle: &str) {
+        self.workspace()
+            .usergroups
+            .insert(handle.to_string(), id.to_string());
+    }
+
+    /// Lets the next call of `method` fail with `status`. Rate limited calls are answered with a
+    /// `Retry-After` of zero seconds.
+    pub fn fail_next(&self, method: &str, status: StatusCode) {
+        self.workspace()
+            .failures
+            .entry(method.to_string())
+            .or_default()
+            .push_back(status);
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.workspace().calls.clone()
+    }
+
+    pub fn calls_to(&self, method: &str) -> Vec<Call> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.method == method)
+            .collect()
+    }
+
+    pub fn usergroup_members(&self, usergroup_id: &str) -> Vec<String> {
+        self.workspace()
+            .members
+            .get(usergroup_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Sets the members of a usergroup as if they were edited in Slack.
+    pub fn set_usergroup_members(&self, usergroup_id: &str, user_ids: &[&str]) {
+        self.workspace().members.insert(
+            usergroup_id.to_string(),
+            user_ids.iter().map(|id| id.to_string()).collect(),
+        );
+    }
+
+    pub fn usergroup_id(&self, handle: &str) -> Option<String> {
+        self.workspace().usergroups.get(handle).cloned()
+    }
+
+    fn workspace(&self) -> std::sync::MutexGuard<Workspace> {
+        self.workspace
+            .lock()
+            .expect("mock Slack workspace poisoned")
+    }
+}
+
+impl Drop for MockSlack {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn handle(
+    State(workspace): State<SharedWorkspace>,
+    Path(method): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let mut params: BTreeMap<String, Value> = query
+        .into_iter()
+        .map(|(key, value)| (key, Value::String(value)))
+        .collect();
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if content_type.starts_with("application/json") {
+        if let Ok(Value::Object(object)) = serde_json::from_slice::<Value>(&body) {
+            params.extend(object);
+        }
+    } else if let Ok(form) = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&body) {
+        params.extend(
+            form.into_iter()
+                .map(|(key, value)| (key, Value::String(value))),
+        );
+    }
+    let authorization = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let mut workspace = workspace.lock().expect("mock Slack workspace poisoned");
+    workspace.calls.push(Call {
+        method: method.clone(),
+        params: params.clone(),
+        authorization: authorization.clone(),
+    });
+
+    if let Some(status) = workspace
+        .failures
+        .get_mut(&method)
+        .and_then(|failures| failures.pop_front())
+    {
+        return (status, [(RETRY_AFTER, "0")], "").into_response();
+    }
+
+    if !authorization
+        .map(|value| value.starts_with("Bearer "))
+        .unwrap_or(false)
+    {
+        return Json(json!({"ok": false, "error": "not_authed"})).into_response();
+    }
+
+    let param = |name: &str| -> String {
+        match params.get(name) {
+            Some(Value::String(value)) => value.clone(),
+            _ => String::new(),
+        }
+    };
+
+    let response = match method.as_str() {
+        "auth.test" => json!({"ok": true, "team_id": workspace.team_id}),
+        "usergroups.list" => {
+            let usergroups: Vec<Value> = workspace
+                .usergroups
+                .iter()
+                .map(|(handle, id)| json!({"id": id, "handle": handle}))
+                .collect();
+            json!({"ok": true, "usergroups": usergroups})
+        }
+        "users.lookupByEmail" => {
+            let email = param("email");
+            match workspace.users.iter().find(|user| user.email == email) {
+                Some(user) => json!({"ok": true, "user": user_json(user)}),
+                None => json!({"ok": false, "error": "users_not_found"}),
+            }
+        }
+        "users.info" => {
+            let id = param("user");
+            match workspace.users.iter().find(|user| user.id == id) {
+                Some(user) => json!({"ok": true, "user": user_json(user)}),
+                None => json!({"ok": false, "error": "user_not_found"}),
+            }
+        }
+        "usergroups.users.list" => {
+            let usergroup = param("usergroup");
+            if workspace.usergroups.values().any(|id| *id == usergroup) {
+                let users = workspace
+                    .members
+                    .get(&usergroup)
+                    .cloned()
+                    .unwrap_or_default();
+                json!({"ok": true, "users": users})
+            } else {
+                json!({"ok": false, "error": "no_such_subteam"})
+            }
+        }
+        "usergroups.users.update" => {
+            let usergroup = param("usergroup");
+            if workspace.usergroups.values().any(|id| *id == usergroup) {
+                let users: Vec<String> = param("users")
+                    .split(',')
+                    .filter(|user| !user.is_empty())
+                    .map(String::from)
+                    .collect();
+                workspace.members.insert(usergroup, users);
+                json!({"ok": true})
+            } else {
+                json!({"ok": false, "error": "no_such_subteam"})
+            }
+        }
+        "usergroups.create" => {
+            let name = param("name");
+            let id = format!("S{:04}", workspace.usergroups.len() + 1);
+            workspace.usergroups.insert(name, id.clone());
+            json!({"ok": true, "usergroup": {"id": id}})
+        }
+        "chat.postMessage" => {
+            if param("channel").is_empty() {
+                json!({"ok": false, "error": "channel_not_found"})
+            } else {
+                json!({"ok": true, "channel": param("channel"), "ts": "1.000001"})
+            }
+        }
+        _ => json!({"ok": false, "error": "unknown_method"}),
+    };
+    Json(response).into_response()
+}
+
+fn user_json(user: &MockUser) -> Value {
+    json!({"id": user.id, "profile": {"email": user.email}})
+}
@@ -0,0 +1,63 @@
+use crate::{Result, SlackError};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Requests older than this are rejected to prevent replay attacks.
+const MAX_REQUEST_AGE_SECS: u64 = 60 * 5;
+
+/// Verifies the `X-Slack-Signature` of an incoming request as described in
+/// https://api.slack.com/authentication/verifying-requests-from-slack.
+pub fn verify_signature(
+    signing_secret: &str,
+    timestamp: &str,
+    body: &str,
+    signature: &str,
+    now: u64,
+) -> Result<()> {
+    let request_time: u64 = timestamp
+        .parse()
+        .map_err(|_| SlackError::InvalidSignatureError("timestamp is not a number"))?;
+    if now.abs_diff(request_time) > MAX_REQUEST_AGE_SECS {
+        return Err(SlackError::InvalidSignatureError("request is too old"));
+    }
+
+    let expected = signature
+        .strip_prefix("v0=")
+        .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        .ok_or(SlackError::InvalidSignatureError("malformed signature"))?;
+
+    let mut mac = HmacSha256::new_from_slice(signing_secret.as_bytes())
+        .map_err(|_| SlackError::InvalidSignatureError("invalid signing secret"))?;
+    mac.update(format!("v0:{}:{}", timestamp, body).as_bytes());
+    mac.verify_slice(&expected)
+        .map_err(|_| SlackError::InvalidSignatureError("signature mismatch"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::signature::verify_signature;
+
+    // Example taken from the Slack documentation.
+    const SECRET: &str = "8f742231b10e8888abcd99yyyzzz85a5";
+    const TIMESTAMP: &str = "1531420618";
+    const BODY: &str = "token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+    const SIGNATURE: &str = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+
+    #[test]
+    fn valid_signature() {
+        assert!(verify_signature(SECRET, TIMESTAMP, BODY, SIGNATURE, 1531420618).is_ok());
+    }
+
+    #[test]
+    fn tampered_body() {
+        let body = BODY.replace("roadrunner", "coyote");
+        assert!(verify_signature(SECRET, TIMESTAMP, &body, SIGNATURE, 1531420618).is_err());
+    }
+
+    #[test]
+    fn stale_request() {
+        assert!(verify_signature(SECRET, TIMESTAMP, BODY, SIGNATURE, 1531420618 + 301).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{Result, SlackError};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Instant};
+
+/// Upper bound of requests the client runs at the same time.
+pub const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Used unless `SLACK_API_BASE_URL` is set.
+pub const DEFAULT_BASE_URL: &str = "https://slack.com/api/";
+const MAX_RETRIES: u32 = 3;
+/// Query parameters whose values never end up in the logs.
+const SENSITIVE_PARAMETERS: [&str; 4] = ["token", "client_secret", "code", "email"];
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+/// Requests sent back to back before the rate of the tier applies.
+const MAX_BURST: f64 = 3.0;
+
+/// Slack rate limit tiers, see https://api.slack.com/apis/rate-limits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tier {
+    Tier2,
+    Tier3,
+    Tier4,
+    /// `chat.postMessage` allows roughly one message per second and channel.
+    PostMessage,
+}
+
+impl Tier {
+    const ALL: [Tier; 4] = [Tier::Tier2, Tier::Tier3, Tier::Tier4, Tier::PostMessage];
+
+    fn requests_per_minute(self) -> f64 {
+        match self {
+            Tier::Tier2 => 20.0,
+            Tier::Tier3 => 50.0,
+            Tier::Tier4 => 100.0,
+            Tier::PostMessage => 60.0,
+        }
+    }
+}
+
+/// Cursor based pagination metadata, see https://api.slack.com/docs/pagination.
+#[derive(Deserialize, Debug, Default)]
+pub struct ResponseMetadata {
+    #[serde(default)]
+    pub next_cursor: String,
+}
+
+impl ResponseMetadata {
+    /// The cursor of the next page, if there is one.
+    pub fn next_cursor(&self) -> Option<String> {
+        if self.next_cursor.is_empty() {
+            None
+        } else {
+            Some(self.next_cursor.clone())
+        }
+    }
+}
+
+/// Token bucket allowing a burst of `MAX_BURST` requests.
+struct RateLimiter {
+    per_second: f64,
+    capacity: f64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(tier: Tier) -> RateLimiter {
+        RateLimiter {
+            per_second: tier.requests_per_minute() / 60.0,
+            capacity: MAX_BURST,
+            bucket: Mutex::new((MAX_BURST, Instant::now())),
+        }
+    }
+
+    async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().await;
+            let now = Instant::now();
+            let (tokens, last) = *bucket;
+            let refilled =
+                (tokens + (now - last).as_secs_f64() * self.per_second).min(self.capacity);
+            // Taking a token below zero reserves the slot for this request.
+            *bucket = (refilled - 1.0, now);
+            if refilled >= 1.0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64((1.0 - refilled) / self.per_second)
+            }
+        };
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+}
+
+/// Sends requests to the Slack API honoring the rate limit tiers and retrying rate limited,
+/// failed and timed out requests with exponential backoff. The token is sent as a bearer token,
+/// never as part of the URL.
+pub struct Transport {
+    client: reqwest::Client,
+    base_url: String,
+    token: String,
+    limiters: HashMap<Tier, RateLimiter>,
+}
+
+impl Transport {
+    /// Creates a transport for the Web API at `base_url`, e.g. `https://slack.com/api/`.
+    pub fn new(token: String, base_url: String) -> Transport {
+        let base_url = if base_url.ends_with('/') {
+            base_url
+        } else {
+            format!("{}/", base_url)
+        };
+        Transport {
+            client: reqwest::Client::new(),
+            base_url,
+            token,
+            limiters: Tier::ALL
+                .iter()
+                .map(|tier| (*tier, RateLimiter::new(*tier)))
+                .collect(),
+        }
+    }
+
+    /// The underlying HTTP client for requests outside of the Slack Web API, e.g. to a
+    /// `response_url`.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Calls a read method of the Web API with URL encoded query parameters.
+    pub async fn get<T>(&self, tier: Tier, method: &str, query: &[(&str, &str)]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.send(tier, Method::GET, method, |request| request.query(query))
+            .await
+    }
+
+    /// Calls a write method of the Web API with a form encoded body.
+    pub async fn post_form<T>(&self, tier: Tier, method: &str, form: &[(&str, &str)]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.send(tier, Method::POST, method, |request| request.form(form))
+            .await
+    }
+
+    /// Calls a write method of the Web API with a JSON body.
+    pub async fn post_json<T, B>(&self, tier: Tier, method: &str, body: &B) -> Result<T>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        self.send(tier, Method::POST, method, |request| request.json(body))
+            .await
+    }
+
+    /// Sends the request and deserializes the JSON response. The request is built again for
+    /// every retry.
+    async fn send<T, F>(&self, tier: Tier, http_method: Method, method: &str, build: F) -> Result<T>
+    where
+        T: DeserializeOwned,
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let url = format!("{}{}", self.base_url, method);
+        let mut attempt = 0;
+        loop {
+            self.limiters[&tier].acquire().await;
+            let request = build(
+                self.client
+                    .request(http_method.clone(), &url)
+                    .bearer_auth(&self.token),
+            )
+            .build()?;
+            let logged_url = redact(request.url());
+            let delay = match self.client.execute(request).await {
+                Ok(response) => {
+                    tracing::info!(
+                        method = %http_method,
+                        url = %logged_url,
+                        status = response.status().as_u16(),
+                        attempt,
+                        "Slack API request"
+                    );
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        retry_after(&response).unwrap_or_else(|| backoff(attempt))
+                    } else if response.status().is_server_error() {
+                        backoff(attempt)
+                    } else {
+                        return Ok(response.error_for_status()?.json().await?);
+                    }
+                }
+                Err(err) if err.is_timeout() || err.is_connect() => {
+                    tracing::warn!(
+                        method = %http_method,
+                        url = %logged_url,
+                        attempt,
+                        "Slack API request failed: {}",
+                        err.without_url()
+                    );
+                    backoff(attempt)
+                }
+                Err(err) => return Err(err.without_url().into()),
+            };
+            if attempt >= MAX_RETRIES {
+                return Err(SlackError::RetriesExhaustedError(attempt));
+            }
+            attempt += 1;
+            tracing::info!(
+                "Retrying Slack request in {:?} (attempt {})",
+                delay,
+                attempt
+            );
+            sleep(delay).await;
+        }
+    }
+}
+
+/// Renders the URL for logging with the values of sensitive query parameters replaced.
+pub fn redact(url: &Url) -> String {
+    let mut redacted = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(key, value)| {
+                if SENSITIVE_PARAMETERS.contains(&key.as_ref()) {
+                    (key.into_owned(), "REDACTED".to_string())
+                } else {
+                    (key.into_owned(), value.into_owned())
+                }
+            })
+            .collect();
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    redacted.to_string()
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF * 2u32.pow(attempt)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::{backoff, redact, RateLimiter, Tier, MAX_BURST};
+    use reqwest::Url;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    #[test]
+    fn redacts_token_and_email() {
+        let url =
+            Url::parse("https://slack.com/api/users.lookupByEmail?token=xoxb-1&email=a%40b.de")
+                .unwrap();
+        assert_eq!(
+            redact(&url),
+            "https://slack.com/api/users.lookupByEmail?token=REDACTED&email=REDACTED"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_bursts() {
+        let limiter = RateLimiter::new(Tier::Tier2);
+        let start = Instant::now();
+        for _ in 0..MAX_BURST as usize {
+            limiter.acquire().await;
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // Tier 2 allows 20 requests per minute, one every 3 seconds.
+        limiter.acquire().await;
+        assert_eq!(start.elapsed().as_secs(), 3);
+        limiter.acquire().await;
+        assert_eq!(start.elapsed().as_secs(), 6);
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+    }
+}
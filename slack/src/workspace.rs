@@ -0,0 +1,82 @@
+use crate::cache::UserIdCache;
+use crate::{get_workspace_slack_token, Client, Result, SlackError};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Name of the workspace of heroes that do not reference one.
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Resolves the workspace a hero references, see `Hero::workspace`.
+pub fn workspace_name(workspace: Option<&str>) -> &str {
+    workspace.unwrap_or(DEFAULT_WORKSPACE)
+}
+
+/// Creates one client per Slack workspace on first use, each with the workspace's own token.
+#[derive(Default)]
+pub struct Workspaces {
+    user_id_cache: Option<Arc<dyn UserIdCache>>,
+    clients: Mutex<HashMap<String, Arc<Client>>>,
+}
+
+impl Workspaces {
+    pub fn new() -> Workspaces {
+        Workspaces::default()
+    }
+
+    /// Passes `cache` on to every client, see `Client::with_user_id_cache`.
+    pub fn with_user_id_cache(mut self, cache: Arc<dyn UserIdCache>) -> Workspaces {
+        self.user_id_cache = Some(cache);
+        self
+    }
+
+    /// Returns the client of a workspace, `None` being the default workspace.
+    pub async fn client(&self, workspace: Option<&str>) -> Result<Arc<Client>> {
+        let name = workspace_name(workspace);
+        let mut clients = self.clients.lock().await;
+        if let Some(client) = clients.get(name) {
+            return Ok(client.clone());
+        }
+
+        let token = get_workspace_slack_token(workspace).await?;
+        let mut client = Client::new(token).in_workspace(name);
+        if let Some(cache) = &self.user_id_cache {
+            client = client.with_user_id_cache(cache.clone());
+        }
+        let client = Arc::new(client);
+        clients.insert(name.to_string(), client.clone());
+        Ok(client)
+    }
+
+    /// Returns the client of the workspace with the Slack team id `team_id`, e.g. of a slash
+    /// command. Only the default workspace and `workspaces` are asked for their team id.
+    pub async fn for_team(
+        &self,
+        team_id: &str,
+        workspaces: &[Option<String>],
+    ) -> Result<Arc<Client>> {
+        let mut names = vec![None];
+        names.extend(workspaces.iter().map(|workspace| workspace.as_deref()));
+        for name in names {
+            let client = match self.client(name).await {
+                Ok(client) => client,
+                Err(err) => {
+                    tracing::warn!(
+                        "Could not get the client of {}: {}",
+                        workspace_name(name),
+                        err
+                    );
+                    continue;
+                }
+            };
+            match client.team_id().await {
+                Ok(id) if id == team_id => return Ok(client),
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::warn!("Could not identify {}: {}", workspace_name(name), err);
+                }
+            }
+        }
+        Err(SlackError::UnknownTeamError(team_id.to_string()))
+    }
+}
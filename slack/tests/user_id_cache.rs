@@ -0,0 +1,150 @@
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use slack::cache::UserIdCache;
+use slack::mock::MockSlack;
+use slack::workspace::DEFAULT_WORKSPACE;
+use slack::{Client, SlackError};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+/// Keeps the user id together with whether it is still fresh, like the TTL of the real cache.
+#[derive(Default)]
+struct InMemoryCache(Mutex<HashMap<(String, String), (String, bool)>>);
+
+fn key(workspace: &str, email: &str) -> (String, String) {
+    (workspace.to_string(), email.to_string())
+}
+
+impl InMemoryCache {
+    fn insert_stale(&self, email: &str, user_id: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key(DEFAULT_WORKSPACE, email), (user_id.to_string(), false));
+    }
+
+    fn user_id(&self, email: &str) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&key(DEFAULT_WORKSPACE, email))
+            .map(|(user_id, _)| user_id.clone())
+    }
+}
+
+impl UserIdCache for InMemoryCache {
+    fn get<'a>(&'a self, workspace: &'a str, email: &'a str) -> BoxFuture<'a, Option<String>> {
+        Box::pin(async move {
+            match self.0.lock().unwrap().get(&key(workspace, email)) {
+                Some((user_id, true)) => Some(user_id.clone()),
+                _ => None,
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        workspace: &'a str,
+        email: &'a str,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(key(workspace, email), (user_id.to_string(), true));
+        })
+    }
+
+    fn remove<'a>(&'a self, workspace: &'a str, email: &'a str) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            self.0.lock().unwrap().remove(&key(workspace, email));
+        })
+    }
+}
+
+#[tokio::test]
+async fn looks_up_each_email_only_once() {
+    let slack = MockSlack::start().await;
+    slack.add_user("U1", "a@b.de");
+    let cache = Arc::new(InMemoryCache::default());
+    let client = Client::with_base_url("xoxb-test".to_string(), slack.base_url())
+        .with_user_id_cache(cache.clone());
+
+    assert_eq!(
+        client.user_id_by_email("a@b.de".into()).await.unwrap(),
+        "U1"
+    );
+    assert_eq!(
+        client.user_id_by_email("a@b.de".into()).await.unwrap(),
+        "U1"
+    );
+
+    assert_eq!(slack.calls_to("users.lookupByEmail").len(), 1);
+    assert_eq!(cache.user_id("a@b.de").as_deref(), Some("U1"));
+}
+
+#[tokio::test]
+async fn refreshes_stale_user_ids() {
+    let slack = MockSlack::start().await;
+    slack.add_user("U2", "a@b.de");
+    let cache = Arc::new(InMemoryCache::default());
+    cache.insert_stale("a@b.de", "U1");
+    let client = Client::with_base_url("xoxb-test".to_string(), slack.base_url())
+        .with_user_id_cache(cache.clone());
+
+    assert_eq!(
+        client.user_id_by_email("a@b.de".into()).await.unwrap(),
+        "U2"
+    );
+    assert_eq!(cache.user_id("a@b.de").as_deref(), Some("U2"));
+}
+
+#[tokio::test]
+async fn forgets_emails_no_longer_in_slack() {
+    let slack = MockSlack::start().await;
+    let cache = Arc::new(InMemoryCache::default());
+    cache.insert_stale("old@b.de", "U1");
+    let client = Client::with_base_url("xoxb-test".to_string(), slack.base_url())
+        .with_user_id_cache(cache.clone());
+
+    assert!(matches!(
+        client.user_id_by_email("old@b.de".into()).await,
+        Err(SlackError::UserNotFoundError(_))
+    ));
+    assert_eq!(cache.user_id("old@b.de"), None);
+}
+
+#[tokio::test]
+async fn keeps_user_ids_apart_per_workspace() {
+    let slack = MockSlack::start().await;
+    slack.add_user("U7", "a@b.de");
+    let cache = Arc::new(InMemoryCache::default());
+    cache.put(DEFAULT_WORKSPACE, "a@b.de", "U1").await;
+    let client = Client::with_base_url("xoxb-test".to_string(), slack.base_url())
+        .in_workspace("subsidiary")
+        .with_user_id_cache(cache.clone());
+
+    assert_eq!(
+        client.user_id_by_email("a@b.de".into()).await.unwrap(),
+        "U7"
+    );
+    assert_eq!(cache.user_id("a@b.de").as_deref(), Some("U1"));
+}
+
+#[tokio::test]
+async fn keeps_the_cause_of_failed_lookups() {
+    let slack = MockSlack::start().await;
+    slack.add_user("U1", "a@b.de");
+    slack.fail_next("users.lookupByEmail", StatusCode::BAD_REQUEST);
+    let client = Client::with_base_url("xoxb-test".to_string(), slack.base_url());
+
+    let err = client.user_id_by_email("a@b.de".into()).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        SlackError::UsersLookupByEmailRequestError { ref email, .. } if email == "a@b.de"
+    ));
+    assert!(err.source().is_some());
+}
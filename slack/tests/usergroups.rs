@@ -0,0 +1,186 @@
+use model::schedule::Schedule;
+use model::sync::{SyncMode, UsergroupUpdateOutcome};
+use reqwest::StatusCode;
+use slack::mock::MockSlack;
+use slack::Client;
+
+fn schedule(hero: &str, assignees: Vec<&str>) -> Schedule {
+    Schedule {
+        hero: hero.to_string(),
+        shift_start_time: 1671404400,
+        assignees: assignees.into_iter().map(String::from).collect(),
+        ..Default::default()
+    }
+}
+
+async fn workspace() -> (MockSlack, Client) {
+    let slack = MockSlack::start().await;
+    slack.add_user("U1", "a@b.de");
+    slack.add_user("U2", "c@d.de");
+    slack.add_usergroup("S1", "oncall");
+    slack.add_usergroup("S2", "release");
+    let client = Client::with_base_url("xoxb-test".to_string(), slack.base_url());
+    (slack, client)
+}
+
+#[tokio::test]
+async fn updates_usergroups_with_assignees() {
+    let (slack, client) = workspace().await;
+
+    let updates = client
+        .usergroups_users_update_with_schedules(vec![
+            schedule("oncall", vec!["a@b.de", "c@d.de"]),
+            schedule("release", vec!["c@d.de"]),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(updates.len(), 2);
+    assert!(updates
+        .iter()
+        .all(|update| matches!(update.outcome, UsergroupUpdateOutcome::Updated)));
+    assert_eq!(slack.usergroup_members("S1"), vec!["U1", "U2"]);
+    assert_eq!(slack.usergroup_members("S2"), vec!["U2"]);
+    assert_eq!(slack.calls_to("usergroups.list").len(), 1);
+    assert_eq!(slack.calls_to("users.lookupByEmail").len(), 3);
+}
+
+#[tokio::test]
+async fn sends_token_as_bearer_header() {
+    let (slack, client) = workspace().await;
+
+    client
+        .usergroups_users_update_with_schedules(vec![schedule("oncall", vec!["a@b.de"])])
+        .await
+        .unwrap();
+
+    for call in slack.calls() {
+        assert_eq!(call.authorization.as_deref(), Some("Bearer xoxb-test"));
+        assert!(!call.params.contains_key("token"));
+    }
+}
+
+#[tokio::test]
+async fn reports_missing_usergroup() {
+    let (slack, client) = workspace().await;
+
+    let updates = client
+        .usergroups_users_update_with_schedules(vec![schedule("support", vec!["a@b.de"])])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        updates[0].outcome,
+        UsergroupUpdateOutcome::MissingUsergroup
+    ));
+    assert!(slack.calls_to("usergroups.users.update").is_empty());
+}
+
+#[tokio::test]
+async fn reports_unknown_assignee_without_failing_other_usergroups() {
+    let (slack, client) = workspace().await;
+
+    let mut updates = client
+        .usergroups_users_update_with_schedules(vec![
+            schedule("oncall", vec!["unknown@b.de"]),
+            schedule("release", vec!["a@b.de"]),
+        ])
+        .await
+        .unwrap();
+    updates.sort_by(|a, b| a.hero.cmp(&b.hero));
+
+    assert!(matches!(
+        updates[0].outcome,
+        UsergroupUpdateOutcome::Failed { .. }
+    ));
+    assert!(matches!(
+        updates[1].outcome,
+        UsergroupUpdateOutcome::Updated
+    ));
+    assert!(slack.usergroup_members("S1").is_empty());
+    assert_eq!(slack.usergroup_members("S2"), vec!["U1"]);
+}
+
+#[tokio::test]
+async fn retries_rate_limited_and_failed_requests() {
+    let (slack, client) = workspace().await;
+    slack.fail_next("usergroups.list", StatusCode::TOO_MANY_REQUESTS);
+    slack.fail_next("usergroups.users.update", StatusCode::INTERNAL_SERVER_ERROR);
+
+    let updates = client
+        .usergroups_users_update_with_schedules(vec![schedule("oncall", vec!["a@b.de"])])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        updates[0].outcome,
+        UsergroupUpdateOutcome::Updated
+    ));
+    assert_eq!(slack.calls_to("usergroups.list").len(), 2);
+    assert_eq!(slack.calls_to("usergroups.users.update").len(), 2);
+    assert_eq!(slack.usergroup_members("S1"), vec!["U1"]);
+}
+
+#[tokio::test]
+async fn dry_run_reports_drift_without_updating() {
+    let (slack, client) = workspace().await;
+    slack.set_usergroup_members("S1", &["U2"]);
+
+    let updates = client
+        .sync_usergroups(vec![schedule("oncall", vec!["a@b.de"])], SyncMode::DryRun)
+        .await
+        .unwrap();
+
+    assert!(matches!(updates[0].outcome, UsergroupUpdateOutcome::DryRun));
+    let drift = updates[0].drift.as_ref().unwrap();
+    assert_eq!(drift.missing, vec!["U1"]);
+    assert_eq!(drift.unexpected, vec!["U2"]);
+    assert_eq!(slack.usergroup_members("S1"), vec!["U2"]);
+    assert!(slack.calls_to("usergroups.users.update").is_empty());
+}
+
+#[tokio::test]
+async fn skips_usergroups_without_drift() {
+    let (slack, client) = workspace().await;
+    slack.set_usergroup_members("S1", &["U2", "U1"]);
+    slack.set_usergroup_members("S2", &["U1"]);
+
+    let updates = client
+        .sync_usergroups(
+            vec![
+                schedule("oncall", vec!["a@b.de", "c@d.de"]),
+                schedule("release", vec!["c@d.de"]),
+            ],
+            SyncMode::SkipUnchanged,
+        )
+        .await
+        .unwrap();
+
+    let outcome = |hero: &str| {
+        &updates
+            .iter()
+            .find(|update| update.hero == hero)
+            .unwrap()
+            .outcome
+    };
+    assert!(matches!(
+        outcome("oncall"),
+        UsergroupUpdateOutcome::Unchanged
+    ));
+    assert!(matches!(
+        outcome("release"),
+        UsergroupUpdateOutcome::Updated
+    ));
+    assert_eq!(slack.usergroup_members("S2"), vec!["U2"]);
+    assert_eq!(slack.calls_to("usergroups.users.update").len(), 1);
+}
+
+#[tokio::test]
+async fn asks_for_the_team_id_once() {
+    let (slack, client) = workspace().await;
+    slack.set_team_id("T1");
+
+    assert_eq!(client.team_id().await.unwrap(), "T1");
+    assert_eq!(client.team_id().await.unwrap(), "T1");
+    assert_eq!(slack.calls_to("auth.test").len(), 1);
+}
@@ -0,0 +1,145 @@
+//! Outbound webhooks. Events are delivered to the subscriptions of a hero as signed JSON, failed
+//! deliveries are retried with exponential backoff by `retry_due`.
+
+use aws_config::SdkConfig;
+use futures::future;
+use model::time::secs_now;
+use model::webhook::{Delivery, Event, EventType, Subscription};
+use rand::RngCore;
+use repository::webhook::{WebhookDeliveryRepository, WebhookRepository};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub mod sender;
+
+pub use sender::Sender;
+
+type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// How long deliveries stay in the delivery log.
+pub const DELIVERY_RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Generates the secret a new subscription's payloads are signed with.
+pub fn new_secret() -> String {
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    hex::encode(secret)
+}
+
+pub struct Dispatcher {
+    subscriptions: WebhookRepository,
+    deliveries: WebhookDeliveryRepository,
+    sender: Sender,
+}
+
+impl Dispatcher {
+    pub fn new(shared_config: &SdkConfig) -> Dispatcher {
+        Dispatcher {
+            subscriptions: WebhookRepository::new(shared_config),
+            deliveries: WebhookDeliveryRepository::new(shared_config),
+            sender: Sender::new(),
+        }
+    }
+
+    /// Delivers an event to every subscription of the hero that asked for it. Failures are
+    /// logged and retried later, they never fail the caller.
+    pub async fn emit(&self, hero: &str, event_type: EventType, data: Value) {
+        let event = Event {
+            id: Uuid::new_v4().to_string(),
+            event_type,
+            hero: hero.to_string(),
+            occurred_at: secs_now(),
+            data,
+        };
+        let subscriptions = match self.subscriptions.list(hero.to_string()).await {
+            Ok(subscriptions) => subscriptions,
+            Err(err) => {
+                eprintln!(
+                    "Could not list the webhooks of {} for {}: {:?}",
+                    hero,
+                    event_type.as_str(),
+                    err
+                );
+                return;
+            }
+        };
+        let payload = match serde_json::to_string(&event) {
+            Ok(payload) => payload,
+            Err(err) => {
+                eprintln!("Could not serialize event {:?}: {:?}", event, err);
+                return;
+            }
+        };
+
+        future::join_all(
+            subscriptions
+                .iter()
+                .filter(|subscription| subscription.is_subscribed(event_type))
+                .map(|subscription| {
+                    let delivery = Delivery::new(subscription, &event, payload.clone());
+                    self.attempt(subscription, delivery)
+                }),
+        )
+        .await;
+    }
+
+    /// Attempts every pending delivery that is due again and returns how many there were.
+    pub async fn retry_due(&self) -> Result<usize, Error> {
+        let due = self.deliveries.list_due(secs_now()).await?;
+        let count = due.len();
+        for delivery in due {
+            match self
+                .subscriptions
+                .get(delivery.hero.clone(), delivery.subscription_id.clone())
+                .await?
+            {
+                Some(subscription) => self.attempt(&subscription, delivery).await,
+                None => self.abandon(delivery).await,
+            }
+        }
+        Ok(count)
+    }
+
+    /// Fails a delivery of a deleted subscription, so it is not due again.
+    async fn abandon(&self, mut delivery: Delivery) {
+        println!(
+            "Dropping delivery {} of deleted webhook {}",
+            delivery.delivery_id, delivery.subscription_id
+        );
+        delivery.abandon("The webhook was deleted".to_string());
+        if let Err(err) = self
+            .deliveries
+            .put(&delivery, DELIVERY_RETENTION_SECS)
+            .await
+        {
+            eprintln!(
+                "Could not log delivery {} of webhook {}: {:?}",
+                delivery.delivery_id, delivery.subscription_id, err
+            );
+        }
+    }
+
+    async fn attempt(&self, subscription: &Subscription, mut delivery: Delivery) {
+        let (status_code, error) = self.sender.send(subscription, &delivery).await;
+        delivery.record_attempt(status_code, error, secs_now());
+        println!(
+            "Webhook {} of {}: {} attempt {} {:?} {:?}",
+            subscription.id,
+            subscription.hero,
+            delivery.event_type.as_str(),
+            delivery.attempts,
+            delivery.status,
+            delivery.last_status_code
+        );
+        if let Err(err) = self
+            .deliveries
+            .put(&delivery, DELIVERY_RETENTION_SECS)
+            .await
+        {
+            eprintln!(
+                "Could not log delivery {} of webhook {}: {:?}",
+                delivery.delivery_id, subscription.id, err
+            );
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use hmac::{Hmac, Mac};
+use model::time::secs_now;
+use model::webhook::{Delivery, Subscription};
+use reqwest::header::CONTENT_TYPE;
+use sha2::Sha256;
+use std::time::Duration;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Hero-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Hero-Timestamp";
+pub const EVENT_HEADER: &str = "X-Hero-Event";
+pub const DELIVERY_HEADER: &str = "X-Hero-Delivery";
+
+/// Kept short as the first attempt is made while handling the request that caused the event.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Signs `<timestamp>.<body>` with the secret of the subscription. Receivers compare it with
+/// the `X-Hero-Signature` header, formatted as `v1=<hex encoded HMAC-SHA256>`.
+pub fn sign(secret: &str, timestamp: u64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("v1={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+pub struct Sender {
+    http: reqwest::Client,
+}
+
+impl Default for Sender {
+    fn default() -> Self {
+        Sender::new()
+    }
+}
+
+impl Sender {
+    pub fn new() -> Sender {
+        Sender {
+            http: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .build()
+                .expect("could not build the webhook HTTP client"),
+        }
+    }
+
+    /// Posts the payload of a delivery and returns the status code of the answer, or the error
+    /// if there was none.
+    pub async fn send(
+        &self,
+        subscription: &Subscription,
+        delivery: &Delivery,
+    ) -> (Option<u16>, Option<String>) {
+        let timestamp = secs_now();
+        let response = self
+            .http
+            .post(&subscription.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                sign(&subscription.secret, timestamp, &delivery.payload),
+            )
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(EVENT_HEADER, delivery.event_type.as_str())
+            .header(DELIVERY_HEADER, &delivery.delivery_id)
+            .body(delivery.payload.clone())
+            .send()
+            .await;
+        match response {
+            Ok(response) => (Some(response.status().as_u16()), None),
+            Err(err) => (None, Some(err.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        assert_eq!(
+            sign("secret", 1700000000, "{}"),
+            sign("secret", 1700000000, "{}")
+        );
+        assert_ne!(
+            sign("secret", 1700000000, "{}"),
+            sign("secret", 1700000001, "{}")
+        );
+        assert_ne!(
+            sign("secret", 1700000000, "{}"),
+            sign("other", 1700000000, "{}")
+        );
+        assert!(sign("secret", 1700000000, "{}").starts_with("v1="));
+    }
+}
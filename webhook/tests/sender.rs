@@ -0,0 +1,111 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use model::webhook::{Delivery, Event, EventType, Subscription};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use webhook::sender::{sign, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use webhook::Sender;
+
+const SECRET: &str = "secret";
+
+/// Verifies the signature like a receiver would and answers with the configured status.
+#[derive(Default)]
+struct Receiver {
+    status: u16,
+    received: Vec<(HeaderMap, String)>,
+}
+
+type SharedReceiver = Arc<Mutex<Receiver>>;
+
+async fn start(receiver: SharedReceiver) -> String {
+    let app = Router::new()
+        .route("/hook", post(hook))
+        .with_state(receiver);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/hook", address)
+}
+
+async fn hook(
+    State(receiver): State<SharedReceiver>,
+    headers: HeaderMap,
+    body: String,
+) -> StatusCode {
+    let timestamp: u64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+    if headers[SIGNATURE_HEADER].to_str().unwrap() != sign(SECRET, timestamp, &body) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    let mut receiver = receiver.lock().unwrap();
+    receiver.received.push((headers, body));
+    StatusCode::from_u16(receiver.status).unwrap()
+}
+
+fn subscription(url: String, secret: &str) -> Subscription {
+    Subscription {
+        hero: "oncall".to_string(),
+        id: "W1".to_string(),
+        url,
+        secret: secret.to_string(),
+        events: vec![EventType::ShiftStarted],
+        created_at: 0,
+    }
+}
+
+fn delivery(subscription: &Subscription) -> Delivery {
+    let event = Event {
+        id: "E1".to_string(),
+        event_type: EventType::ShiftStarted,
+        hero: "oncall".to_string(),
+        occurred_at: 1700000000,
+        data: json!({ "assignees": ["a@b.de"] }),
+    };
+    Delivery::new(subscription, &event, serde_json::to_string(&event).unwrap())
+}
+
+#[tokio::test]
+async fn sends_signed_payload() {
+    let receiver = SharedReceiver::default();
+    receiver.lock().unwrap().status = 204;
+    let subscription = subscription(start(receiver.clone()).await, SECRET);
+    let delivery = delivery(&subscription);
+
+    let (status_code, error) = Sender::new().send(&subscription, &delivery).await;
+
+    assert_eq!(status_code, Some(204));
+    assert_eq!(error, None);
+    let receiver = receiver.lock().unwrap();
+    let (headers, body) = &receiver.received[0];
+    assert_eq!(headers[EVENT_HEADER], "shift.started");
+    assert_eq!(headers[DELIVERY_HEADER], delivery.delivery_id.as_str());
+    assert_eq!(*body, delivery.payload);
+}
+
+#[tokio::test]
+async fn rejected_with_wrong_secret() {
+    let receiver = SharedReceiver::default();
+    receiver.lock().unwrap().status = 204;
+    let subscription = subscription(start(receiver.clone()).await, "other");
+
+    let (status_code, _) = Sender::new()
+        .send(&subscription, &delivery(&subscription))
+        .await;
+
+    assert_eq!(status_code, Some(401));
+    assert!(receiver.lock().unwrap().received.is_empty());
+}
+
+#[tokio::test]
+async fn reports_unreachable_receiver() {
+    let subscription = subscription("http://127.0.0.1:1/hook".to_string(), SECRET);
+
+    let (status_code, error) = Sender::new()
+        .send(&subscription, &delivery(&subscription))
+        .await;
+
+    assert_eq!(status_code, None);
+    assert!(error.is_some());
+}